@@ -0,0 +1,153 @@
+//! Measures `list_workouts` latency when several callers query at once,
+//! comparing the single writer mutex (how every command used to run) with the
+//! pool of read-only services. A background thread keeps logging sets during
+//! each run to show reads and writes no longer queue behind each other.
+//!
+//! Run with `cargo bench --bench concurrent_list_workouts`.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{Duration as ChronoDuration, Utc};
+use task_athlete_gui_lib::state::{ServicePool, DEFAULT_READER_COUNT};
+use task_athlete_lib::{AddWorkoutParams, AppService, ExerciseType, WorkoutFilters};
+
+const EXERCISE: &str = "Bench Press";
+const SEED_WORKOUTS: i64 = 5_000;
+const THREADS: usize = 8;
+const QUERIES_PER_THREAD: usize = 40;
+
+fn bench_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("task-athlete-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create bench directory");
+    dir
+}
+
+fn add_set(service: &mut AppService, days_ago: i64) {
+    service
+        .add_workout(AddWorkoutParams {
+            exercise_identifier: EXERCISE,
+            date: Utc::now() - ChronoDuration::days(days_ago),
+            sets: Some(1),
+            reps: Some(5),
+            weight: Some(100.0),
+            duration: None,
+            distance: None,
+            notes: None,
+            implicit_type: None,
+            implicit_muscles: None,
+            bodyweight_to_use: None,
+        })
+        .expect("add workout");
+}
+
+fn seeded_pool(dir: &PathBuf, reader_count: usize) -> Arc<ServicePool> {
+    let mut service =
+        AppService::initialize_with_paths(&dir.join("config.toml"), &dir.join("workouts.sqlite"))
+            .expect("initialize service");
+    if service
+        .list_exercises(None, None)
+        .expect("list exercises")
+        .is_empty()
+    {
+        let ex_type = ExerciseType::try_from("resistance").expect("exercise type");
+        service
+            .create_exercise(EXERCISE, ex_type, None, Some("chest"))
+            .expect("create exercise");
+        for i in 0..SEED_WORKOUTS {
+            add_set(&mut service, i % 365);
+        }
+    }
+    Arc::new(ServicePool::new(service, reader_count).expect("open pool"))
+}
+
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[index]
+}
+
+fn run(label: &str, pool: Arc<ServicePool>, use_readers: bool) {
+    let stop = Arc::new(AtomicBool::new(false));
+    let writer = {
+        let pool = Arc::clone(&pool);
+        let stop = Arc::clone(&stop);
+        thread::spawn(move || {
+            let mut logged = 0u32;
+            let mut slowest = Duration::ZERO;
+            while !stop.load(Ordering::Relaxed) {
+                let started = Instant::now();
+                add_set(&mut pool.write().expect("write"), 0);
+                slowest = slowest.max(started.elapsed());
+                logged += 1;
+                thread::sleep(Duration::from_millis(5));
+            }
+            (logged, slowest)
+        })
+    };
+
+    let readers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let pool = Arc::clone(&pool);
+            thread::spawn(move || {
+                let filters = WorkoutFilters {
+                    exercise_name: Some(EXERCISE),
+                    date: None,
+                    exercise_type: None,
+                    muscle: None,
+                    limit: None,
+                };
+                (0..QUERIES_PER_THREAD)
+                    .map(|_| {
+                        let started = Instant::now();
+                        if use_readers {
+                            pool.read().expect("read").list_workouts(&filters)
+                        } else {
+                            pool.write().expect("write").list_workouts(&filters)
+                        }
+                        .expect("list workouts");
+                        started.elapsed()
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    let mut latencies: Vec<Duration> = readers
+        .into_iter()
+        .flat_map(|handle| handle.join().expect("reader thread"))
+        .collect();
+    stop.store(true, Ordering::Relaxed);
+    let (logged, slowest_write) = writer.join().expect("writer thread");
+
+    latencies.sort();
+    let total: Duration = latencies.iter().sum();
+    println!(
+        "{:<24} list_workouts x{:<4} mean {:>9.2?}  p50 {:>9.2?}  p95 {:>9.2?}  max {:>9.2?} | sets logged {:>4}, slowest {:>9.2?}",
+        label,
+        latencies.len(),
+        total / latencies.len() as u32,
+        percentile(&latencies, 0.50),
+        percentile(&latencies, 0.95),
+        latencies[latencies.len() - 1],
+        logged,
+        slowest_write,
+    );
+}
+
+fn main() {
+    let dir = bench_dir();
+    println!(
+        "{} threads, {} queries each, {} seeded workouts",
+        THREADS, QUERIES_PER_THREAD, SEED_WORKOUTS
+    );
+    run("single mutex", seeded_pool(&dir, 0), false);
+    run(
+        &format!("{} read-only handles", DEFAULT_READER_COUNT),
+        seeded_pool(&dir, DEFAULT_READER_COUNT),
+        true,
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
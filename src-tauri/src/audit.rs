@@ -0,0 +1,1024 @@
+//! Audit trail of changes to workouts, exercises, aliases, bodyweights and the
+//! config.
+//!
+//! Mutating commands capture the records they may touch, by id where they know
+//! it, and compare them once the change is done, so the log also covers what
+//! the lib changes on its own (a rename rewriting workouts, records arriving
+//! through sync). Entries
+//! live in a table of the profile's database: they move with the database and
+//! are not part of what sync sends.
+//!
+//! Each entry names where the change came from: this device's id for local
+//! changes, the server's URL for synced ones, since the server doesn't say
+//! which device made them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+
+use task_athlete_lib::{AppService, Config};
+
+use crate::events;
+use crate::exercise_details;
+use crate::records::{self, ExerciseSnapshot, WorkoutSnapshot};
+use crate::state::run_blocking;
+use crate::trash;
+use crate::undo::{self, Operation, UndoHistory};
+use crate::AppState;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS gui_audit_log (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        record_id TEXT NOT NULL,
+        origin TEXT NOT NULL,
+        old_value TEXT,
+        new_value TEXT,
+        device TEXT
+    );
+    CREATE INDEX IF NOT EXISTS idx_gui_audit_log_record
+        ON gui_audit_log (kind, record_id);
+";
+
+const DEFAULT_RECENT_LIMIT: u32 = 100;
+const DEVICE_ID_FILE_NAME: &str = "device_id";
+
+// Config fields that change as a side effect rather than by user choice.
+const UNTRACKED_CONFIG_FIELDS: [&str; 2] = ["db_path", "last_sync_timestamp"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordKind {
+    Workout,
+    Exercise,
+    Alias,
+    Bodyweight,
+    Config,
+}
+
+impl RecordKind {
+    pub const ALL: [RecordKind; 5] = [
+        RecordKind::Workout,
+        RecordKind::Exercise,
+        RecordKind::Alias,
+        RecordKind::Bodyweight,
+        RecordKind::Config,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            RecordKind::Workout => "workout",
+            RecordKind::Exercise => "exercise",
+            RecordKind::Alias => "alias",
+            RecordKind::Bodyweight => "bodyweight",
+            RecordKind::Config => "config",
+        }
+    }
+
+    fn parse(s: &str) -> Result<Self, String> {
+        RecordKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("Unknown record kind in audit log: {}", s))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Origin {
+    Local,
+    Sync,
+}
+
+impl Origin {
+    fn as_str(self) -> &'static str {
+        match self {
+            Origin::Local => "local",
+            Origin::Sync => "sync",
+        }
+    }
+
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "local" => Ok(Origin::Local),
+            "sync" => Ok(Origin::Sync),
+            _ => Err(format!("Unknown origin in audit log: {}", s)),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// One change to one record. `new_value` is the version a revert goes back to;
+/// it is `None` when the change deleted the record.
+#[derive(Serialize, Clone, Debug)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub kind: RecordKind,
+    pub record_id: String,
+    pub action: Action,
+    pub origin: Origin,
+    /// This device's id for a local change, the sync server's URL for a
+    /// synced one. `None` for entries logged before devices were recorded.
+    pub device: Option<String>,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+}
+
+/// Workout, exercise and bodyweight ids arrive as numbers, alias names as
+/// strings.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum RecordKey {
+    Id(i64),
+    Name(String),
+}
+
+impl RecordKey {
+    fn into_string(self) -> String {
+        match self {
+            RecordKey::Id(id) => id.to_string(),
+            RecordKey::Name(name) => name,
+        }
+    }
+}
+
+pub fn ensure_schema(service: &AppService) -> Result<(), String> {
+    service
+        .conn
+        .execute_batch(SCHEMA)
+        .map_err(|e| format!("Failed to create audit log table: {}", e))?;
+    // Tables created before devices were recorded lack the column.
+    let has_device: i64 = service
+        .conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('gui_audit_log') WHERE name = 'device'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if has_device == 0 {
+        service
+            .conn
+            .execute_batch("ALTER TABLE gui_audit_log ADD COLUMN device TEXT")
+            .map_err(|e| format!("Failed to upgrade audit log table: {}", e))?;
+    }
+    Ok(())
+}
+
+static DEVICE_ID: OnceLock<String> = OnceLock::new();
+
+/// Reads this device's id from the app data directory, which no profile or
+/// sync shares, creating it on first run. Local changes are logged under it.
+pub fn load_device_id(app: &tauri::AppHandle) -> Result<(), String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to find the app data directory: {}", e))?;
+    let path = dir.join(DEVICE_ID_FILE_NAME);
+    let id = match fs::read_to_string(&path) {
+        Ok(id) if !id.trim().is_empty() => id.trim().to_string(),
+        _ => {
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or_default();
+            let id = format!("{:x}-{:x}", nanos, std::process::id());
+            fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+            fs::write(&path, &id).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+            id
+        }
+    };
+    let _ = DEVICE_ID.set(id);
+    Ok(())
+}
+
+// --- Capturing changes ---
+
+/// What a change may touch. Workouts and exercises are named, so only those
+/// rows are read and compared; aliases, bodyweights and the config are small
+/// and read whole.
+pub enum Scope<'a> {
+    Workouts(&'a [i64]),
+    /// Exercises by id, name or alias. One that doesn't exist yet is looked up
+    /// again on commit, so a change can name the exercise it creates.
+    Exercises(&'a [String]),
+    /// Every workout of the exercises, e.g. for a rename that rewrites them.
+    WorkoutsOf(&'a [String]),
+    Aliases,
+    Bodyweights,
+    Config,
+    /// Every record. Only for sync, which can change any of them.
+    All,
+}
+
+// Kept typed so the comparison doesn't serialize records that didn't change.
+#[derive(PartialEq)]
+enum Record {
+    Workout(WorkoutSnapshot),
+    Exercise(ExerciseSnapshot),
+    Value(Value),
+}
+
+impl Record {
+    fn into_value(self) -> Result<Value, String> {
+        match self {
+            Record::Workout(snapshot) => to_value(&snapshot),
+            Record::Exercise(snapshot) => to_value(&snapshot),
+            Record::Value(value) => Ok(value),
+        }
+    }
+}
+
+type Records = BTreeMap<String, Record>;
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Failed to serialize record: {}", e))
+}
+
+fn config_value(config: &Config) -> Result<Value, String> {
+    let mut value = to_value(config)?;
+    if let Some(fields) = value.as_object_mut() {
+        for field in UNTRACKED_CONFIG_FIELDS {
+            fields.remove(field);
+        }
+    }
+    Ok(value)
+}
+
+// The records of `kind`, only those in `ids` where given.
+fn current(
+    service: &mut AppService,
+    kind: RecordKind,
+    ids: Option<&BTreeSet<i64>>,
+) -> Result<Records, String> {
+    match kind {
+        RecordKind::Workout => {
+            let workouts = match ids {
+                Some(ids) => {
+                    let ids: Vec<i64> = ids.iter().copied().collect();
+                    records::find_workouts(service, &ids)?
+                }
+                None => records::all_workouts(service)?,
+            };
+            Ok(workouts
+                .iter()
+                .map(|w| (w.id.to_string(), Record::Workout(WorkoutSnapshot::from(w))))
+                .collect())
+        }
+        RecordKind::Exercise => {
+            if ids.is_some_and(BTreeSet::is_empty) {
+                return Ok(Records::new());
+            }
+            let mut details = match ids {
+                Some(ids) => ids
+                    .iter()
+                    .map(|&id| Ok((id, exercise_details::load(service, id)?)))
+                    .collect::<Result<HashMap<_, _>, String>>()?,
+                None => exercise_details::load_all(service)?,
+            };
+            Ok(service
+                .list_exercises(None, None)
+                .map_err(|e| e.to_string())?
+                .iter()
+                .filter(|def| ids.map_or(true, |ids| ids.contains(&def.id)))
+                .map(|def| {
+                    let details = details.remove(&def.id).unwrap_or_default();
+                    let snapshot = ExerciseSnapshot::new(def, Vec::new(), details);
+                    (def.id.to_string(), Record::Exercise(snapshot))
+                })
+                .collect())
+        }
+        RecordKind::Alias => Ok(service
+            .list_aliases()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            // Carriers only pass through on their way into the details table.
+            .filter(|(alias, _)| !exercise_details::is_carrier(alias))
+            .map(|(alias, exercise)| (alias, Record::Value(json!({ "exercise": exercise }))))
+            .collect()),
+        RecordKind::Bodyweight => Ok(service
+            .list_bodyweights(u32::MAX)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|(id, timestamp, weight)| {
+                let value = json!({ "timestamp": timestamp, "weight": weight });
+                (id.to_string(), Record::Value(value))
+            })
+            .collect()),
+        RecordKind::Config => {
+            let value = config_value(&service.config)?;
+            Ok(BTreeMap::from([(
+                "config".to_string(),
+                Record::Value(value),
+            )]))
+        }
+    }
+}
+
+/// The records a change may touch, as they were before it. Call `commit` once
+/// the change succeeded to log the differences.
+///
+/// Audit failures are logged and never fail the change itself.
+#[derive(Default)]
+pub struct Capture {
+    all: bool,
+    kinds: BTreeSet<RecordKind>,
+    workout_ids: BTreeSet<i64>,
+    exercise_ids: BTreeSet<i64>,
+    // Exercise identifiers that didn't resolve yet.
+    pending_exercises: Vec<String>,
+    before: Vec<(RecordKind, Records)>,
+}
+
+impl Capture {
+    pub fn take(service: &mut AppService, scopes: &[Scope]) -> Self {
+        let mut capture = Capture::default();
+        for scope in scopes {
+            match scope {
+                Scope::Workouts(ids) => capture.workout_ids.extend(ids.iter().copied()),
+                Scope::Exercises(identifiers) => capture
+                    .pending_exercises
+                    .extend(identifiers.iter().cloned()),
+                Scope::WorkoutsOf(identifiers) => {
+                    for identifier in identifiers.iter() {
+                        match records::resolve_exercise(service, identifier)
+                            .and_then(|def| records::workouts_for_exercise(service, &def.name))
+                        {
+                            Ok(workouts) => {
+                                capture.workout_ids.extend(workouts.iter().map(|w| w.id))
+                            }
+                            Err(e) => eprintln!(
+                                "Audit log: failed to read workouts of {}: {}",
+                                identifier, e
+                            ),
+                        }
+                    }
+                }
+                Scope::Aliases => {
+                    capture.kinds.insert(RecordKind::Alias);
+                }
+                Scope::Bodyweights => {
+                    capture.kinds.insert(RecordKind::Bodyweight);
+                }
+                Scope::Config => {
+                    capture.kinds.insert(RecordKind::Config);
+                }
+                Scope::All => {
+                    capture.all = true;
+                    capture.kinds.extend(RecordKind::ALL);
+                }
+            }
+        }
+        capture.kinds.insert(RecordKind::Workout);
+        capture.kinds.insert(RecordKind::Exercise);
+        capture.resolve_pending(service);
+        let kinds: Vec<RecordKind> = capture.kinds.iter().copied().collect();
+        capture.before = kinds
+            .into_iter()
+            .filter_map(|kind| match capture.read(service, kind) {
+                Ok(records) => Some((kind, records)),
+                Err(e) => {
+                    eprintln!("Audit log: failed to read {}s: {}", kind.as_str(), e);
+                    None
+                }
+            })
+            .collect();
+        capture
+    }
+
+    /// Also logs these workouts, e.g. ones the change created.
+    pub fn include_workouts(&mut self, ids: &[i64]) {
+        self.workout_ids.extend(ids.iter().copied());
+    }
+
+    /// Also logs these exercises (id, name or alias), e.g. ones the change
+    /// created.
+    pub fn include_exercises(&mut self, identifiers: &[String]) {
+        self.pending_exercises.extend(identifiers.iter().cloned());
+    }
+
+    fn resolve_pending(&mut self, service: &AppService) {
+        if self.pending_exercises.is_empty() {
+            return;
+        }
+        let lists = service
+            .list_exercises(None, None)
+            .map_err(|e| e.to_string())
+            .and_then(|defs| Ok((defs, service.list_aliases().map_err(|e| e.to_string())?)));
+        let (definitions, aliases) = match lists {
+            Ok(lists) => lists,
+            Err(e) => {
+                eprintln!("Audit log: failed to read exercises: {}", e);
+                return;
+            }
+        };
+        let exercise_ids = &mut self.exercise_ids;
+        self.pending_exercises.retain(|identifier| {
+            match records::find_exercise_id(&definitions, &aliases, identifier) {
+                Some(id) => {
+                    exercise_ids.insert(id);
+                    false
+                }
+                None => true,
+            }
+        });
+    }
+
+    fn read(&self, service: &mut AppService, kind: RecordKind) -> Result<Records, String> {
+        let ids = match kind {
+            _ if self.all => None,
+            RecordKind::Workout => Some(&self.workout_ids),
+            RecordKind::Exercise => Some(&self.exercise_ids),
+            _ => None,
+        };
+        current(service, kind, ids)
+    }
+
+    /// Logs what changed, under this device's id for local changes.
+    pub fn commit(self, service: &mut AppService, origin: Origin) {
+        let device = match origin {
+            Origin::Local => DEVICE_ID.get().map(String::as_str),
+            Origin::Sync => None,
+        };
+        self.commit_from(service, origin, device);
+    }
+
+    /// Logs what a sync with `server_url` changed.
+    pub fn commit_synced(self, service: &mut AppService, server_url: &str) {
+        self.commit_from(service, Origin::Sync, Some(server_url));
+    }
+
+    fn commit_from(mut self, service: &mut AppService, origin: Origin, device: Option<&str>) {
+        self.resolve_pending(service);
+        if let Err(e) = self.try_commit(service, origin, device) {
+            eprintln!("Audit log: failed to record changes: {}", e);
+        }
+    }
+
+    fn try_commit(
+        mut self,
+        service: &mut AppService,
+        origin: Origin,
+        device: Option<&str>,
+    ) -> Result<(), String> {
+        let timestamp = Utc::now().to_rfc3339();
+        let mut changes = Vec::new();
+        for (kind, mut before) in std::mem::take(&mut self.before) {
+            let mut after = self.read(service, kind)?;
+            let keys: BTreeSet<String> = before.keys().chain(after.keys()).cloned().collect();
+            for key in keys {
+                let old = before.remove(&key);
+                let new = after.remove(&key);
+                if old == new {
+                    continue;
+                }
+                let old = old.map(Record::into_value).transpose()?;
+                let new = new.map(Record::into_value).transpose()?;
+                changes.push((kind, key, old, new));
+            }
+        }
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let tx = service
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| e.to_string())?;
+        for (kind, record_id, old, new) in changes {
+            tx.execute(
+                "INSERT INTO gui_audit_log
+                    (timestamp, kind, record_id, origin, device, old_value, new_value)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                (
+                    &timestamp,
+                    kind.as_str(),
+                    &record_id,
+                    origin.as_str(),
+                    device,
+                    old.map(|v| v.to_string()),
+                    new.map(|v| v.to_string()),
+                ),
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+}
+
+// --- Reading the log ---
+
+#[derive(Default)]
+struct EntryQuery {
+    id: Option<i64>,
+    kind: Option<RecordKind>,
+    record_id: Option<String>,
+    origin: Option<Origin>,
+    limit: Option<u32>,
+}
+
+type RawEntry = (
+    i64,
+    String,
+    String,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+fn parse_value(raw: Option<String>) -> Result<Option<Value>, String> {
+    raw.map(|s| serde_json::from_str(&s))
+        .transpose()
+        .map_err(|e| format!("Corrupt value in audit log: {}", e))
+}
+
+fn entry_from_raw(raw: RawEntry) -> Result<AuditEntry, String> {
+    let (id, timestamp, kind, record_id, origin, device, old_value, new_value) = raw;
+    let old_value = parse_value(old_value)?;
+    let new_value = parse_value(new_value)?;
+    let action = match (&old_value, &new_value) {
+        (None, _) => Action::Created,
+        (_, None) => Action::Deleted,
+        _ => Action::Updated,
+    };
+    Ok(AuditEntry {
+        id,
+        timestamp: DateTime::parse_from_rfc3339(&timestamp)
+            .map_err(|e| format!("Corrupt timestamp in audit log: {}", e))?
+            .with_timezone(&Utc),
+        kind: RecordKind::parse(&kind)?,
+        record_id,
+        action,
+        origin: Origin::parse(&origin)?,
+        device,
+        old_value,
+        new_value,
+    })
+}
+
+/// Matching entries, newest first.
+fn query_entries(service: &AppService, query: EntryQuery) -> Result<Vec<AuditEntry>, String> {
+    let mut stmt = service
+        .conn
+        .prepare(
+            "SELECT id, timestamp, kind, record_id, origin, device, old_value, new_value
+             FROM gui_audit_log
+             WHERE (?1 IS NULL OR id = ?1)
+               AND (?2 IS NULL OR kind = ?2)
+               AND (?3 IS NULL OR record_id = ?3)
+               AND (?4 IS NULL OR origin = ?4)
+             ORDER BY id DESC
+             LIMIT ?5",
+        )
+        .map_err(|e| e.to_string())?;
+    // SQLite treats a negative LIMIT as no limit.
+    let limit = query.limit.map_or(-1, i64::from);
+    let rows = stmt
+        .query_map(
+            (
+                query.id,
+                query.kind.map(RecordKind::as_str),
+                query.record_id,
+                query.origin.map(Origin::as_str),
+                limit,
+            ),
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                    row.get(7)?,
+                ))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    rows.map(|row| entry_from_raw(row.map_err(|e| e.to_string())?))
+        .collect()
+}
+
+// --- Reverting ---
+
+fn parse_id(record_id: &str) -> Result<i64, String> {
+    record_id
+        .parse()
+        .map_err(|_| format!("Invalid record id in audit log: {}", record_id))
+}
+
+fn from_value<T: serde::de::DeserializeOwned>(value: Value) -> Result<T, String> {
+    serde_json::from_value(value).map_err(|e| format!("Corrupt value in audit log: {}", e))
+}
+
+// Returns the id the workout has afterwards, new if it was re-added.
+fn revert_workout(
+    service: &mut AppService,
+    history: &mut UndoHistory,
+    id: i64,
+    target: Option<Value>,
+) -> Result<i64, String> {
+    let existing = records::find_workouts(service, &[id])?.pop();
+    match (existing, target) {
+        (Some(workout), Some(value)) => {
+            let before = WorkoutSnapshot::from(&workout);
+            records::overwrite_workout(service, id, &from_value(value)?)?;
+            let after = WorkoutSnapshot::from(&records::find_workout(service, id)?);
+            history.record(Operation::EditedWorkout { id, before, after });
+        }
+        (None, Some(value)) => {
+            let new_id = records::readd_workout(service, &from_value(value)?)?;
+            let snapshot = WorkoutSnapshot::from(&records::find_workout(service, new_id)?);
+            history.record(Operation::AddedWorkout {
+                id: new_id,
+                snapshot,
+            });
+            return Ok(new_id);
+        }
+        (Some(_), None) => {
+            let (_, entry_id) = trash::trash_workouts(service, &[id])?;
+            history.record(Operation::TrashedWorkouts { entry_id });
+        }
+        (None, None) => return Err(format!("Workout {} is already deleted", id)),
+    }
+    Ok(id)
+}
+
+// Returns the exercise's name afterwards, or before if it was deleted.
+fn revert_exercise(
+    service: &mut AppService,
+    history: &mut UndoHistory,
+    id: i64,
+    target: Option<Value>,
+) -> Result<String, String> {
+    let existing = service
+        .list_exercises(None, None)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|def| def.id == id);
+    match (existing, target) {
+        (Some(definition), Some(value)) => {
+            let snapshot: ExerciseSnapshot = from_value(value)?;
+            let new_name = (snapshot.name != definition.name).then_some(snapshot.name.as_str());
+            let log_flags = Some((
+                Some(snapshot.log_weight),
+                Some(snapshot.log_reps),
+                Some(snapshot.log_duration),
+                Some(snapshot.log_distance),
+            ));
+            service
+                .edit_exercise(
+                    &definition.name,
+                    new_name,
+                    Some(crate::parse_exercise_type(&snapshot.exercise_type)?),
+                    log_flags,
+                    Some(snapshot.muscles.as_deref()),
+                )
+                .map_err(|e| e.to_string())?;
+            exercise_details::save(service, id, &snapshot.details)?;
+            if snapshot.archived == definition.is_active {
+                records::set_archived(
+                    service,
+                    id,
+                    &snapshot.name,
+                    snapshot.muscles.as_deref(),
+                    snapshot.archived,
+                )?;
+            }
+            Ok(snapshot.name)
+        }
+        (None, Some(value)) => {
+            let snapshot: ExerciseSnapshot = from_value(value)?;
+            records::recreate_exercise(service, &snapshot)?;
+            Ok(snapshot.name)
+        }
+        (Some(definition), None) => {
+            let (_, entry_id) = trash::trash_exercises(service, &[definition.name.clone()])?;
+            history.record(Operation::TrashedExercises { entry_id });
+            Ok(definition.name)
+        }
+        (None, None) => Err(format!("Exercise {} is already deleted", id)),
+    }
+}
+
+fn revert_alias(service: &AppService, alias: &str, target: Option<Value>) -> Result<(), String> {
+    let exists = service
+        .list_aliases()
+        .map_err(|e| e.to_string())?
+        .contains_key(alias);
+    if exists {
+        service.delete_alias(alias).map_err(|e| e.to_string())?;
+    }
+    if let Some(value) = target {
+        let exercise = value
+            .get("exercise")
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("Corrupt alias value in audit log for '{}'", alias))?;
+        service
+            .create_alias(alias, exercise)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// The lib can only add bodyweight entries, so only a missing entry can be
+// brought back.
+fn revert_bodyweight(
+    service: &mut AppService,
+    id: i64,
+    target: Option<Value>,
+) -> Result<(), String> {
+    let value = target.ok_or("Bodyweight entries can't be deleted")?;
+    let exists = service
+        .list_bodyweights(u32::MAX)
+        .map_err(|e| e.to_string())?
+        .iter()
+        .any(|(entry_id, _, _)| *entry_id == id);
+    if exists {
+        return Err("Bodyweight entries can't be edited".to_string());
+    }
+    let timestamp: DateTime<Utc> = from_value(value["timestamp"].clone())?;
+    let weight: f64 = from_value(value["weight"].clone())?;
+    service
+        .add_bodyweight_entry(timestamp, weight)
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn revert_config(service: &mut AppService, target: Option<Value>) -> Result<(), String> {
+    let Some(Value::Object(fields)) = target else {
+        return Err("Corrupt config value in audit log".to_string());
+    };
+    let mut merged = to_value(&service.config)?;
+    for (field, value) in fields {
+        if !UNTRACKED_CONFIG_FIELDS.contains(&field.as_str()) {
+            merged[field] = value;
+        }
+    }
+    service.config = from_value(merged)?;
+    service.save_config().map_err(|e| e.to_string())
+}
+
+fn emit_reverted(app: &tauri::AppHandle, kind: RecordKind) {
+    match kind {
+        RecordKind::Workout => events::workouts_changed(app, Vec::new(), Vec::new()),
+        RecordKind::Exercise => {
+            events::exercises_changed(app);
+            events::workouts_changed(app, Vec::new(), Vec::new());
+        }
+        RecordKind::Alias => events::exercises_changed(app),
+        RecordKind::Bodyweight => events::bodyweight_changed(app, Vec::new()),
+        RecordKind::Config => events::config_changed(app),
+    }
+}
+
+// --- Tauri Commands ---
+
+/// Every logged change to one record, newest first.
+#[tauri::command]
+pub async fn get_record_history(
+    kind: RecordKind,
+    id: RecordKey,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<AuditEntry>, String> {
+    run_blocking(&state, move |pool| {
+        let service = pool.read()?;
+        query_entries(
+            &service,
+            EntryQuery {
+                kind: Some(kind),
+                record_id: Some(id.into_string()),
+                ..EntryQuery::default()
+            },
+        )
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn list_recent_changes(
+    limit: Option<u32>,
+    kind: Option<RecordKind>,
+    origin: Option<Origin>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<AuditEntry>, String> {
+    run_blocking(&state, move |pool| {
+        let service = pool.read()?;
+        query_entries(
+            &service,
+            EntryQuery {
+                kind,
+                origin,
+                limit: Some(limit.unwrap_or(DEFAULT_RECENT_LIMIT)),
+                ..EntryQuery::default()
+            },
+        )
+    })
+    .await
+}
+
+/// Puts a record back to the version written by audit entry `entry_id` and
+/// logs that as a local change. Returns the kind of record reverted.
+fn revert_entry(
+    service: &mut AppService,
+    history: &mut UndoHistory,
+    entry_id: i64,
+) -> Result<RecordKind, String> {
+    let entry = query_entries(
+        service,
+        EntryQuery {
+            id: Some(entry_id),
+            ..EntryQuery::default()
+        },
+    )?
+    .pop()
+    .ok_or_else(|| format!("Audit entry {} not found", entry_id))?;
+
+    // What the revert may touch, directly or through the lib.
+    let record_id = [entry.record_id.clone()];
+    let workout_ids = match entry.kind {
+        RecordKind::Workout => vec![parse_id(&entry.record_id)?],
+        _ => Vec::new(),
+    };
+    // Re-adding a workout can re-create its exercise.
+    let workout_exercise: Vec<String> = entry
+        .new_value
+        .as_ref()
+        .and_then(|value| value.get("exercise_name"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .into_iter()
+        .collect();
+    let scopes = match entry.kind {
+        RecordKind::Workout => vec![
+            Scope::Workouts(&workout_ids),
+            Scope::Exercises(&workout_exercise),
+        ],
+        RecordKind::Exercise => vec![
+            Scope::Exercises(&record_id),
+            Scope::WorkoutsOf(&record_id),
+            Scope::Aliases,
+        ],
+        RecordKind::Alias => vec![Scope::Aliases],
+        RecordKind::Bodyweight => vec![Scope::Bodyweights],
+        RecordKind::Config => vec![Scope::Config],
+    };
+    let mut capture = Capture::take(service, &scopes);
+    let target = entry.new_value;
+    match entry.kind {
+        RecordKind::Workout => {
+            let id = revert_workout(service, history, parse_id(&entry.record_id)?, target)?;
+            capture.include_workouts(&[id]);
+        }
+        RecordKind::Exercise => {
+            let name = revert_exercise(service, history, parse_id(&entry.record_id)?, target)?;
+            capture.include_exercises(&[name]);
+        }
+        RecordKind::Alias => revert_alias(service, &entry.record_id, target)?,
+        RecordKind::Bodyweight => revert_bodyweight(service, parse_id(&entry.record_id)?, target)?,
+        RecordKind::Config => revert_config(service, target)?,
+    }
+    capture.commit(service, Origin::Local);
+    Ok(entry.kind)
+}
+
+/// Puts a record back to the version written by audit entry `entry_id`. The
+/// revert is itself logged as a local change and, for workouts and exercise
+/// deletions, can be undone.
+#[tauri::command]
+pub async fn revert_record(
+    entry_id: i64,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        let mut history = undo::lock(&app)?;
+        let mut service = pool.write()?;
+        let kind = revert_entry(&mut service, &mut history, entry_id)?;
+        drop(service);
+        emit_reverted(&app, kind);
+        Ok(())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn temp_service(name: &str) -> AppService {
+        let dir =
+            std::env::temp_dir().join(format!("task-athlete-gui-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let service = AppService::initialize_with_paths(
+            &dir.join("config.toml"),
+            &dir.join("workouts.sqlite"),
+        )
+        .unwrap();
+        ensure_schema(&service).unwrap();
+        exercise_details::ensure_schema(&service).unwrap();
+        service
+    }
+
+    fn squat(reps: i64) -> WorkoutSnapshot {
+        WorkoutSnapshot {
+            id: 0,
+            timestamp: Utc.with_ymd_and_hms(2024, 5, 10, 8, 0, 0).unwrap(),
+            exercise_name: "Squat".to_string(),
+            exercise_type: Some("resistance".to_string()),
+            sets: Some(3),
+            reps: Some(reps),
+            weight: Some(100.0),
+            duration_minutes: None,
+            distance: None,
+            bodyweight: None,
+            notes: None,
+        }
+    }
+
+    fn history_of(service: &AppService, id: i64) -> Vec<AuditEntry> {
+        query_entries(
+            service,
+            EntryQuery {
+                kind: Some(RecordKind::Workout),
+                record_id: Some(id.to_string()),
+                ..EntryQuery::default()
+            },
+        )
+        .unwrap()
+    }
+
+    fn reps_in(value: &Option<Value>) -> Option<i64> {
+        value.as_ref()?.get("reps")?.as_i64()
+    }
+
+    #[test]
+    fn commit_logs_only_what_changed() {
+        let mut service = temp_service("audit-commit");
+        let other = records::add_snapshot(&mut service, &squat(5)).unwrap().0;
+
+        let mut capture = Capture::take(&mut service, &[Scope::Workouts(&[])]);
+        let id = records::add_snapshot(&mut service, &squat(5)).unwrap().0;
+        capture.include_workouts(&[id]);
+        capture.commit(&mut service, Origin::Local);
+
+        let capture = Capture::take(&mut service, &[Scope::Workouts(&[id, other])]);
+        records::overwrite_workout(&service, id, &squat(8)).unwrap();
+        capture.commit_synced(&mut service, "https://sync.example");
+
+        let entries = history_of(&service, id);
+        assert_eq!(entries.len(), 2);
+        let (edit, create) = (&entries[0], &entries[1]);
+        assert!(matches!(edit.action, Action::Updated));
+        assert_eq!(edit.origin, Origin::Sync);
+        assert_eq!(edit.device.as_deref(), Some("https://sync.example"));
+        assert_eq!(reps_in(&edit.old_value), Some(5));
+        assert_eq!(reps_in(&edit.new_value), Some(8));
+        assert!(matches!(create.action, Action::Created));
+        assert_eq!(create.origin, Origin::Local);
+        assert!(history_of(&service, other).is_empty());
+    }
+
+    #[test]
+    fn revert_puts_back_the_logged_version() {
+        let mut service = temp_service("audit-revert");
+        let mut capture = Capture::take(&mut service, &[Scope::Workouts(&[])]);
+        let id = records::add_snapshot(&mut service, &squat(5)).unwrap().0;
+        capture.include_workouts(&[id]);
+        capture.commit(&mut service, Origin::Local);
+        let capture = Capture::take(&mut service, &[Scope::Workouts(&[id])]);
+        records::overwrite_workout(&service, id, &squat(8)).unwrap();
+        capture.commit(&mut service, Origin::Local);
+
+        let created = history_of(&service, id).pop().unwrap();
+        let mut history = UndoHistory::default();
+        let kind = revert_entry(&mut service, &mut history, created.id).unwrap();
+
+        assert_eq!(kind, RecordKind::Workout);
+        assert_eq!(records::find_workout(&service, id).unwrap().reps, Some(5));
+        let entries = history_of(&service, id);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(reps_in(&entries[0].old_value), Some(8));
+        assert_eq!(reps_in(&entries[0].new_value), Some(5));
+        assert!(revert_entry(&mut service, &mut history, 999).is_err());
+    }
+}
@@ -0,0 +1,375 @@
+//! Edits and deletes applied to many workouts at once, picked either by id or
+//! by the same filters `list_workouts` takes.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use task_athlete_lib::{AppService, EditWorkoutParams, Workout};
+
+use crate::audit::{Capture, Origin, Scope};
+use crate::events;
+use crate::records::{self, WorkoutSnapshot};
+use crate::state::run_blocking;
+use crate::timezone;
+use crate::trash;
+use crate::undo::{self, Operation};
+use crate::{list_workouts_in, parse_naive_date, AppState, WorkoutFiltersCmdParams};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkoutSelection {
+    Ids(Vec<i64>),
+    Filters(WorkoutFiltersCmdParams),
+}
+
+/// Changes applied to every selected workout. The `new_*` fields work like
+/// `edit_workout`'s and set the same value everywhere; the relative fields
+/// adjust each workout's own value.
+#[derive(Deserialize, Default)]
+pub struct WorkoutPatch {
+    pub new_exercise_identifier: Option<String>,
+    pub new_sets: Option<i64>,
+    pub new_reps: Option<i64>,
+    pub new_weight: Option<f64>,
+    pub new_bodyweight: Option<f64>,
+    pub new_duration: Option<i64>,
+    pub new_distance_arg: Option<f64>,
+    pub new_notes: Option<String>,
+    pub new_date: Option<String>,
+    /// Moves each workout this many days (negative moves it back). Like
+    /// `new_date`, it keeps each workout's local time of day.
+    pub shift_days: Option<i64>,
+    /// Multiplies each workout's weight, e.g. 2.2046 to turn kg into lb.
+    pub weight_factor: Option<f64>,
+    pub distance_factor: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct BulkEditPreview {
+    pub count: usize,
+    pub ids: Vec<i64>,
+}
+
+struct CheckedPatch {
+    patch: WorkoutPatch,
+    new_date: Option<NaiveDate>,
+}
+
+impl WorkoutPatch {
+    fn check(self) -> Result<CheckedPatch, String> {
+        if self.new_date.is_some() && self.shift_days.is_some() {
+            return Err("Set either a new date or a day shift, not both".to_string());
+        }
+        if self.new_weight.is_some() && self.weight_factor.is_some() {
+            return Err("Set either a new weight or a weight factor, not both".to_string());
+        }
+        if self.new_distance_arg.is_some() && self.distance_factor.is_some() {
+            return Err("Set either a new distance or a distance factor, not both".to_string());
+        }
+        for factor in [self.weight_factor, self.distance_factor]
+            .into_iter()
+            .flatten()
+        {
+            if !factor.is_finite() || factor <= 0.0 {
+                return Err(format!("Invalid factor: {}", factor));
+            }
+        }
+        let new_date = self.new_date.as_deref().map(parse_naive_date).transpose()?;
+        Ok(CheckedPatch {
+            patch: self,
+            new_date,
+        })
+    }
+}
+
+impl CheckedPatch {
+    /// Edit parameters for one workout, plus its new timestamp if it moves.
+    fn params_for(&self, workout: &Workout, tz: Tz) -> (EditWorkoutParams, Option<DateTime<Utc>>) {
+        let patch = &self.patch;
+        let new_date = match patch.shift_days {
+            Some(days) => Some(timezone::local_date(tz, workout.timestamp) + Duration::days(days)),
+            None => self.new_date,
+        };
+        let new_timestamp =
+            new_date.map(|date| timezone::on_local_date(tz, workout.timestamp, date));
+        let new_weight = match patch.weight_factor {
+            Some(factor) => workout.weight.map(|w| w * factor),
+            None => patch.new_weight,
+        };
+        // The lib reads this in the user's units; a scaled km value is
+        // stored over it afterwards, see `scaled_distance`.
+        let new_distance_arg = match patch.distance_factor {
+            Some(_) => self.scaled_distance(workout),
+            None => patch.new_distance_arg,
+        };
+        let params = EditWorkoutParams {
+            id: workout.id,
+            new_exercise_identifier: patch.new_exercise_identifier.clone(),
+            new_sets: patch.new_sets,
+            new_reps: patch.new_reps,
+            new_weight,
+            new_bodyweight: patch.new_bodyweight,
+            new_duration: patch.new_duration,
+            new_distance_arg,
+            new_notes: patch.new_notes.clone(),
+            new_date: None,
+        };
+        (params, new_timestamp)
+    }
+
+    /// The workout's distance in km after scaling, if the patch scales it.
+    fn scaled_distance(&self, workout: &Workout) -> Option<f64> {
+        let factor = self.patch.distance_factor?;
+        workout.distance.map(|d| d * factor)
+    }
+}
+
+fn select_workouts(
+    service: &AppService,
+    selection: &WorkoutSelection,
+    tz: Tz,
+) -> Result<Vec<Workout>, String> {
+    match selection {
+        WorkoutSelection::Ids(ids) => records::find_workouts(service, ids),
+        WorkoutSelection::Filters(filters) => list_workouts_in(service, filters, tz),
+    }
+}
+
+// One undoable edit per workout found in both lists.
+fn edit_ops(before: &[Workout], after: &[Workout]) -> Vec<Operation> {
+    before
+        .iter()
+        .filter_map(|old| {
+            let new = after.iter().find(|w| w.id == old.id)?;
+            Some(Operation::EditedWorkout {
+                id: old.id,
+                before: WorkoutSnapshot::from(old),
+                after: WorkoutSnapshot::from(new),
+            })
+        })
+        .collect()
+}
+
+/// Runs `edit` on each workout inside one savepoint: if any of them fails,
+/// none of the edits are kept.
+fn edit_each(
+    service: &AppService,
+    workouts: &[Workout],
+    mut edit: impl FnMut(&AppService, &Workout) -> Result<(), String>,
+) -> Result<(), String> {
+    records::in_savepoint(service, "bulk_edit", |service| {
+        for workout in workouts {
+            edit(service, workout)
+                .map_err(|e| format!("Failed to edit workout {}: {}", workout.id, e))?;
+        }
+        Ok(())
+    })
+}
+
+/// The workouts' current timestamps handed out in the order of `ids`, nudged
+/// forward a second where needed so the order is strict.
+fn reordered_timestamps(workouts: &[Workout], ids: &[i64]) -> Vec<(i64, DateTime<Utc>)> {
+    let mut timestamps: Vec<DateTime<Utc>> = workouts.iter().map(|w| w.timestamp).collect();
+    timestamps.sort();
+    let mut assigned: Vec<(i64, DateTime<Utc>)> = Vec::with_capacity(ids.len());
+    for (&id, timestamp) in ids.iter().zip(timestamps) {
+        let timestamp = match assigned.last() {
+            Some(&(_, previous)) if timestamp <= previous => previous + Duration::seconds(1),
+            _ => timestamp,
+        };
+        assigned.push((id, timestamp));
+    }
+    assigned
+}
+
+// --- Tauri Commands ---
+
+/// Which workouts a bulk edit would touch, without changing anything. Also
+/// rejects a patch that `bulk_edit_workouts` would reject.
+#[tauri::command]
+pub async fn preview_bulk_edit(
+    selection: WorkoutSelection,
+    patch: Option<WorkoutPatch>,
+    state: tauri::State<'_, AppState>,
+) -> Result<BulkEditPreview, String> {
+    run_blocking(&state, move |pool| {
+        if let Some(patch) = patch {
+            patch.check()?;
+        }
+        let service = pool.read()?;
+        let tz = timezone::user_time_zone(&service);
+        let ids: Vec<i64> = select_workouts(&service, &selection, tz)?
+            .iter()
+            .map(|w| w.id)
+            .collect();
+        Ok(BulkEditPreview {
+            count: ids.len(),
+            ids,
+        })
+    })
+    .await
+}
+
+/// Applies `patch` to every selected workout: either all of them change or
+/// none do. Undoing it restores them all in one step. Returns
+/// the ids of the edited workouts.
+#[tauri::command]
+pub async fn bulk_edit_workouts(
+    selection: WorkoutSelection,
+    patch: WorkoutPatch,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<i64>, String> {
+    run_blocking(&state, move |pool| {
+        let patch = patch.check()?;
+        let mut history = undo::lock(&app)?;
+        let mut service = pool.write()?;
+        let tz = timezone::user_time_zone(&service);
+        let workouts = select_workouts(&service, &selection, tz)?;
+        if workouts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids: Vec<i64> = workouts.iter().map(|w| w.id).collect();
+        let capture = Capture::take(&mut service, &[Scope::Workouts(&ids)]);
+
+        edit_each(&service, &workouts, |service, workout| {
+            let (params, new_timestamp) = patch.params_for(workout, tz);
+            records::edit_workout_at(service, params, new_timestamp)?;
+            match patch.scaled_distance(workout) {
+                Some(km) => records::set_distance(service, workout.id, km),
+                None => Ok(()),
+            }
+        })?;
+
+        let edited = records::find_workouts(&service, &ids)?;
+        history.record(Operation::Batch {
+            label: format!("Edit {} workouts", ids.len()),
+            ops: edit_ops(&workouts, &edited),
+        });
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+
+        let dates = workouts
+            .iter()
+            .chain(&edited)
+            .map(|w| timezone::local_date(tz, w.timestamp))
+            .collect();
+        events::workouts_changed(&app, ids.clone(), dates);
+        Ok(ids)
+    })
+    .await
+}
+
+/// Moves every selected workout to the trash as a single entry. Returns the
+/// ids actually deleted.
+#[tauri::command]
+pub async fn bulk_delete_workouts(
+    selection: WorkoutSelection,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<i64>, String> {
+    run_blocking(&state, move |pool| {
+        let mut history = undo::lock(&app)?;
+        let mut service = pool.write()?;
+        let tz = timezone::user_time_zone(&service);
+        let ids: Vec<i64> = select_workouts(&service, &selection, tz)?
+            .iter()
+            .map(|w| w.id)
+            .collect();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let capture = Capture::take(&mut service, &[Scope::Workouts(&ids)]);
+        let (deleted, entry_id) = trash::trash_workouts(&service, &ids)?;
+        history.record(Operation::TrashedWorkouts { entry_id });
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::workouts_changed(&app, deleted.clone(), Vec::new());
+        Ok(deleted)
+    })
+    .await
+}
+
+/// Reorders workouts logged on the same local day: `ids` lists them in the
+/// order they should appear, and they swap timestamps accordingly. Returns the
+/// ids whose time changed.
+#[tauri::command]
+pub async fn reorder_workouts(
+    ids: Vec<i64>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<i64>, String> {
+    run_blocking(&state, move |pool| {
+        let mut history = undo::lock(&app)?;
+        let mut service = pool.write()?;
+        let workouts = records::find_workouts(&service, &ids)?;
+        if let Some(missing) = ids.iter().find(|id| !workouts.iter().any(|w| w.id == **id)) {
+            return Err(format!("Workout {} not found", missing));
+        }
+        if workouts.len() != ids.len() {
+            return Err("Each workout can only be listed once".to_string());
+        }
+        let Some(first) = workouts.first() else {
+            return Ok(Vec::new());
+        };
+        let tz = timezone::user_time_zone(&service);
+        let day = timezone::local_date(tz, first.timestamp);
+        if workouts
+            .iter()
+            .any(|w| timezone::local_date(tz, w.timestamp) != day)
+        {
+            return Err("Only workouts from the same day can be reordered".to_string());
+        }
+
+        let moves: Vec<(i64, DateTime<Utc>)> = reordered_timestamps(&workouts, &ids)
+            .into_iter()
+            .filter(|(id, timestamp)| {
+                workouts
+                    .iter()
+                    .any(|w| w.id == *id && w.timestamp != *timestamp)
+            })
+            .collect();
+        if moves.is_empty() {
+            return Ok(Vec::new());
+        }
+        let moved: Vec<i64> = moves.iter().map(|(id, _)| *id).collect();
+        let capture = Capture::take(&mut service, &[Scope::Workouts(&moved)]);
+        let moving: Vec<Workout> = workouts
+            .iter()
+            .filter(|w| moves.iter().any(|(id, _)| *id == w.id))
+            .cloned()
+            .collect();
+        edit_each(&service, &moving, |service, workout| {
+            let timestamp = moves
+                .iter()
+                .find(|(id, _)| *id == workout.id)
+                .map(|&(_, timestamp)| timestamp);
+            let params = EditWorkoutParams {
+                id: workout.id,
+                new_exercise_identifier: None,
+                new_sets: None,
+                new_reps: None,
+                new_weight: None,
+                new_bodyweight: None,
+                new_duration: None,
+                new_distance_arg: None,
+                new_notes: None,
+                new_date: None,
+            };
+            records::edit_workout_at(service, params, timestamp).map(|_| ())
+        })?;
+
+        let moved: Vec<i64> = moves.iter().map(|(id, _)| *id).collect();
+        let edited = records::find_workouts(&service, &moved)?;
+        history.record(Operation::Batch {
+            label: "Reorder workouts".to_string(),
+            ops: edit_ops(&workouts, &edited),
+        });
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::workouts_changed(&app, moved.clone(), vec![day]);
+        Ok(moved)
+    })
+    .await
+}
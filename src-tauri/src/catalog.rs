@@ -0,0 +1,293 @@
+//! The bundled catalog of common exercises (`resources/exercise_catalog.json`)
+//! that users can browse and import instead of typing every exercise in.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use task_athlete_lib::AppService;
+
+use crate::audit::{Capture, Origin};
+use crate::events;
+use crate::exercise_details::{self, Equipment, ExerciseDetails};
+use crate::muscles;
+use crate::state::run_blocking;
+use crate::{parse_exercise_type, AppState};
+
+const CATALOG_JSON: &str = include_str!("../resources/exercise_catalog.json");
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CatalogExercise {
+    pub name: String,
+    /// "resistance", "cardio" or "body-weight", as `create_exercise` takes it.
+    #[serde(rename = "type")]
+    pub exercise_type: String,
+    pub primary_muscles: Vec<String>,
+    pub secondary_muscles: Vec<String>,
+    pub equipment: String,
+    pub log_weight: bool,
+    pub log_reps: bool,
+    pub log_duration: bool,
+    pub log_distance: bool,
+}
+
+impl CatalogExercise {
+    /// The muscles as the comma-separated list exercises store, primary first.
+    fn muscles(&self) -> Option<String> {
+        let all: Vec<&str> = self
+            .primary_muscles
+            .iter()
+            .chain(&self.secondary_muscles)
+            .map(String::as_str)
+            .collect();
+        (!all.is_empty()).then(|| all.join(", "))
+    }
+
+    /// The catalog's finer equipment names folded into the ones exercises
+    /// store; "none" and "other" have no match.
+    fn equipment(&self) -> Option<Equipment> {
+        match self.equipment.as_str() {
+            "smith_machine" => Some(Equipment::Machine),
+            "ez_bar" | "trap_bar" => Some(Equipment::Barbell),
+            other => Equipment::parse(other).ok(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct CatalogEntry {
+    #[serde(flatten)]
+    pub exercise: CatalogExercise,
+    /// An exercise or alias with this name already exists, so importing it
+    /// would be skipped.
+    pub already_added: bool,
+}
+
+#[derive(Deserialize, Default)]
+pub struct CatalogQuery {
+    /// Words that must all appear in the name, in any case and order.
+    pub search: Option<String>,
+    pub exercise_type: Option<String>,
+    /// A muscle or muscle group.
+    pub muscle: Option<String>,
+    pub equipment: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ImportedExercise {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Serialize, Default)]
+pub struct CatalogImport {
+    pub created: Vec<ImportedExercise>,
+    /// Names that already existed as an exercise or alias.
+    pub skipped: Vec<String>,
+}
+
+pub fn catalog() -> &'static [CatalogExercise] {
+    static CATALOG: OnceLock<Vec<CatalogExercise>> = OnceLock::new();
+    CATALOG.get_or_init(|| {
+        serde_json::from_str(CATALOG_JSON).expect("bundled exercise catalog is valid JSON")
+    })
+}
+
+impl CatalogQuery {
+    fn matches(&self, exercise: &CatalogExercise) -> bool {
+        let name = exercise.name.to_lowercase();
+        let search_ok = self.search.as_deref().map_or(true, |search| {
+            search
+                .to_lowercase()
+                .split_whitespace()
+                .all(|word| name.contains(word))
+        });
+        let type_ok = self
+            .exercise_type
+            .as_deref()
+            .map_or(true, |t| exercise.exercise_type.eq_ignore_ascii_case(t));
+        let muscle_ok = self.muscle.as_deref().map_or(true, |muscle| {
+            let all: Vec<String> = exercise
+                .primary_muscles
+                .iter()
+                .chain(&exercise.secondary_muscles)
+                .cloned()
+                .collect();
+            muscles::matches_any(&all, &[muscle])
+        });
+        let equipment_ok = self
+            .equipment
+            .as_deref()
+            .map_or(true, |e| exercise.equipment.eq_ignore_ascii_case(e));
+        search_ok && type_ok && muscle_ok && equipment_ok
+    }
+}
+
+/// Lowercased names of every exercise and alias in the profile.
+fn taken_names(service: &AppService) -> Result<HashSet<String>, String> {
+    let mut names: HashSet<String> = service
+        .list_exercises(None, None)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|def| def.name.to_lowercase())
+        .collect();
+    let aliases = service.list_aliases().map_err(|e| e.to_string())?;
+    names.extend(
+        aliases
+            .into_keys()
+            .filter(|alias| !exercise_details::is_carrier(alias))
+            .map(|alias| alias.to_lowercase()),
+    );
+    Ok(names)
+}
+
+// --- Tauri Commands ---
+
+/// Catalog exercises matching `query`, in catalog order.
+#[tauri::command]
+pub async fn search_exercise_catalog(
+    query: Option<CatalogQuery>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<CatalogEntry>, String> {
+    run_blocking(&state, move |pool| {
+        let query = query.unwrap_or_default();
+        let service = pool.read()?;
+        let taken = taken_names(&service)?;
+        Ok(catalog()
+            .iter()
+            .filter(|exercise| query.matches(exercise))
+            .map(|exercise| CatalogEntry {
+                exercise: exercise.clone(),
+                already_added: taken.contains(&exercise.name.to_lowercase()),
+            })
+            .collect())
+    })
+    .await
+}
+
+/// Creates the named catalog exercises, skipping any whose name is already
+/// taken by an exercise or alias. Stops at the first failure, keeping the
+/// exercises created before it.
+#[tauri::command]
+pub async fn import_catalog_exercises(
+    names: Vec<String>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<CatalogImport, String> {
+    run_blocking(&state, move |pool| {
+        let selected = names
+            .iter()
+            .map(|name| {
+                catalog()
+                    .iter()
+                    .find(|exercise| exercise.name.eq_ignore_ascii_case(name))
+                    .ok_or_else(|| format!("'{}' is not in the exercise catalog", name))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut service = pool.write()?;
+        let mut taken = taken_names(&service)?;
+        let mut capture = Capture::take(&mut service, &[]);
+        let mut import = CatalogImport::default();
+        let mut failure = None;
+        for exercise in selected {
+            if !taken.insert(exercise.name.to_lowercase()) {
+                import.skipped.push(exercise.name.clone());
+                continue;
+            }
+            let log_flags = Some((
+                Some(exercise.log_weight),
+                Some(exercise.log_reps),
+                Some(exercise.log_duration),
+                Some(exercise.log_distance),
+            ));
+            let created = parse_exercise_type(&exercise.exercise_type).and_then(|ex_type| {
+                service
+                    .create_exercise(
+                        &exercise.name,
+                        ex_type,
+                        log_flags,
+                        exercise.muscles().as_deref(),
+                    )
+                    .map_err(|e| e.to_string())
+            });
+            match created {
+                Ok(id) => {
+                    capture.include_exercises(&[id.to_string()]);
+                    let details = ExerciseDetails {
+                        equipment: exercise.equipment(),
+                        secondary_muscles: exercise.secondary_muscles.clone(),
+                        ..Default::default()
+                    };
+                    if let Err(e) = exercise_details::save(&service, id, &details) {
+                        eprintln!("{}", e);
+                    }
+                    import.created.push(ImportedExercise {
+                        id,
+                        name: exercise.name.clone(),
+                    });
+                }
+                Err(e) => {
+                    failure = Some(format!("Failed to import {}: {}", exercise.name, e));
+                    break;
+                }
+            }
+        }
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        if !import.created.is_empty() {
+            events::exercises_changed(&app);
+        }
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(import),
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_catalog_is_valid() {
+        let catalog = catalog();
+        assert!(!catalog.is_empty());
+        let mut names = HashSet::new();
+        for exercise in catalog {
+            let name = &exercise.name;
+            assert!(
+                names.insert(name.to_lowercase()),
+                "{} is listed twice",
+                name
+            );
+            assert!(
+                parse_exercise_type(&exercise.exercise_type).is_ok(),
+                "{} has type {}",
+                name,
+                exercise.exercise_type
+            );
+            for muscle in exercise
+                .primary_muscles
+                .iter()
+                .chain(&exercise.secondary_muscles)
+            {
+                assert_eq!(muscles::normalize(muscle).as_ref(), Some(muscle));
+                assert!(
+                    muscles::group_of(muscle).is_some(),
+                    "{} lists {}, which isn't in the taxonomy",
+                    name,
+                    muscle
+                );
+            }
+            assert!(
+                exercise.equipment().is_some()
+                    || ["none", "other"].contains(&exercise.equipment.as_str()),
+                "{} has equipment {}",
+                name,
+                exercise.equipment
+            );
+        }
+    }
+}
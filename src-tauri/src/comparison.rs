@@ -0,0 +1,366 @@
+//! Overlaying several exercises on one graph, optionally from other profiles
+//! (e.g. two athletes' bench press), on a shared date axis.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use task_athlete_lib::AppService;
+
+use crate::graphs::{self, Metric};
+use crate::one_rm::{self, OneRmFormula};
+use crate::profiles::{open_profile_service, ProfilesState};
+use crate::records;
+use crate::state::run_blocking;
+use crate::timezone;
+use crate::{parse_naive_date, AppState};
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Normalization {
+    Absolute,
+    /// Percent change from the series' first non-zero value.
+    PercentChange,
+    /// Value divided by the bodyweight logged on or before that day.
+    RelativeToBodyweight,
+}
+
+impl Normalization {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "absolute" => Ok(Normalization::Absolute),
+            "percent_change" => Ok(Normalization::PercentChange),
+            "relative_to_bodyweight" => Ok(Normalization::RelativeToBodyweight),
+            _ => Err(format!(
+                "Invalid normalization '{}'; use absolute, percent_change or relative_to_bodyweight",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ComparedExercise {
+    /// Exercise id, name or alias.
+    pub identifier: String,
+    /// Profile to read it from; the active one by default.
+    pub profile: Option<String>,
+    pub label: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ComparisonGraphPayload {
+    pub series: Vec<ComparedExercise>,
+    #[serde(alias = "graphTypeStr")]
+    pub graph_type_str: String,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    /// "absolute" (the default), "percent_change" or "relative_to_bodyweight".
+    pub normalization: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ComparisonSeries {
+    pub label: String,
+    pub exercise: String,
+    pub profile: String,
+    /// One value per entry of `ComparisonGraph::dates`; `None` where the
+    /// exercise has no data that day.
+    pub values: Vec<Option<f64>>,
+}
+
+#[derive(Serialize)]
+pub struct ComparisonGraph {
+    pub normalization: Normalization,
+    /// Every day any series has a value, oldest first.
+    pub dates: Vec<NaiveDate>,
+    pub series: Vec<ComparisonSeries>,
+}
+
+/// Bodyweight log as (local day, kg), oldest first.
+pub fn bodyweights(service: &mut AppService) -> Result<Vec<(NaiveDate, f64)>, String> {
+    let tz = timezone::user_time_zone(service);
+    let mut entries: Vec<(NaiveDate, f64)> = service
+        .list_bodyweights(u32::MAX)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(_, timestamp, weight)| (timezone::local_date(tz, timestamp), weight))
+        .collect();
+    entries.sort_by_key(|(day, _)| *day);
+    Ok(entries)
+}
+
+// The last weight logged on or before `day`, else the first one logged.
+fn bodyweight_on(entries: &[(NaiveDate, f64)], day: NaiveDate) -> Option<f64> {
+    entries
+        .iter()
+        .rev()
+        .find(|(logged, _)| *logged <= day)
+        .or_else(|| entries.first())
+        .map(|(_, weight)| *weight)
+}
+
+fn normalize(
+    service: &mut AppService,
+    points: Vec<(NaiveDate, f64)>,
+    normalization: Normalization,
+    profile: &str,
+) -> Result<Vec<(NaiveDate, f64)>, String> {
+    match normalization {
+        Normalization::Absolute => Ok(points),
+        Normalization::PercentChange => {
+            let Some(base) = points.iter().map(|(_, v)| *v).find(|v| *v != 0.0) else {
+                return Ok(points);
+            };
+            Ok(points
+                .into_iter()
+                .map(|(day, v)| (day, (v - base) / base * 100.0))
+                .collect())
+        }
+        Normalization::RelativeToBodyweight => {
+            let entries = bodyweights(service)?;
+            let fallback = service.config.bodyweight;
+            points
+                .into_iter()
+                .map(|(day, v)| match bodyweight_on(&entries, day).or(fallback) {
+                    Some(bodyweight) if bodyweight > 0.0 => Ok((day, v / bodyweight)),
+                    _ => Err(format!(
+                        "Profile '{}' has no bodyweight logged to compare against",
+                        profile
+                    )),
+                })
+                .collect()
+        }
+    }
+}
+
+// --- Tauri Commands ---
+
+/// The same graph type for several exercises, aligned on one date axis so
+/// they can be overlaid.
+#[tauri::command]
+pub async fn get_comparison_graph(
+    payload: ComparisonGraphPayload,
+    profiles: tauri::State<'_, ProfilesState>,
+    state: tauri::State<'_, AppState>,
+) -> Result<ComparisonGraph, String> {
+    // Checked up front; parsed again with each profile's 1RM formula.
+    let metric = Metric::parse(&payload.graph_type_str, OneRmFormula::default())?;
+    let normalization = payload
+        .normalization
+        .as_deref()
+        .map(Normalization::parse)
+        .transpose()?
+        .unwrap_or(Normalization::Absolute);
+    if normalization == Normalization::RelativeToBodyweight
+        && !matches!(
+            metric,
+            Metric::Estimated1RM(_) | Metric::MaxWeight | Metric::WorkoutVolume
+        )
+    {
+        return Err(format!(
+            "{} can't be shown relative to bodyweight",
+            payload.graph_type_str
+        ));
+    }
+    let start = payload
+        .start_date
+        .as_deref()
+        .map(parse_naive_date)
+        .transpose()?;
+    let end = payload
+        .end_date
+        .as_deref()
+        .map(parse_naive_date)
+        .transpose()?;
+
+    // Other profiles are opened on their own, without switching to them.
+    let (active, others) = {
+        let registry = profiles
+            .lock()
+            .map_err(|e| format!("Failed to lock profiles: {}", e))?;
+        let mut others = HashMap::new();
+        for name in payload.series.iter().filter_map(|s| s.profile.as_ref()) {
+            if *name == registry.active || others.contains_key(name) {
+                continue;
+            }
+            let profile = registry
+                .get(name)
+                .ok_or_else(|| format!("Profile '{}' not found", name))?;
+            others.insert(name.clone(), profile.clone());
+        }
+        (registry.active.clone(), others)
+    };
+
+    run_blocking(&state, move |pool| {
+        let mut active_service = pool.read()?;
+        let mut other_services = HashMap::new();
+        for (name, profile) in &others {
+            other_services.insert(name.clone(), open_profile_service(profile)?);
+        }
+        let mut series = Vec::new();
+        let mut points_by_series = Vec::new();
+        for compared in &payload.series {
+            let profile = compared.profile.clone().unwrap_or_else(|| active.clone());
+            let service: &mut AppService = match other_services.get_mut(&profile) {
+                Some(service) => service,
+                None => &mut *active_service,
+            };
+            let tz = timezone::user_time_zone(service);
+            let metric = Metric::parse(&payload.graph_type_str, one_rm::user_formula(service))?;
+            let exercise = records::resolve_exercise(service, &compared.identifier)
+                .map_err(|e| format!("{} (profile '{}')", e, profile))?
+                .name;
+            let points = graphs::exercise_series(service, &exercise, metric, tz, start, end)?;
+            let points = normalize(service, points, normalization, &profile)?;
+            let label = compared.label.clone().unwrap_or_else(|| {
+                if profile == active {
+                    exercise.clone()
+                } else {
+                    format!("{} ({})", exercise, profile)
+                }
+            });
+            points_by_series.push(points.into_iter().collect::<BTreeMap<_, _>>());
+            series.push(ComparisonSeries {
+                label,
+                exercise,
+                profile,
+                values: Vec::new(),
+            });
+        }
+
+        let dates: Vec<NaiveDate> = points_by_series
+            .iter()
+            .flat_map(|points| points.keys().copied())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        for (series, points) in series.iter_mut().zip(&points_by_series) {
+            series.values = dates.iter().map(|day| points.get(day).copied()).collect();
+        }
+        Ok(ComparisonGraph {
+            normalization,
+            dates,
+            series,
+        })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn temp_service(name: &str) -> AppService {
+        let dir =
+            std::env::temp_dir().join(format!("task-athlete-gui-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        AppService::initialize_with_paths(&dir.join("config.toml"), &dir.join("workouts.sqlite"))
+            .unwrap()
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+    }
+
+    fn log_bodyweight(service: &mut AppService, day: u32, weight: f64) {
+        service
+            .add_bodyweight_entry(
+                Utc.with_ymd_and_hms(2024, 5, day, 12, 0, 0).unwrap(),
+                weight,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn absolute_leaves_points_alone() {
+        let mut service = temp_service("normalize-absolute");
+        let points = vec![(date(1), 50.0), (date(2), 75.0)];
+        assert_eq!(
+            normalize(&mut service, points.clone(), Normalization::Absolute, "me").unwrap(),
+            points
+        );
+    }
+
+    #[test]
+    fn percent_change_is_from_the_first_non_zero_value() {
+        let mut service = temp_service("normalize-percent");
+        let points = vec![(date(1), 0.0), (date(2), 50.0), (date(3), 75.0)];
+        assert_eq!(
+            normalize(&mut service, points, Normalization::PercentChange, "me").unwrap(),
+            vec![(date(1), -100.0), (date(2), 0.0), (date(3), 50.0)]
+        );
+        let zeros = vec![(date(1), 0.0), (date(2), 0.0)];
+        assert_eq!(
+            normalize(
+                &mut service,
+                zeros.clone(),
+                Normalization::PercentChange,
+                "me"
+            )
+            .unwrap(),
+            zeros
+        );
+    }
+
+    #[test]
+    fn relative_to_bodyweight_uses_the_weight_logged_by_then() {
+        let mut service = temp_service("normalize-bodyweight");
+        log_bodyweight(&mut service, 2, 80.0);
+        log_bodyweight(&mut service, 10, 100.0);
+        let points = vec![(date(1), 40.0), (date(5), 120.0), (date(12), 150.0)];
+        assert_eq!(
+            normalize(
+                &mut service,
+                points,
+                Normalization::RelativeToBodyweight,
+                "me"
+            )
+            .unwrap(),
+            vec![(date(1), 0.5), (date(5), 1.5), (date(12), 1.5)]
+        );
+    }
+
+    #[test]
+    fn relative_to_bodyweight_falls_back_to_the_configured_weight() {
+        let mut service = temp_service("normalize-fallback");
+        let points = vec![(date(1), 40.0)];
+        assert!(normalize(
+            &mut service,
+            points.clone(),
+            Normalization::RelativeToBodyweight,
+            "me"
+        )
+        .is_err());
+        service.set_bodyweight(80.0).unwrap();
+        assert_eq!(
+            normalize(
+                &mut service,
+                points,
+                Normalization::RelativeToBodyweight,
+                "me"
+            )
+            .unwrap(),
+            vec![(date(1), 0.5)]
+        );
+    }
+
+    #[test]
+    fn bodyweight_before_the_first_entry_is_the_first_entry() {
+        let entries = vec![(date(2), 80.0), (date(10), 100.0)];
+        assert_eq!(bodyweight_on(&entries, date(1)), Some(80.0));
+        assert_eq!(bodyweight_on(&entries, date(10)), Some(100.0));
+        assert_eq!(bodyweight_on(&[], date(1)), None);
+    }
+
+    #[test]
+    fn parse_rejects_unknown_normalizations() {
+        assert_eq!(
+            Normalization::parse("percent_change"),
+            Ok(Normalization::PercentChange)
+        );
+        assert!(Normalization::parse("log").is_err());
+    }
+}
@@ -0,0 +1,294 @@
+//! Copying workouts to another day, e.g. repeating a previous session today,
+//! optionally with a progression applied.
+
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::ops::Add;
+
+use task_athlete_lib::{PBInfo, Units};
+
+use crate::audit::{Capture, Origin, Scope};
+use crate::events;
+use crate::pb_notifications;
+use crate::records::{self, WorkoutSnapshot, MILES_PER_KM};
+use crate::state::run_blocking;
+use crate::timezone;
+use crate::undo::{self, Operation};
+use crate::{list_workouts_in, parse_naive_date, AppState, WorkoutFiltersCmdParams};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopySource {
+    /// Every workout logged on this local day ("YYYY-MM-DD").
+    Date(String),
+    Ids(Vec<i64>),
+}
+
+/// Progression applied to each copy, e.g. `weight_increment: 2.5` or
+/// `reps_increment: 1`. Increments are in the units values are entered in:
+/// weight as it was logged, distance in km or miles by the user's units,
+/// duration in minutes. They may be negative and only change values the
+/// original has.
+#[derive(Deserialize, Default)]
+pub struct CopyOptions {
+    pub weight_increment: Option<f64>,
+    pub reps_increment: Option<i64>,
+    pub sets_increment: Option<i64>,
+    pub duration_increment: Option<i64>,
+    pub distance_increment: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct CopiedPersonalBest {
+    pub workout_id: i64,
+    pub exercise_name: String,
+    pub pb_info: PBInfo,
+}
+
+#[derive(Serialize, Default)]
+pub struct CopyResult {
+    /// Ids of the new workouts, in the order of the originals.
+    pub ids: Vec<i64>,
+    pub personal_bests: Vec<CopiedPersonalBest>,
+}
+
+fn bump<T: Add<Output = T> + Copy>(value: Option<T>, increment: Option<T>) -> Option<T> {
+    match (value, increment) {
+        (Some(value), Some(increment)) => Some(value + increment),
+        (value, _) => value,
+    }
+}
+
+impl CopyOptions {
+    /// The copy of `original` to add on `to_date`, at the same local time of
+    /// day.
+    fn copy_of(
+        &self,
+        original: &WorkoutSnapshot,
+        units: &Units,
+        tz: Tz,
+        to_date: NaiveDate,
+    ) -> Result<WorkoutSnapshot, String> {
+        // Distances are stored in km.
+        let distance_increment = match units {
+            Units::Metric => self.distance_increment,
+            Units::Imperial => self.distance_increment.map(|mi| mi / MILES_PER_KM),
+        };
+        let mut copy = original.clone();
+        copy.timestamp = timezone::on_local_date(tz, original.timestamp, to_date);
+        copy.sets = bump(copy.sets, self.sets_increment);
+        copy.reps = bump(copy.reps, self.reps_increment);
+        copy.weight = bump(copy.weight, self.weight_increment);
+        copy.duration_minutes = bump(copy.duration_minutes, self.duration_increment);
+        copy.distance = bump(copy.distance, distance_increment);
+        if copy.sets.is_some_and(|s| s < 1)
+            || copy.reps.is_some_and(|r| r < 1)
+            || copy.weight.is_some_and(|w| w < 0.0)
+            || copy.duration_minutes.is_some_and(|d| d < 0)
+            || copy.distance.is_some_and(|d| d < 0.0)
+        {
+            return Err(format!(
+                "The progression leaves {} (workout {}) with an invalid value",
+                copy.exercise_name, original.id
+            ));
+        }
+        Ok(copy)
+    }
+}
+
+// --- Tauri Commands ---
+
+/// Duplicates the workouts of a day, or the given ones, onto `to_date` (today
+/// when omitted). Either every copy is added or none is, and undoing removes
+/// them all in one step.
+#[tauri::command]
+pub async fn copy_workouts(
+    source: CopySource,
+    to_date: Option<String>,
+    options: Option<CopyOptions>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<CopyResult, String> {
+    run_blocking(&state, move |pool| {
+        let options = options.unwrap_or_default();
+        let to_date = to_date.as_deref().map(parse_naive_date).transpose()?;
+        let mut history = undo::lock(&app)?;
+        let mut service = pool.write()?;
+        let tz = timezone::user_time_zone(&service);
+        let mut originals = match &source {
+            CopySource::Date(date) => {
+                parse_naive_date(date)?;
+                let filters = WorkoutFiltersCmdParams {
+                    date: Some(date.clone()),
+                    ..Default::default()
+                };
+                list_workouts_in(&service, &filters, tz)?
+            }
+            CopySource::Ids(ids) => {
+                let found = records::find_workouts(&service, ids)?;
+                if let Some(missing) = ids.iter().find(|id| !found.iter().any(|w| w.id == **id)) {
+                    return Err(format!("Workout {} not found", missing));
+                }
+                found
+            }
+        };
+        if originals.is_empty() {
+            return Ok(CopyResult::default());
+        }
+        originals.sort_by_key(|w| w.timestamp);
+        let to_date = to_date.unwrap_or_else(|| timezone::today(tz));
+        let copies = originals
+            .iter()
+            .map(|w| {
+                options.copy_of(
+                    &WorkoutSnapshot::from(w),
+                    &service.config.units,
+                    tz,
+                    to_date,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Adding can create an exercise that no longer exists.
+        let exercises: Vec<String> = copies.iter().map(|c| c.exercise_name.clone()).collect();
+        let mut capture = Capture::take(&mut service, &[Scope::Exercises(&exercises)]);
+        let mut result = CopyResult::default();
+        for copy in &copies {
+            match records::add_snapshot(&mut service, copy) {
+                Ok((id, pb_info)) => {
+                    result.ids.push(id);
+                    if let Some(pb_info) = pb_info {
+                        result.personal_bests.push(CopiedPersonalBest {
+                            workout_id: id,
+                            exercise_name: copy.exercise_name.clone(),
+                            pb_info,
+                        });
+                    }
+                }
+                Err(e) => {
+                    // Take back the copies made so far.
+                    if !result.ids.is_empty() {
+                        if let Err(cleanup) = service.delete_workouts(&result.ids) {
+                            eprintln!("Failed to remove partial copies: {}", cleanup);
+                        }
+                    }
+                    capture.include_workouts(&result.ids);
+                    capture.commit(&mut service, Origin::Local);
+                    return Err(format!("Failed to copy {}: {}", copy.exercise_name, e));
+                }
+            }
+        }
+
+        let added = records::find_workouts(&service, &result.ids)?;
+        let ops = result
+            .ids
+            .iter()
+            .filter_map(|id| added.iter().find(|w| w.id == *id))
+            .map(|workout| Operation::AddedWorkout {
+                id: workout.id,
+                snapshot: WorkoutSnapshot::from(workout),
+            })
+            .collect();
+        history.record(Operation::Batch {
+            label: format!("Copy {} workouts", result.ids.len()),
+            ops,
+        });
+        capture.include_workouts(&result.ids);
+        capture.commit(&mut service, Origin::Local);
+        pb_notifications::notify(&app, &service, &result.ids);
+        drop(service);
+        events::workouts_changed(&app, result.ids.clone(), vec![to_date]);
+        Ok(result)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn run(distance: Option<f64>) -> WorkoutSnapshot {
+        WorkoutSnapshot {
+            id: 7,
+            // 07:30 in Berlin, before the switch to summer time.
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 28, 6, 30, 0).unwrap(),
+            exercise_name: "Running".to_string(),
+            exercise_type: Some("cardio".to_string()),
+            sets: None,
+            reps: None,
+            weight: None,
+            duration_minutes: Some(30),
+            distance,
+            bodyweight: None,
+            notes: None,
+        }
+    }
+
+    fn berlin() -> Tz {
+        timezone::parse_time_zone("Europe/Berlin").unwrap()
+    }
+
+    #[test]
+    fn copies_keep_the_local_time_of_day() {
+        let to_date = NaiveDate::from_ymd_opt(2024, 4, 2).unwrap();
+        let copy = CopyOptions::default()
+            .copy_of(&run(Some(5.0)), &Units::Metric, berlin(), to_date)
+            .unwrap();
+        // 07:30 in Berlin is 05:30 UTC in summer time.
+        assert_eq!(
+            copy.timestamp,
+            Utc.with_ymd_and_hms(2024, 4, 2, 5, 30, 0).unwrap()
+        );
+        assert_eq!(copy.distance, Some(5.0));
+    }
+
+    #[test]
+    fn increments_only_change_values_the_original_has() {
+        let options = CopyOptions {
+            weight_increment: Some(2.5),
+            reps_increment: Some(1),
+            duration_increment: Some(-5),
+            distance_increment: Some(1.0),
+            ..CopyOptions::default()
+        };
+        let to_date = NaiveDate::from_ymd_opt(2024, 3, 29).unwrap();
+        let copy = options
+            .copy_of(&run(Some(5.0)), &Units::Metric, berlin(), to_date)
+            .unwrap();
+        assert_eq!(copy.duration_minutes, Some(25));
+        assert_eq!(copy.distance, Some(6.0));
+        assert_eq!((copy.weight, copy.reps, copy.sets), (None, None, None));
+
+        let copy = options
+            .copy_of(&run(None), &Units::Metric, berlin(), to_date)
+            .unwrap();
+        assert_eq!(copy.distance, None);
+    }
+
+    #[test]
+    fn distance_increments_follow_the_users_units() {
+        let options = CopyOptions {
+            distance_increment: Some(1.0),
+            ..CopyOptions::default()
+        };
+        let to_date = NaiveDate::from_ymd_opt(2024, 3, 29).unwrap();
+        let copy = options
+            .copy_of(&run(Some(5.0)), &Units::Imperial, berlin(), to_date)
+            .unwrap();
+        assert!((copy.distance.unwrap() - (5.0 + 1.0 / MILES_PER_KM)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn progressions_below_zero_are_rejected() {
+        let options = CopyOptions {
+            duration_increment: Some(-31),
+            ..CopyOptions::default()
+        };
+        let to_date = NaiveDate::from_ymd_opt(2024, 3, 29).unwrap();
+        assert!(options
+            .copy_of(&run(Some(5.0)), &Units::Metric, berlin(), to_date)
+            .is_err());
+    }
+}
@@ -0,0 +1,320 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use task_athlete_lib::AppService;
+
+use crate::events;
+use crate::profiles::{self, ProfileRegistry};
+use crate::state::{run_blocking, WriteGuard};
+use crate::AppState;
+
+const WRITE_TEST_FILE_NAME: &str = ".task-athlete-write-test";
+// Extra room required on the target volume beyond the size of the database.
+const FREE_SPACE_MARGIN_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Resolves the user-supplied destination. A directory means "put the
+/// database in there under its current file name".
+fn resolve_target_path(new_path: &str, current_db_path: &Path) -> Result<PathBuf, String> {
+    if new_path.trim().is_empty() {
+        return Err("New database path cannot be empty".to_string());
+    }
+    let requested = PathBuf::from(new_path);
+    let target = if requested.is_dir() {
+        let file_name = current_db_path
+            .file_name()
+            .ok_or_else(|| "Current database path has no file name".to_string())?;
+        requested.join(file_name)
+    } else {
+        requested
+    };
+    if !target.is_absolute() {
+        return Err(format!("Database path must be absolute: {:?}", target));
+    }
+    if target.exists() {
+        return Err(format!("A file already exists at {:?}", target));
+    }
+    Ok(target)
+}
+
+/// Size of the database including its WAL file, which may hold data not yet
+/// checkpointed into the main file.
+fn database_size(db_path: &Path) -> u64 {
+    let wal_path = sidecar_file(db_path, "-wal");
+    [db_path.to_path_buf(), wal_path]
+        .iter()
+        .filter_map(|p| fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+fn sidecar_file(db_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = db_path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn check_target_directory(target_dir: &Path, required_bytes: u64) -> Result<(), String> {
+    if !target_dir.is_dir() {
+        return Err(format!("Directory {:?} does not exist", target_dir));
+    }
+
+    let probe = target_dir.join(WRITE_TEST_FILE_NAME);
+    fs::write(&probe, b"")
+        .map_err(|e| format!("Directory {:?} is not writable: {}", target_dir, e))?;
+    let _ = fs::remove_file(&probe);
+
+    let available = fs2::available_space(target_dir)
+        .map_err(|e| format!("Failed to query free space of {:?}: {}", target_dir, e))?;
+    let needed = required_bytes.saturating_add(FREE_SPACE_MARGIN_BYTES);
+    if available < needed {
+        return Err(format!(
+            "Not enough free space in {:?}: {} bytes available, {} bytes needed",
+            target_dir, available, needed
+        ));
+    }
+    Ok(())
+}
+
+/// Removes a database file together with its WAL and shared-memory files.
+pub fn remove_database_files(db_path: &Path) {
+    for path in [
+        db_path.to_path_buf(),
+        sidecar_file(db_path, "-wal"),
+        sidecar_file(db_path, "-shm"),
+    ] {
+        if path.exists() {
+            if let Err(e) = fs::remove_file(&path) {
+                eprintln!("Failed to remove {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+/// Writes a consistent snapshot of the open database to `target`. Unlike a
+/// plain file copy this includes changes still sitting in the WAL.
+fn snapshot_database(service: &AppService, target: &Path) -> Result<(), String> {
+    let target_str = target
+        .to_str()
+        .ok_or_else(|| format!("Database path is not valid UTF-8: {:?}", target))?;
+    service
+        .conn
+        .execute("VACUUM INTO ?1", [target_str])
+        .map_err(|e| format!("Failed to copy database to {:?}: {}", target, e))?;
+    Ok(())
+}
+
+/// Moves the active profile's database to `new_path`. Every step is undone
+/// if a later one fails.
+fn move_active_database(
+    registry: &mut ProfileRegistry,
+    mut service: WriteGuard<'_>,
+    new_path: &str,
+    keep_original: bool,
+) -> Result<PathBuf, String> {
+    let old_db_path = service.get_db_path().to_path_buf();
+    let config_path = service.get_config_path().to_path_buf();
+    let target = resolve_target_path(new_path, &old_db_path)?;
+    let target_dir = target
+        .parent()
+        .ok_or_else(|| format!("Database path has no parent directory: {:?}", target))?;
+    check_target_directory(target_dir, database_size(&old_db_path))?;
+
+    snapshot_database(&service, &target)?;
+
+    let mut new_service = match AppService::initialize_with_paths(&config_path, &target) {
+        Ok(new_service) => new_service,
+        Err(e) => {
+            remove_database_files(&target);
+            return Err(format!("Failed to open database at {:?}: {}", target, e));
+        }
+    };
+
+    let active = registry.active.clone();
+    if let Some(profile) = registry.get_mut(&active) {
+        profile.db_path = target.clone();
+    }
+    if let Err(e) = registry.save() {
+        if let Some(profile) = registry.get_mut(&active) {
+            profile.db_path = old_db_path.clone();
+        }
+        drop(new_service);
+        remove_database_files(&target);
+        return Err(e);
+    }
+
+    new_service.config.db_path = target.clone();
+    let swapped = new_service
+        .save_config()
+        .map_err(|e| format!("Failed to update config with new database path: {}", e))
+        .and_then(|()| service.replace(new_service));
+    let old_service = match swapped {
+        Ok(old_service) => old_service,
+        Err(e) => {
+            if let Some(profile) = registry.get_mut(&active) {
+                profile.db_path = old_db_path.clone();
+            }
+            if let Err(revert_err) = registry.save() {
+                eprintln!(
+                    "Failed to restore profiles after aborted move: {}",
+                    revert_err
+                );
+            }
+            // Rewrite the previous config over whatever the new service saved.
+            if let Err(revert_err) = service.save_config() {
+                eprintln!(
+                    "Failed to restore config after aborted move: {}",
+                    revert_err
+                );
+            }
+            remove_database_files(&target);
+            return Err(e);
+        }
+    };
+
+    // Dropping the old service closes its connection before its files go away.
+    drop(old_service);
+    drop(service);
+
+    if !keep_original {
+        remove_database_files(&old_db_path);
+    }
+    Ok(target)
+}
+
+// --- Tauri Commands ---
+
+/// Copies the active profile's database to `new_path`, points the profile's
+/// config at it and swaps in a service opened on the new file. Every step is
+/// undone if a later one fails; the old file is only removed (unless
+/// `keep_original` is set) once the new location is live.
+#[tauri::command]
+pub async fn move_database(
+    new_path: String,
+    keep_original: Option<bool>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<PathBuf, String> {
+    run_blocking(&state, move |pool| {
+        let mut registry = profiles::lock(&app)?;
+        let target = move_active_database(
+            &mut registry,
+            pool.write()?,
+            &new_path,
+            keep_original.unwrap_or(false),
+        )?;
+        events::config_changed(&app);
+        Ok(target)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::records::{self, WorkoutSnapshot};
+    use crate::state::ServicePool;
+    use chrono::{TimeZone, Utc};
+
+    fn temp_pool(name: &str) -> (PathBuf, ProfileRegistry, ServicePool) {
+        let dir =
+            std::env::temp_dir().join(format!("task-athlete-gui-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let mut service = AppService::initialize_with_paths(
+            &dir.join("config.toml"),
+            &dir.join("workouts.sqlite"),
+        )
+        .unwrap();
+        let snapshot = WorkoutSnapshot {
+            id: 0,
+            timestamp: Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap(),
+            exercise_name: "Squat".to_string(),
+            exercise_type: Some("resistance".to_string()),
+            sets: Some(3),
+            reps: Some(5),
+            weight: Some(100.0),
+            duration_minutes: None,
+            distance: None,
+            bodyweight: None,
+            notes: None,
+        };
+        records::add_snapshot(&mut service, &snapshot).unwrap();
+        let registry = ProfileRegistry::load_or_create(&service).unwrap();
+        let pool = ServicePool::new(service, 1).unwrap();
+        (dir, registry, pool)
+    }
+
+    fn active_db_path(registry: &ProfileRegistry) -> PathBuf {
+        registry.active_profile().unwrap().db_path.clone()
+    }
+
+    #[test]
+    fn move_switches_every_handle_to_the_new_file() {
+        let (dir, mut registry, pool) = temp_pool("move-database");
+        let target = dir.join("moved.sqlite");
+        let moved = move_active_database(
+            &mut registry,
+            pool.write().unwrap(),
+            target.to_str().unwrap(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(moved, target);
+        assert!(!dir.join("workouts.sqlite").exists());
+        assert_eq!(active_db_path(&registry), target);
+        assert_eq!(pool.write().unwrap().config.db_path, target);
+        let read = pool.read().unwrap();
+        assert_eq!(read.get_db_path(), target);
+        assert_eq!(
+            records::workouts_for_exercise(&read, "Squat")
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn failed_move_leaves_the_old_database_in_place() {
+        let (dir, mut registry, pool) = temp_pool("move-database-rollback");
+        let old = dir.join("workouts.sqlite");
+        // Saving the registry fails once its file is a directory.
+        fs::remove_file(dir.join("profiles.json")).unwrap();
+        fs::create_dir(dir.join("profiles.json")).unwrap();
+
+        let target = dir.join("moved.sqlite");
+        assert!(move_active_database(
+            &mut registry,
+            pool.write().unwrap(),
+            target.to_str().unwrap(),
+            false,
+        )
+        .is_err());
+        assert!(!target.exists());
+        assert!(old.exists());
+        assert_eq!(active_db_path(&registry), old);
+        let service = pool.write().unwrap();
+        assert_eq!(service.get_db_path(), old);
+        assert_eq!(
+            records::workouts_for_exercise(&service, "Squat")
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn taken_and_relative_targets_are_refused() {
+        let (dir, mut registry, pool) = temp_pool("move-database-refused");
+        fs::write(dir.join("taken.sqlite"), b"").unwrap();
+        for target in [dir.join("taken.sqlite"), PathBuf::from("relative.sqlite")] {
+            assert!(move_active_database(
+                &mut registry,
+                pool.write().unwrap(),
+                target.to_str().unwrap(),
+                false,
+            )
+            .is_err());
+        }
+        assert_eq!(active_db_path(&registry), dir.join("workouts.sqlite"));
+    }
+}
@@ -0,0 +1,150 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use task_athlete_lib::SyncSummary;
+
+pub const WORKOUTS_CHANGED: &str = "workouts-changed";
+pub const EXERCISES_CHANGED: &str = "exercises-changed";
+pub const BODYWEIGHT_CHANGED: &str = "bodyweight-changed";
+pub const CONFIG_CHANGED: &str = "config-changed";
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeSource {
+    Local,
+    Sync,
+    Profile,
+}
+
+/// Payload of `workouts-changed`. `dates` holds the affected days when the
+/// backend knows them; an empty list means any day may have changed.
+#[derive(Serialize, Clone, Debug)]
+pub struct WorkoutsChanged {
+    pub ids: Vec<i64>,
+    pub dates: Vec<NaiveDate>,
+    pub source: ChangeSource,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ExercisesChanged {
+    pub source: ChangeSource,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct BodyweightChanged {
+    pub ids: Vec<i64>,
+    pub source: ChangeSource,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ConfigChanged {
+    pub source: ChangeSource,
+}
+
+// Events are best-effort: a window that misses one just shows stale data until
+// its next fetch, so failures are logged rather than failing the command.
+fn emit<P: Serialize + Clone>(app: &AppHandle, event: &str, payload: P) {
+    if let Err(e) = app.emit(event, payload) {
+        eprintln!("Failed to emit '{}' event: {}", event, e);
+    }
+}
+
+pub fn workouts_changed(app: &AppHandle, ids: Vec<i64>, mut dates: Vec<NaiveDate>) {
+    dates.sort();
+    dates.dedup();
+    emit(
+        app,
+        WORKOUTS_CHANGED,
+        WorkoutsChanged {
+            ids,
+            dates,
+            source: ChangeSource::Local,
+        },
+    );
+}
+
+pub fn exercises_changed(app: &AppHandle) {
+    emit(
+        app,
+        EXERCISES_CHANGED,
+        ExercisesChanged {
+            source: ChangeSource::Local,
+        },
+    );
+}
+
+pub fn bodyweight_changed(app: &AppHandle, ids: Vec<i64>) {
+    emit(
+        app,
+        BODYWEIGHT_CHANGED,
+        BodyweightChanged {
+            ids,
+            source: ChangeSource::Local,
+        },
+    );
+}
+
+pub fn config_changed(app: &AppHandle) {
+    emit(
+        app,
+        CONFIG_CHANGED,
+        ConfigChanged {
+            source: ChangeSource::Local,
+        },
+    );
+}
+
+/// Everything may differ after swapping the database (profile switch or move).
+pub fn all_changed(app: &AppHandle, source: ChangeSource) {
+    emit(
+        app,
+        WORKOUTS_CHANGED,
+        WorkoutsChanged {
+            ids: Vec::new(),
+            dates: Vec::new(),
+            source,
+        },
+    );
+    emit(app, EXERCISES_CHANGED, ExercisesChanged { source });
+    emit(
+        app,
+        BODYWEIGHT_CHANGED,
+        BodyweightChanged {
+            ids: Vec::new(),
+            source,
+        },
+    );
+    emit(app, CONFIG_CHANGED, ConfigChanged { source });
+}
+
+/// Emits one event per kind of record the server sent us.
+pub fn sync_applied(app: &AppHandle, received: &SyncSummary) {
+    let source = ChangeSource::Sync;
+    if received.workouts > 0 {
+        emit(
+            app,
+            WORKOUTS_CHANGED,
+            WorkoutsChanged {
+                ids: Vec::new(),
+                dates: Vec::new(),
+                source,
+            },
+        );
+    }
+    if received.exercises > 0 || received.aliases > 0 {
+        emit(app, EXERCISES_CHANGED, ExercisesChanged { source });
+    }
+    if received.bodyweights > 0 {
+        emit(
+            app,
+            BODYWEIGHT_CHANGED,
+            BodyweightChanged {
+                ids: Vec::new(),
+                source,
+            },
+        );
+    }
+    // The last sync timestamp is stored in the config, so it always changes.
+    emit(app, CONFIG_CHANGED, ConfigChanged { source });
+}
@@ -0,0 +1,730 @@
+//! Exercise metadata the lib has no columns for: instructions, equipment, a
+//! reference link, media files and which muscles are only worked secondarily.
+//!
+//! The details live in the `gui_exercise_details` table of the profile's
+//! database, keyed by exercise id. The lib only syncs its own tables, so they
+//! stay on this device, as do the media files copied into `exercise_media/`
+//! next to the profile's config.
+//!
+//! A few builds kept the details on "carrier" aliases (`#details:<stamp>:<json>`)
+//! instead. `adopt_carriers` moves those into the table, also when a sync
+//! brings one from a device still running such a build.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+
+use task_athlete_lib::AppService;
+
+use crate::audit::{Capture, Origin, Scope};
+use crate::events;
+use crate::records;
+use crate::settings;
+use crate::state::run_blocking;
+use crate::AppState;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS gui_exercise_details (
+    exercise_id INTEGER PRIMARY KEY,
+    instructions TEXT,
+    equipment TEXT,
+    reference_url TEXT,
+    media TEXT NOT NULL DEFAULT '[]',
+    secondary_muscles TEXT NOT NULL DEFAULT '[]'
+);
+";
+
+const CARRIER_PREFIX: &str = "#details:";
+const MEDIA_DIR_NAME: &str = "exercise_media";
+const MEDIA_EXTENSIONS: [&str; 8] = ["png", "jpg", "jpeg", "gif", "webp", "mp4", "webm", "mov"];
+// Enough for a short clip, small enough to keep the data directory sane.
+const MAX_MEDIA_BYTES: u64 = 50 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Equipment {
+    Barbell,
+    Dumbbell,
+    Cable,
+    Machine,
+    Bodyweight,
+    Kettlebell,
+    Band,
+}
+
+impl Equipment {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .map_err(|_| format!("Invalid equipment: {}", s))
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Equipment::Barbell => "barbell",
+            Equipment::Dumbbell => "dumbbell",
+            Equipment::Cable => "cable",
+            Equipment::Machine => "machine",
+            Equipment::Bodyweight => "bodyweight",
+            Equipment::Kettlebell => "kettlebell",
+            Equipment::Band => "band",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ExerciseDetails {
+    pub instructions: Option<String>,
+    pub equipment: Option<Equipment>,
+    pub reference_url: Option<String>,
+    /// File names inside the media directory, in display order.
+    #[serde(default)]
+    pub media: Vec<String>,
+    /// Muscles from the exercise's muscle list that it only works secondarily.
+    #[serde(default)]
+    pub secondary_muscles: Vec<String>,
+}
+
+impl ExerciseDetails {
+    pub fn is_empty(&self) -> bool {
+        *self == ExerciseDetails::default()
+    }
+}
+
+/// Changes to an exercise's details, as `create_exercise` and `edit_exercise`
+/// take them. For each field `None` keeps it and `Some(None)` clears it.
+#[derive(Deserialize, Default)]
+pub struct DetailsPatch {
+    pub instructions: Option<Option<String>>,
+    pub equipment: Option<Option<String>>,
+    pub reference_url: Option<Option<String>>,
+    /// Already normalized; see `muscles::MuscleTargets`.
+    pub secondary_muscles: Option<Vec<String>>,
+}
+
+impl DetailsPatch {
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_none()
+            && self.equipment.is_none()
+            && self.reference_url.is_none()
+            && self.secondary_muscles.is_none()
+    }
+
+    /// Rejects bad values before anything is written.
+    pub fn check(&self) -> Result<(), String> {
+        if let Some(Some(equipment)) = &self.equipment {
+            Equipment::parse(equipment)?;
+        }
+        if let Some(Some(url)) = &self.reference_url {
+            let url = url.trim();
+            if !(url.starts_with("https://") || url.starts_with("http://")) {
+                return Err(format!(
+                    "Reference URL must start with http:// or https://: {}",
+                    url
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(&self, details: &mut ExerciseDetails) -> Result<(), String> {
+        // Blank text counts as clearing the field.
+        let cleaned = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        if let Some(instructions) = &self.instructions {
+            details.instructions = cleaned(instructions);
+        }
+        if let Some(equipment) = &self.equipment {
+            details.equipment = cleaned(equipment)
+                .as_deref()
+                .map(Equipment::parse)
+                .transpose()?;
+        }
+        if let Some(url) = &self.reference_url {
+            details.reference_url = cleaned(url);
+        }
+        if let Some(secondary) = &self.secondary_muscles {
+            details.secondary_muscles = secondary.clone();
+        }
+        Ok(())
+    }
+}
+
+/// Whether `alias` is a carrier of exercise details rather than a name the
+/// user gave the exercise.
+pub fn is_carrier(alias: &str) -> bool {
+    alias.starts_with(CARRIER_PREFIX)
+}
+
+// The details a carrier alias holds. Equipment stays a string so an unknown
+// value doesn't hide the rest.
+#[derive(Deserialize, Default, PartialEq)]
+struct Carried {
+    instructions: Option<String>,
+    equipment: Option<String>,
+    reference_url: Option<String>,
+    #[serde(default)]
+    media: Vec<String>,
+    #[serde(default)]
+    secondary_muscles: Vec<String>,
+}
+
+impl Carried {
+    fn into_details(self) -> ExerciseDetails {
+        ExerciseDetails {
+            instructions: self.instructions,
+            equipment: self.equipment.and_then(|e| Equipment::parse(&e).ok()),
+            reference_url: self.reference_url,
+            media: self.media,
+            secondary_muscles: self.secondary_muscles,
+        }
+    }
+}
+
+fn stamp() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default()
+}
+
+// "<prefix><stamp>:<json>"; the latest stamp of an exercise wins.
+fn parse_carrier(alias: &str) -> Option<(u128, Carried)> {
+    let (stamp, json) = alias.strip_prefix(CARRIER_PREFIX)?.split_once(':')?;
+    Some((stamp.parse().ok()?, serde_json::from_str(json).ok()?))
+}
+
+// The latest carrier of each exercise, by lowercased exercise name.
+fn carried_by_name(aliases: &HashMap<String, String>) -> HashMap<String, Carried> {
+    let mut latest: HashMap<String, (u128, Carried)> = HashMap::new();
+    for (alias, exercise) in aliases {
+        let Some((stamp, carried)) = parse_carrier(alias) else {
+            continue;
+        };
+        let key = exercise.to_lowercase();
+        if latest.get(&key).map_or(true, |(newest, _)| stamp > *newest) {
+            latest.insert(key, (stamp, carried));
+        }
+    }
+    latest
+        .into_iter()
+        .map(|(name, (_, carried))| (name, carried))
+        .collect()
+}
+
+pub fn ensure_schema(service: &AppService) -> Result<(), String> {
+    service
+        .conn
+        .execute_batch(SCHEMA)
+        .map_err(|e| format!("Failed to create exercise details table: {}", e))?;
+    // Tables created before secondary muscles were tracked lack the column.
+    let has_secondary: i64 = service
+        .conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('gui_exercise_details')
+             WHERE name = 'secondary_muscles'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if has_secondary == 0 {
+        service
+            .conn
+            .execute_batch(
+                "ALTER TABLE gui_exercise_details
+                 ADD COLUMN secondary_muscles TEXT NOT NULL DEFAULT '[]'",
+            )
+            .map_err(|e| format!("Failed to upgrade exercise details table: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Moves the details on carrier aliases into the table and deletes the
+/// aliases. Details already in the table win, but get the carrier's
+/// secondary muscles if they have none.
+pub fn adopt_carriers(service: &AppService) -> Result<(), String> {
+    let aliases = service.list_aliases().map_err(|e| e.to_string())?;
+    if !aliases.keys().any(|alias| is_carrier(alias)) {
+        return Ok(());
+    }
+    let mut carried = carried_by_name(&aliases);
+    records::in_savepoint(service, "adopt_carriers", |service| {
+        for definition in service
+            .list_exercises(None, None)
+            .map_err(|e| e.to_string())?
+        {
+            let Some(carried) = carried.remove(&definition.name.to_lowercase()) else {
+                continue;
+            };
+            let carried = carried.into_details();
+            let mut details = load(service, definition.id)?;
+            if details.is_empty() {
+                details = carried;
+            } else if details.secondary_muscles.is_empty() {
+                details.secondary_muscles = carried.secondary_muscles;
+            }
+            save(service, definition.id, &details)?;
+        }
+        for alias in aliases.keys().filter(|alias| is_carrier(alias)) {
+            service
+                .delete_alias(alias)
+                .map_err(|e| format!("Failed to delete exercise details alias: {}", e))?;
+        }
+        Ok(())
+    })
+}
+
+// All rows, or just `exercise_id`'s when given.
+fn query(
+    service: &AppService,
+    exercise_id: Option<i64>,
+) -> Result<HashMap<i64, ExerciseDetails>, String> {
+    let mut stmt = service
+        .conn
+        .prepare(
+            "SELECT exercise_id, instructions, equipment, reference_url, media,
+                    secondary_muscles
+             FROM gui_exercise_details
+             WHERE ?1 IS NULL OR exercise_id = ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([exercise_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut details = HashMap::new();
+    for row in rows {
+        let (id, instructions, equipment, reference_url, media, secondary_muscles) =
+            row.map_err(|e| e.to_string())?;
+        details.insert(
+            id,
+            ExerciseDetails {
+                instructions,
+                // Unknown values (e.g. written by a newer version) read as unset.
+                equipment: equipment.and_then(|e| Equipment::parse(&e).ok()),
+                reference_url,
+                media: serde_json::from_str(&media).unwrap_or_default(),
+                secondary_muscles: serde_json::from_str(&secondary_muscles).unwrap_or_default(),
+            },
+        );
+    }
+    Ok(details)
+}
+
+pub fn load(service: &AppService, exercise_id: i64) -> Result<ExerciseDetails, String> {
+    Ok(query(service, Some(exercise_id))?
+        .remove(&exercise_id)
+        .unwrap_or_default())
+}
+
+/// Details of every exercise that has any, by exercise id.
+pub fn load_all(service: &AppService) -> Result<HashMap<i64, ExerciseDetails>, String> {
+    query(service, None)
+}
+
+/// Stores the details, or removes the row when they are empty.
+pub fn save(
+    service: &AppService,
+    exercise_id: i64,
+    details: &ExerciseDetails,
+) -> Result<(), String> {
+    let result = if details.is_empty() {
+        service.conn.execute(
+            "DELETE FROM gui_exercise_details WHERE exercise_id = ?1",
+            [exercise_id],
+        )
+    } else {
+        let media = serde_json::to_string(&details.media).map_err(|e| e.to_string())?;
+        let secondary_muscles =
+            serde_json::to_string(&details.secondary_muscles).map_err(|e| e.to_string())?;
+        service.conn.execute(
+            "INSERT OR REPLACE INTO gui_exercise_details
+                (exercise_id, instructions, equipment, reference_url, media,
+                 secondary_muscles)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            (
+                exercise_id,
+                details.instructions.as_deref(),
+                details.equipment.map(Equipment::as_str),
+                details.reference_url.as_deref(),
+                media,
+                secondary_muscles,
+            ),
+        )
+    };
+    result
+        .map(|_| ())
+        .map_err(|e| format!("Failed to save details of exercise {}: {}", exercise_id, e))
+}
+
+/// Applies `patch` to the exercise's stored details.
+pub fn update(service: &AppService, exercise_id: i64, patch: &DetailsPatch) -> Result<(), String> {
+    if patch.is_empty() {
+        return Ok(());
+    }
+    let mut details = load(service, exercise_id)?;
+    patch.apply(&mut details)?;
+    save(service, exercise_id, &details)
+}
+
+/// Drops the details of a deleted exercise. The media files stay, since a
+/// trashed exercise can still be restored with them.
+pub fn forget(service: &AppService, exercise_id: i64) -> Result<(), String> {
+    save(service, exercise_id, &ExerciseDetails::default())
+}
+
+/// The active profile's media directory, next to its config.
+pub fn media_dir(service: &AppService) -> Result<PathBuf, String> {
+    let dir = settings::profile_file(service, MEDIA_DIR_NAME);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    Ok(dir)
+}
+
+// Media names come from synced data, so only plain names inside the
+// directory are ever used.
+fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty() && Path::new(name).file_name() == Some(OsStr::new(name))
+}
+
+/// Moves the profile's media files out of the shared directory under the app
+/// data directory, where they were kept before each profile had its own.
+pub fn adopt_legacy_media(app: &tauri::AppHandle, service: &AppService) -> Result<(), String> {
+    let legacy = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to find the app data directory: {}", e))?
+        .join(MEDIA_DIR_NAME);
+    if !legacy.is_dir() {
+        return Ok(());
+    }
+    let dir = media_dir(service)?;
+    for details in load_all(service)?.values() {
+        for file in details.media.iter().filter(|f| is_plain_file_name(f)) {
+            let (from, to) = (legacy.join(file), dir.join(file));
+            if !from.exists() || to.exists() || fs::rename(&from, &to).is_ok() {
+                continue;
+            }
+            // Renaming fails across file systems.
+            fs::copy(&from, &to)
+                .map_err(|e| format!("Failed to copy {:?} to {:?}: {}", from, to, e))?;
+            if let Err(e) = fs::remove_file(&from) {
+                eprintln!("Failed to delete {:?}: {}", from, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Deletes the media files no exercise uses any more, e.g. a trashed
+/// exercise's once its entry is gone.
+pub fn delete_unused_media(service: &AppService, files: &[String]) -> Result<(), String> {
+    if files.is_empty() {
+        return Ok(());
+    }
+    let used: HashSet<String> = load_all(service)?
+        .into_values()
+        .flat_map(|details| details.media)
+        .collect();
+    let dir = media_dir(service)?;
+    for file in files {
+        if used.contains(file) || !is_plain_file_name(file) {
+            continue;
+        }
+        let path = dir.join(file);
+        match fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => eprintln!("Failed to delete {:?}: {}", path, e),
+        }
+    }
+    Ok(())
+}
+
+fn media_extension(path: &Path) -> Result<String, String> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    if MEDIA_EXTENSIONS.contains(&extension.as_str()) {
+        Ok(extension)
+    } else {
+        Err(format!(
+            "Unsupported media file {:?}; use one of: {}",
+            path,
+            MEDIA_EXTENSIONS.join(", ")
+        ))
+    }
+}
+
+#[derive(Serialize)]
+pub struct ExerciseDetailsPayload {
+    pub exercise_id: i64,
+    pub name: String,
+    #[serde(flatten)]
+    pub details: ExerciseDetails,
+    /// Absolute paths of `details.media`.
+    pub media_paths: Vec<PathBuf>,
+}
+
+fn payload(service: &AppService, identifier: &str) -> Result<ExerciseDetailsPayload, String> {
+    let definition = records::resolve_exercise(service, identifier)?;
+    let details = load(service, definition.id)?;
+    let dir = media_dir(service)?;
+    Ok(ExerciseDetailsPayload {
+        exercise_id: definition.id,
+        name: definition.name,
+        media_paths: details
+            .media
+            .iter()
+            .filter(|file| is_plain_file_name(file))
+            .map(|file| dir.join(file))
+            .collect(),
+        details,
+    })
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub async fn get_exercise_details(
+    identifier: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<ExerciseDetailsPayload, String> {
+    run_blocking(&state, move |pool| {
+        let service = pool.read()?;
+        payload(&service, &identifier)
+    })
+    .await
+}
+
+/// Copies an image or short video into the media directory and attaches it to
+/// the exercise.
+#[tauri::command]
+pub async fn add_exercise_media(
+    identifier: String,
+    source_path: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<ExerciseDetailsPayload, String> {
+    run_blocking(&state, move |pool| {
+        let source = PathBuf::from(&source_path);
+        let extension = media_extension(&source)?;
+        let size = fs::metadata(&source)
+            .map_err(|e| format!("Failed to read {:?}: {}", source, e))?
+            .len();
+        if size > MAX_MEDIA_BYTES {
+            return Err(format!(
+                "{:?} is {} MB; media files can be at most {} MB",
+                source,
+                size / (1024 * 1024),
+                MAX_MEDIA_BYTES / (1024 * 1024)
+            ));
+        }
+
+        let mut service = pool.write()?;
+        let definition = records::resolve_exercise(&service, &identifier)?;
+        let file_name = format!("{}-{}.{}", definition.id, stamp(), extension);
+        let target = media_dir(&service)?.join(&file_name);
+        fs::copy(&source, &target)
+            .map_err(|e| format!("Failed to copy {:?} to {:?}: {}", source, target, e))?;
+
+        let capture = Capture::take(
+            &mut service,
+            &[Scope::Exercises(&[definition.id.to_string()])],
+        );
+        let mut details = load(&service, definition.id)?;
+        details.media.push(file_name);
+        if let Err(e) = save(&service, definition.id, &details) {
+            let _ = fs::remove_file(&target);
+            return Err(e);
+        }
+        capture.commit(&mut service, Origin::Local);
+        let result = payload(&service, &definition.name);
+        drop(service);
+        events::exercises_changed(&app);
+        result
+    })
+    .await
+}
+
+/// The bytes of a media file, for the frontend to show through a blob URL.
+#[tauri::command]
+pub async fn read_exercise_media(
+    file_name: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<tauri::ipc::Response, String> {
+    run_blocking(&state, move |pool| {
+        if !is_plain_file_name(&file_name) {
+            return Err(format!("Invalid media file name: {}", file_name));
+        }
+        let path = media_dir(&*pool.read()?)?.join(&file_name);
+        let bytes = fs::read(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        Ok(tauri::ipc::Response::new(bytes))
+    })
+    .await
+}
+
+/// Detaches a media file from the exercise and deletes it.
+#[tauri::command]
+pub async fn remove_exercise_media(
+    identifier: String,
+    file_name: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<ExerciseDetailsPayload, String> {
+    run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
+        let definition = records::resolve_exercise(&service, &identifier)?;
+        let mut details = load(&service, definition.id)?;
+        let Some(index) = details.media.iter().position(|f| *f == file_name) else {
+            return Err(format!(
+                "{} has no media file {}",
+                definition.name, file_name
+            ));
+        };
+        let capture = Capture::take(
+            &mut service,
+            &[Scope::Exercises(&[definition.id.to_string()])],
+        );
+        details.media.remove(index);
+        save(&service, definition.id, &details)?;
+        capture.commit(&mut service, Origin::Local);
+        delete_unused_media(&service, &[file_name])?;
+        let result = payload(&service, &definition.name);
+        drop(service);
+        events::exercises_changed(&app);
+        result
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use task_athlete_lib::ExerciseType;
+
+    fn carrier(stamp: u128, instructions: &str) -> String {
+        format!(
+            "#details:{}:{}",
+            stamp,
+            serde_json::json!({ "instructions": instructions, "media": ["3-1.png"] })
+        )
+    }
+
+    fn temp_service(name: &str) -> AppService {
+        let dir =
+            std::env::temp_dir().join(format!("task-athlete-gui-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let service = AppService::initialize_with_paths(
+            &dir.join("config.toml"),
+            &dir.join("workouts.sqlite"),
+        )
+        .unwrap();
+        ensure_schema(&service).unwrap();
+        service
+    }
+
+    #[test]
+    fn latest_carrier_wins() {
+        let aliases = HashMap::from([
+            (carrier(1, "old"), "Bench Press".to_string()),
+            (carrier(2, "new"), "bench press".to_string()),
+            (
+                "#details:not-a-stamp:{}".to_string(),
+                "Bench Press".to_string(),
+            ),
+            ("bench".to_string(), "Bench Press".to_string()),
+        ]);
+        let mut by_name = carried_by_name(&aliases);
+        assert_eq!(by_name.len(), 1);
+        let details = by_name.remove("bench press").unwrap().into_details();
+        assert_eq!(details.instructions.as_deref(), Some("new"));
+        assert_eq!(details.media, vec!["3-1.png".to_string()]);
+        assert!(!is_carrier("bench"));
+    }
+
+    #[test]
+    fn unknown_equipment_reads_as_unset() {
+        let details = Carried {
+            instructions: Some("Push".to_string()),
+            equipment: Some("sled".to_string()),
+            ..Carried::default()
+        }
+        .into_details();
+        assert_eq!(details.equipment, None);
+        assert_eq!(details.instructions.as_deref(), Some("Push"));
+    }
+
+    #[test]
+    fn carriers_move_into_the_table() {
+        let service = temp_service("carriers");
+        let bench = service
+            .create_exercise("Bench Press", ExerciseType::Resistance, None, None)
+            .unwrap();
+        let squat = service
+            .create_exercise("Squat", ExerciseType::Resistance, None, None)
+            .unwrap();
+        let kept = ExerciseDetails {
+            instructions: Some("Sit back".to_string()),
+            ..ExerciseDetails::default()
+        };
+        save(&service, squat, &kept).unwrap();
+        service
+            .create_alias(&carrier(1, "Arch"), "Bench Press")
+            .unwrap();
+        service
+            .create_alias(&carrier(2, "Ignored"), "Squat")
+            .unwrap();
+        service.create_alias("bench", "Bench Press").unwrap();
+
+        adopt_carriers(&service).unwrap();
+
+        assert_eq!(
+            load(&service, bench).unwrap().instructions.as_deref(),
+            Some("Arch")
+        );
+        assert_eq!(load(&service, squat).unwrap(), kept);
+        let aliases = service.list_aliases().unwrap();
+        assert_eq!(aliases.keys().collect::<Vec<_>>(), vec!["bench"]);
+    }
+
+    #[test]
+    fn empty_details_remove_the_row() {
+        let service = temp_service("details-row");
+        let details = ExerciseDetails {
+            equipment: Some(Equipment::Barbell),
+            ..ExerciseDetails::default()
+        };
+        save(&service, 7, &details).unwrap();
+        assert_eq!(load_all(&service).unwrap()[&7], details);
+        forget(&service, 7).unwrap();
+        assert!(load_all(&service).unwrap().is_empty());
+    }
+
+    #[test]
+    fn media_names_stay_inside_the_directory() {
+        assert!(is_plain_file_name("3-1700000000.png"));
+        assert!(!is_plain_file_name("../config.toml"));
+        assert!(!is_plain_file_name("/etc/passwd"));
+        assert!(!is_plain_file_name(""));
+    }
+}
@@ -0,0 +1,531 @@
+//! Graph data, grouped on the user's local days rather than the UTC dates the
+//! lib groups by: per exercise, or aggregated over many exercises in day, week
+//! or month buckets, and day by day for an activity heatmap.
+
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use task_athlete_lib::{AppService, Workout};
+
+use crate::one_rm::{self, OneRmFormula};
+use crate::records;
+use crate::state::run_blocking;
+use crate::timezone;
+use crate::{
+    list_workouts_in, parse_naive_date, AppState, VolumeFiltersCmdParams, WorkoutFiltersCmdParams,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Estimated1RM(OneRmFormula),
+    MaxWeight,
+    MaxReps,
+    WorkoutVolume,
+    WorkoutReps,
+    WorkoutSets,
+    WorkoutDuration,
+    WorkoutDistance,
+    /// Logged workouts, one per exercise entry.
+    WorkoutCount,
+    /// Days with at least one workout.
+    TrainingDays,
+}
+
+impl Metric {
+    /// `formula` is used for "Estimated1RM".
+    pub fn parse(s: &str, formula: OneRmFormula) -> Result<Self, String> {
+        match s {
+            "Estimated1RM" => Ok(Metric::Estimated1RM(formula)),
+            "MaxWeight" => Ok(Metric::MaxWeight),
+            "MaxReps" => Ok(Metric::MaxReps),
+            "WorkoutVolume" => Ok(Metric::WorkoutVolume),
+            "WorkoutReps" => Ok(Metric::WorkoutReps),
+            "WorkoutSets" => Ok(Metric::WorkoutSets),
+            "WorkoutDuration" => Ok(Metric::WorkoutDuration),
+            "WorkoutDistance" => Ok(Metric::WorkoutDistance),
+            "WorkoutCount" => Ok(Metric::WorkoutCount),
+            "TrainingDays" => Ok(Metric::TrainingDays),
+            _ => Err(format!("Invalid graph type: {}", s)),
+        }
+    }
+
+    // Peak metrics keep the best workout; the rest add up.
+    fn is_peak(self) -> bool {
+        matches!(
+            self,
+            Metric::Estimated1RM(_) | Metric::MaxWeight | Metric::MaxReps
+        )
+    }
+
+    // Training days count a day once however many workouts it has.
+    fn is_peak_within_day(self) -> bool {
+        self.is_peak() || self == Metric::TrainingDays
+    }
+
+    fn value(self, workout: &Workout) -> Option<f64> {
+        let sets = workout.sets.unwrap_or(1) as f64;
+        let reps = workout.reps.map(|r| r as f64);
+        match self {
+            Metric::Estimated1RM(formula) => formula.estimate_workout(workout),
+            Metric::MaxWeight => workout.weight,
+            Metric::MaxReps => reps,
+            Metric::WorkoutVolume => Some(sets * reps? * workout.weight?),
+            Metric::WorkoutReps => Some(sets * reps?),
+            Metric::WorkoutSets => Some(sets),
+            Metric::WorkoutDuration => workout.duration_minutes.map(|d| d as f64),
+            Metric::WorkoutDistance => workout.distance,
+            Metric::WorkoutCount | Metric::TrainingDays => Some(1.0),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Period {
+    Day,
+    /// Monday to Sunday.
+    Week,
+    Month,
+}
+
+impl Period {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "day" => Ok(Period::Day),
+            "week" => Ok(Period::Week),
+            "month" => Ok(Period::Month),
+            _ => Err(format!("Invalid period '{}'; use day, week or month", s)),
+        }
+    }
+
+    pub fn start_of(self, day: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => day,
+            Period::Week => day - Duration::days(i64::from(day.weekday().num_days_from_monday())),
+            Period::Month => day.with_day(1).unwrap_or(day),
+        }
+    }
+
+    pub fn next(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => start + Duration::days(1),
+            Period::Week => start + Duration::days(7),
+            Period::Month => start + Months::new(1),
+        }
+    }
+}
+
+fn combine(total: &mut f64, value: f64, peak: bool) {
+    *total = if peak {
+        total.max(value)
+    } else {
+        *total + value
+    };
+}
+
+/// One point per local day with a value, oldest first. Both bounds are
+/// inclusive local dates.
+pub fn daily_series(
+    workouts: &[Workout],
+    metric: Metric,
+    tz: Tz,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+) -> Vec<(NaiveDate, f64)> {
+    let mut days: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    for workout in workouts {
+        let day = timezone::local_date(tz, workout.timestamp);
+        if start.is_some_and(|s| day < s) || end.is_some_and(|e| day > e) {
+            continue;
+        }
+        let Some(value) = metric.value(workout) else {
+            continue;
+        };
+        days.entry(day)
+            .and_modify(|total| combine(total, value, metric.is_peak_within_day()))
+            .or_insert(value);
+    }
+    days.into_iter().collect()
+}
+
+/// `daily_series` rolled up into buckets, each dated by its first day. Summed
+/// metrics get a 0 for every empty bucket from `start` to `end`; peak metrics
+/// only have points where there is a value.
+pub fn bucketed_series(
+    workouts: &[Workout],
+    metric: Metric,
+    tz: Tz,
+    period: Period,
+    start: Option<NaiveDate>,
+    end: NaiveDate,
+) -> Vec<(NaiveDate, f64)> {
+    let days = daily_series(workouts, metric, tz, start, Some(end));
+    let mut buckets: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    if !metric.is_peak() {
+        if let Some(first) = start.or_else(|| days.first().map(|(day, _)| *day)) {
+            let mut bucket = period.start_of(first);
+            while bucket <= end {
+                buckets.insert(bucket, 0.0);
+                bucket = period.next(bucket);
+            }
+        }
+    }
+    for (day, value) in days {
+        buckets
+            .entry(period.start_of(day))
+            .and_modify(|total| combine(total, value, metric.is_peak()))
+            .or_insert(value);
+    }
+    buckets.into_iter().collect()
+}
+
+/// `get_data_for_graph` for one exercise, given by id, name or alias.
+pub fn exercise_series(
+    service: &AppService,
+    identifier: &str,
+    metric: Metric,
+    tz: Tz,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+) -> Result<Vec<(NaiveDate, f64)>, String> {
+    let definition = records::resolve_exercise(service, identifier)?;
+    let workouts = records::workouts_for_exercise(service, &definition.name)?;
+    Ok(daily_series(&workouts, metric, tz, start, end))
+}
+
+#[derive(Deserialize)]
+pub struct SeriesQuery {
+    /// Shown in the legend. Defaults to a description of the filters.
+    pub label: Option<String>,
+    #[serde(alias = "graphTypeStr", alias = "graph_type_str")]
+    pub metric: String,
+    #[serde(flatten)]
+    pub filters: VolumeFiltersCmdParams,
+}
+
+#[derive(Deserialize)]
+pub struct AggregateGraphPayload {
+    pub series: Vec<SeriesQuery>,
+    /// "day" (the default), "week" or "month".
+    pub bucket: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct GraphSeries {
+    pub label: String,
+    pub metric: String,
+    pub points: Vec<(NaiveDate, f64)>,
+}
+
+fn default_label(query: &SeriesQuery) -> String {
+    let filters = &query.filters;
+    let parts: Vec<&str> = [
+        filters.exercise_name.as_deref(),
+        filters.exercise_type.as_deref(),
+        filters.muscle.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let scope = if parts.is_empty() {
+        "All exercises".to_string()
+    } else {
+        parts.join(", ")
+    };
+    format!("{} ({})", scope, query.metric)
+}
+
+fn aggregate_series(
+    service: &AppService,
+    query: &SeriesQuery,
+    period: Period,
+    tz: Tz,
+) -> Result<GraphSeries, String> {
+    let metric = Metric::parse(&query.metric, one_rm::user_formula(service))?;
+    let filters = &query.filters;
+    let exercise_name = filters
+        .exercise_name
+        .as_deref()
+        .map(|identifier| records::resolve_exercise(service, identifier).map(|def| def.name))
+        .transpose()?;
+    let end = match filters.end_date.as_deref() {
+        Some(end) => parse_naive_date(end)?,
+        None => timezone::today(tz),
+    };
+    let start = match (filters.start_date.as_deref(), filters.limit_days) {
+        (Some(start), _) => Some(parse_naive_date(start)?),
+        (None, Some(days)) => Some(end - Duration::days(i64::from(days.max(1)) - 1)),
+        (None, None) => None,
+    };
+    let workouts = list_workouts_in(
+        service,
+        &WorkoutFiltersCmdParams {
+            exercise_name,
+            exercise_type: filters.exercise_type.clone(),
+            muscle: filters.muscle.clone(),
+            ..Default::default()
+        },
+        tz,
+    )?;
+    Ok(GraphSeries {
+        label: query.label.clone().unwrap_or_else(|| default_label(query)),
+        metric: query.metric.clone(),
+        points: bucketed_series(&workouts, metric, tz, period, start, end),
+    })
+}
+
+/// Longest range a heatmap covers, about ten years.
+const MAX_HEATMAP_DAYS: i64 = 3660;
+
+#[derive(Serialize)]
+pub struct ActivityHeatmap {
+    pub metric: String,
+    /// The highest daily value, for scaling the colours.
+    pub max: f64,
+    /// Every day from start to end, 0 on rest days.
+    pub days: Vec<(NaiveDate, f64)>,
+}
+
+fn heatmap_metric(s: &str) -> Result<Metric, String> {
+    match s.to_lowercase().as_str() {
+        "count" | "workouts" => Ok(Metric::WorkoutCount),
+        "sets" => Ok(Metric::WorkoutSets),
+        "volume" => Ok(Metric::WorkoutVolume),
+        "duration" => Ok(Metric::WorkoutDuration),
+        "distance" => Ok(Metric::WorkoutDistance),
+        _ => Err(format!(
+            "Invalid heatmap metric '{}'; use count, sets, volume, duration or distance",
+            s
+        )),
+    }
+}
+
+pub fn activity_heatmap(
+    service: &AppService,
+    start: NaiveDate,
+    end: NaiveDate,
+    metric_name: &str,
+) -> Result<ActivityHeatmap, String> {
+    let metric = heatmap_metric(metric_name)?;
+    if end < start {
+        return Err(format!("End date {} is before start date {}", end, start));
+    }
+    if (end - start).num_days() >= MAX_HEATMAP_DAYS {
+        return Err(format!(
+            "A heatmap covers at most {} days",
+            MAX_HEATMAP_DAYS
+        ));
+    }
+    let tz = timezone::user_time_zone(service);
+    let workouts = records::all_workouts(service)?;
+    let values: BTreeMap<NaiveDate, f64> =
+        daily_series(&workouts, metric, tz, Some(start), Some(end))
+            .into_iter()
+            .collect();
+    let days: Vec<(NaiveDate, f64)> = start
+        .iter_days()
+        .take_while(|day| *day <= end)
+        .map(|day| (day, values.get(&day).copied().unwrap_or(0.0)))
+        .collect();
+    Ok(ActivityHeatmap {
+        metric: metric_name.to_lowercase(),
+        max: days.iter().map(|(_, v)| *v).fold(0.0, f64::max),
+        days,
+    })
+}
+
+// --- Tauri Commands ---
+
+/// Graph series over any set of workouts picked with the volume filters, e.g.
+/// weekly volume of all resistance exercises or monthly distance of all
+/// cardio. Each series is computed on its own; leaving out `start_date` starts
+/// at its first workout, and `limit_days` keeps only the last N days up to
+/// `end_date` (today by default).
+#[tauri::command]
+pub async fn get_aggregate_graph(
+    payload: AggregateGraphPayload,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<GraphSeries>, String> {
+    run_blocking(&state, move |pool| {
+        let service = pool.read()?;
+        let period = payload
+            .bucket
+            .as_deref()
+            .map(Period::parse)
+            .transpose()?
+            .unwrap_or(Period::Day);
+        let tz = timezone::user_time_zone(&service);
+        payload
+            .series
+            .iter()
+            .map(|query| aggregate_series(&service, query, period, tz))
+            .collect()
+    })
+    .await
+}
+
+/// Per-day workout count, sets, volume, duration or distance over any local
+/// date range, e.g. a year for a GitHub-style heatmap. `metric` defaults to
+/// count.
+#[tauri::command]
+pub async fn get_activity_heatmap(
+    start: String,
+    end: String,
+    metric: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<ActivityHeatmap, String> {
+    let start = parse_naive_date(&start)?;
+    let end = parse_naive_date(&end)?;
+    let metric = metric.unwrap_or_else(|| "count".to_string());
+    run_blocking(&state, move |pool| {
+        activity_heatmap(&pool.read()?, start, end, &metric)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::records::WorkoutSnapshot;
+    use crate::settings::GuiSettings;
+    use chrono::{TimeZone, Utc};
+
+    fn temp_service(name: &str) -> AppService {
+        let dir =
+            std::env::temp_dir().join(format!("task-athlete-gui-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        AppService::initialize_with_paths(&dir.join("config.toml"), &dir.join("workouts.sqlite"))
+            .unwrap()
+    }
+
+    fn log_at(service: &mut AppService, day: u32, hour: u32, sets: i64) {
+        let snapshot = WorkoutSnapshot {
+            id: 0,
+            timestamp: Utc.with_ymd_and_hms(2024, 6, day, hour, 30, 0).unwrap(),
+            exercise_name: "Squat".to_string(),
+            exercise_type: Some("resistance".to_string()),
+            sets: Some(sets),
+            reps: Some(5),
+            weight: Some(100.0),
+            duration_minutes: None,
+            distance: None,
+            bodyweight: None,
+            notes: None,
+        };
+        records::add_snapshot(service, &snapshot).unwrap();
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        // 2024-06-02 is a Sunday.
+        assert_eq!(Period::Week.start_of(date(2024, 6, 2)), date(2024, 5, 27));
+        assert_eq!(Period::Week.start_of(date(2024, 6, 3)), date(2024, 6, 3));
+        assert_eq!(Period::Week.next(date(2024, 5, 27)), date(2024, 6, 3));
+    }
+
+    #[test]
+    fn months_start_on_the_first() {
+        assert_eq!(Period::Month.start_of(date(2024, 2, 29)), date(2024, 2, 1));
+        assert_eq!(Period::Month.next(date(2024, 1, 1)), date(2024, 2, 1));
+        assert_eq!(Period::Month.next(date(2024, 12, 1)), date(2025, 1, 1));
+    }
+
+    #[test]
+    fn days_are_their_own_bucket() {
+        assert_eq!(Period::Day.start_of(date(2024, 6, 2)), date(2024, 6, 2));
+        assert_eq!(Period::Day.next(date(2024, 2, 28)), date(2024, 2, 29));
+    }
+
+    #[test]
+    fn parse_ignores_case() {
+        assert_eq!(Period::parse("Week"), Ok(Period::Week));
+        assert!(Period::parse("fortnight").is_err());
+    }
+
+    #[test]
+    fn summed_metrics_get_a_bucket_for_every_period() {
+        let series = bucketed_series(
+            &[],
+            Metric::WorkoutSets,
+            chrono_tz::UTC,
+            Period::Week,
+            Some(date(2024, 6, 2)),
+            date(2024, 6, 17),
+        );
+        assert_eq!(
+            series,
+            vec![
+                (date(2024, 5, 27), 0.0),
+                (date(2024, 6, 3), 0.0),
+                (date(2024, 6, 10), 0.0),
+                (date(2024, 6, 17), 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn peak_metrics_have_no_empty_buckets() {
+        let series = bucketed_series(
+            &[],
+            Metric::MaxWeight,
+            chrono_tz::UTC,
+            Period::Month,
+            Some(date(2024, 1, 1)),
+            date(2024, 6, 30),
+        );
+        assert!(series.is_empty());
+    }
+
+    #[test]
+    fn heatmap_covers_every_local_day() {
+        let mut service = temp_service("heatmap-days");
+        let settings = GuiSettings {
+            time_zone: Some("Europe/Berlin".to_string()),
+            ..GuiSettings::default()
+        };
+        settings.save(&service).unwrap();
+        log_at(&mut service, 3, 8, 3);
+        log_at(&mut service, 3, 18, 2);
+        // 23:30 UTC is already the next day in Berlin.
+        log_at(&mut service, 4, 23, 4);
+        log_at(&mut service, 20, 8, 1);
+
+        let heatmap =
+            activity_heatmap(&service, date(2024, 6, 2), date(2024, 6, 6), "Sets").unwrap();
+        assert_eq!(heatmap.metric, "sets");
+        assert_eq!(heatmap.max, 5.0);
+        assert_eq!(
+            heatmap.days,
+            vec![
+                (date(2024, 6, 2), 0.0),
+                (date(2024, 6, 3), 5.0),
+                (date(2024, 6, 4), 0.0),
+                (date(2024, 6, 5), 4.0),
+                (date(2024, 6, 6), 0.0),
+            ]
+        );
+
+        let counts =
+            activity_heatmap(&service, date(2024, 6, 3), date(2024, 6, 3), "count").unwrap();
+        assert_eq!(counts.days, vec![(date(2024, 6, 3), 2.0)]);
+    }
+
+    #[test]
+    fn heatmap_rejects_bad_ranges_and_metrics() {
+        let service = temp_service("heatmap-invalid");
+        assert!(activity_heatmap(&service, date(2024, 6, 2), date(2024, 6, 1), "count").is_err());
+        assert!(activity_heatmap(&service, date(2014, 1, 1), date(2024, 6, 1), "count").is_err());
+        assert!(activity_heatmap(&service, date(2024, 6, 1), date(2024, 6, 2), "reps").is_err());
+        let empty =
+            activity_heatmap(&service, date(2024, 6, 1), date(2024, 6, 2), "volume").unwrap();
+        assert_eq!(empty.max, 0.0);
+        assert_eq!(empty.days.len(), 2);
+    }
+}
@@ -1,21 +1,55 @@
-use chrono::{DateTime, NaiveDate, Utc};
-use serde::{Deserialize, Serialize}; // Serialize might be needed if any local structs are returned
+use chrono::{DateTime, Datelike, Months, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::sync::{Arc, Mutex};
-// For HashMap in list_aliases, though often implicitly available via std prelude
-// use std::collections::HashMap; // Explicit import not strictly necessary for HashMap usually
+use tauri::Manager;
+
+mod audit;
+mod batch;
+mod catalog;
+mod comparison;
+mod copy;
+mod database;
+mod events;
+mod exercise_details;
+mod graphs;
+mod merge;
+mod muscles;
+mod one_rm;
+mod pb_notifications;
+mod personal_records;
+mod profiles;
+mod records;
+mod settings;
+pub mod state;
+mod streaks;
+mod summary;
+mod sync_changes;
+mod timezone;
+mod trash;
+mod trends;
+mod undo;
+mod volume;
+
+use audit::{Capture, Origin, Scope};
+use exercise_details::{DetailsPatch, ExerciseDetails};
+use muscles::MuscleTargets;
+use profiles::{ProfileRegistry, ProfilesState};
+use records::{ExerciseSnapshot, WorkoutSnapshot};
+use settings::GuiSettings;
+use state::{run_blocking, AppState, ServicePool};
+use sync_changes::SyncChanges;
+use undo::{Operation, UndoHistory, UndoState};
 
-// Import your library crate - assuming the name is task_athlete_lib
 use task_athlete_lib::{
     AddWorkoutParams,
     AppService,
     Config,
-    // ConfigError, // Handled as String
-    // DbError, // Handled as String
     EditWorkoutParams,
     ExerciseDefinition,
     ExerciseStats,
     ExerciseType,
-    GraphType,
     PBInfo,
     PersonalBests,
     Units,
@@ -27,16 +61,52 @@ use task_athlete_lib::{
 };
 
 
-
-// Type alias for the shared state
-type AppState = Arc<Mutex<AppService>>;
-
 #[derive(Deserialize, Serialize)]
 struct SyncResultPayload {
     sent: SyncSummary,
     received: SyncSummary,
 }
 
+#[derive(Serialize)]
+struct ConfigPayload {
+    #[serde(flatten)]
+    config: Config,
+    #[serde(flatten)]
+    gui: GuiSettings,
+    // The zone day boundaries are computed in: `time_zone` or the system's.
+    effective_time_zone: String,
+    profile: String,
+}
+
+/// A workout plus the day it falls on in the user's time zone. The frontend
+/// groups by `local_date` rather than the UTC timestamp.
+#[derive(Serialize)]
+struct LocalWorkout {
+    #[serde(flatten)]
+    workout: Workout,
+    local_date: NaiveDate,
+}
+
+/// The lib's stats plus the best estimated 1RM with the configured formula.
+#[derive(Serialize)]
+struct ExerciseStatsPayload {
+    #[serde(flatten)]
+    stats: ExerciseStats,
+    best_estimated_1rm: Option<f64>,
+}
+
+/// An exercise plus the details only the GUI stores (instructions, equipment,
+/// reference link, media).
+#[derive(Serialize)]
+struct ExerciseWithDetails {
+    #[serde(flatten)]
+    definition: ExerciseDefinition,
+    /// `definition.muscles` minus the secondary ones, which are in `details`.
+    primary_muscles: Vec<String>,
+    #[serde(flatten)]
+    details: ExerciseDetails,
+}
+
 struct SyncPrelude {
     server_url: String,
     last_sync_ts: Option<DateTime<Utc>>,
@@ -93,7 +163,10 @@ struct EditWorkoutCmdParams {
     pub new_duration: Option<i64>,
     pub new_distance_arg: Option<f64>,
     pub new_notes: Option<String>,
+    // Date only ("YYYY-MM-DD"); the workout keeps its local time of day.
     pub new_date: Option<String>,
+    // Full RFC3339 timestamp. Can't be combined with `new_date`.
+    pub new_timestamp: Option<String>,
 }
 
 #[derive(Deserialize, Default)]
@@ -138,6 +211,30 @@ fn parse_exercise_type(type_str: &str) -> Result<ExerciseType, String> {
         .map_err(|e| format!("Invalid exercise type string: {} ({})", type_str, e))
 }
 
+fn workout_filters(filters: &WorkoutFiltersCmdParams) -> Result<WorkoutFilters<'_>, String> {
+    Ok(WorkoutFilters {
+        exercise_name: filters.exercise_name.as_deref(),
+        date: filters.date.as_deref().map(parse_naive_date).transpose()?,
+        exercise_type: filters
+            .exercise_type
+            .as_deref()
+            .map(parse_exercise_type)
+            .transpose()?,
+        muscle: filters.muscle.as_deref(),
+        limit: filters.limit,
+    })
+}
+
+// Inverse of parse_exercise_type, for values the GUI stores itself.
+fn exercise_type_str(ex_type: &ExerciseType) -> String {
+    match ex_type {
+        ExerciseType::Resistance => "resistance",
+        ExerciseType::Cardio => "cardio",
+        ExerciseType::BodyWeight => "body-weight",
+    }
+    .to_string()
+}
+
 // --- Tauri Commands ---
 
 #[tauri::command]
@@ -149,13 +246,12 @@ fn greet(name: &str) -> String {
 #[tauri::command]
 async fn perform_sync(
     server_url_override: Option<String>,
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
 ) -> Result<SyncResultPayload, String> {
-    // Phase 1: Read data from the database (synchronous)
-    let prelude = {
-        let service = state
-            .lock()
-            .map_err(|e| format!("Failed to lock state for sync (read phase): {}", e))?;
+    // Phase 1: Read data from the database (blocking pool)
+    let prelude = run_blocking(&state, move |pool| {
+        let service = pool.read()?;
 
         let server_url = service
             .get_server_url(server_url_override)
@@ -173,13 +269,14 @@ async fn perform_sync(
             bodyweights: local_changes.bodyweights.len(),
         };
 
-        SyncPrelude {
+        Ok(SyncPrelude {
             server_url,
             last_sync_ts,
             local_changes,
             summary_sent,
-        }
-    }; // Mutex lock is released here as `service` goes out of scope.
+        })
+    })
+    .await?;
 
     // Phase 2: Network communication (asynchronous)
     let client = sync_client::SyncClient::new(prelude.server_url.clone());
@@ -189,13 +286,15 @@ async fn perform_sync(
         .await
         .map_err(|e| format!("Sync communication with server failed: {}", e))?;
 
-    // Phase 3: Write data to the database (synchronous)
-    let summary_received = {
-        let mut service = state
-            .lock()
-            .map_err(|e| format!("Failed to lock state for sync (write phase): {}", e))?;
-
+    // Phase 3: Write data to the database (blocking pool)
+    let notify_app = app.clone();
+    let server_url = prelude.server_url;
+    let summary_received = run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
         println!("Applying server changes...");
+        let payload = serde_json::to_value(&server_response.data_to_client).unwrap_or_default();
+        let changes = SyncChanges::before(&service, &payload);
+        let mut capture = Capture::take(&mut service, &changes.scopes());
         let summary = service
             .apply_server_changes(server_response.data_to_client)
             .map_err(|e| format!("Failed to apply server changes: {}", e))?;
@@ -204,9 +303,19 @@ async fn perform_sync(
             .set_last_sync_timestamp(server_response.server_current_ts)
             .map_err(|e| format!("Failed to update last sync timestamp in config: {}", e))?;
 
+        let received = changes.workout_ids(&service);
+        capture.include_workouts(&received);
+        capture.include_exercises(&changes.added_exercises(&service));
+        capture.commit_synced(&mut service, &server_url);
+        if let Err(e) = exercise_details::adopt_carriers(&service) {
+            eprintln!("Failed to move synced exercise details: {}", e);
+        }
         println!("Local database and config updated with server changes.");
-        summary
-    }; // Mutex lock is released here.
+        pb_notifications::notify(&notify_app, &service, &received);
+        Ok(summary)
+    })
+    .await?;
+    events::sync_applied(&app, &summary_received);
 
     Ok(SyncResultPayload {
         sent: prelude.summary_sent,
@@ -215,56 +324,163 @@ async fn perform_sync(
 }
 
 #[tauri::command]
-fn set_sync_server_url(url: Option<String>, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let mut service = state.lock().map_err(|e| format!("Failed to lock state: {}", e))?;
-    service.set_sync_server_url(url).map_err(|e| e.to_string())
+async fn set_sync_server_url(
+    url: Option<String>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
+        let capture = Capture::take(&mut service, &[Scope::Config]);
+        service
+            .set_sync_server_url(url)
+            .map_err(|e| e.to_string())?;
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::config_changed(&app);
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-fn get_config(state: tauri::State<'_, AppState>) -> Result<Config, String> {
-    let service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    Ok(service.config.clone())
+async fn get_config(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<ConfigPayload, String> {
+    run_blocking(&state, move |pool| {
+        let registry = profiles::lock(&app)?;
+        let service = pool.read()?;
+        let gui = GuiSettings::load(&service)?;
+        let effective_time_zone = timezone::user_time_zone(&service).name().to_string();
+        drop(service);
+        Ok(ConfigPayload {
+            config: pool.config()?,
+            gui,
+            effective_time_zone,
+            profile: registry.active.clone(),
+        })
+    })
+    .await
 }
 
 #[tauri::command]
-fn save_config(state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service.save_config().map_err(|e| e.to_string())
+async fn save_config(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        let service = pool.write()?;
+        service.save_config().map_err(|e| e.to_string())
+    })
+    .await
 }
 
 #[tauri::command]
-fn set_bodyweight(weight: f64, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let mut service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service.set_bodyweight(weight).map_err(|e| e.to_string())
+async fn set_bodyweight(
+    weight: f64,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
+        let capture = Capture::take(&mut service, &[Scope::Config]);
+        service.set_bodyweight(weight).map_err(|e| e.to_string())?;
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::config_changed(&app);
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-fn list_workouts(
+async fn list_workouts(
     filters: WorkoutFiltersCmdParams,
     state: tauri::State<'_, AppState>,
+) -> Result<Vec<LocalWorkout>, String> {
+    run_blocking(&state, move |pool| {
+        let service = pool.read()?;
+        let tz = timezone::user_time_zone(&service);
+        let mut workouts = list_workouts_in(&service, &filters, tz)?;
+        one_rm::apply(&mut workouts, one_rm::user_formula(&service));
+        Ok(workouts
+            .into_iter()
+            .map(|workout| LocalWorkout {
+                local_date: timezone::local_date(tz, workout.timestamp),
+                workout,
+            })
+            .collect())
+    })
+    .await
+}
+
+/// `list_workouts` with `filters.date` taken as a day in `tz`. The lib matches
+/// UTC dates, so the UTC days around it are fetched and filtered here.
+/// Workouts come back newest first, like the lib lists them; with a limit,
+/// the most recent ones.
+fn list_workouts_in(
+    service: &AppService,
+    filters: &WorkoutFiltersCmdParams,
+    tz: Tz,
 ) -> Result<Vec<Workout>, String> {
-    let service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    let lib_filters = WorkoutFilters {
-        exercise_name: filters.exercise_name.as_deref(),
-        date: filters.date.map(|s| parse_naive_date(&s)).transpose()?,
-        exercise_type: filters
-            .exercise_type
-            .map(|s| parse_exercise_type(&s))
-            .transpose()?,
-        muscle: filters.muscle.as_deref(),
-        limit: filters.limit,
+    // The lib matches muscles as raw substrings; groups and synonyms are
+    // resolved against the taxonomy here instead, and the targeted exercises
+    // listed one by one.
+    let targeting = filters
+        .muscle
+        .as_deref()
+        .map(|muscle| muscles::exercises_targeting(service, muscle))
+        .transpose()?;
+    let mut lib_filters = workout_filters(filters)?;
+    lib_filters.muscle = None;
+    let names: Vec<Option<&str>> = match (&targeting, lib_filters.exercise_name) {
+        (Some(targeted), None) => targeted.iter().map(|name| Some(name.as_str())).collect(),
+        // A named exercise is listed as usual and checked against the muscle
+        // below.
+        (_, name) => vec![name],
     };
-    service
-        .list_workouts(&lib_filters)
-        .map_err(|e| e.to_string())
+    let date = lib_filters.date;
+    // A local day's workouts are only known once the UTC days around it are
+    // filtered, so the limit can't go to the lib then.
+    if date.is_some() {
+        lib_filters.limit = None;
+    }
+    let mut workouts = Vec::new();
+    for name in names {
+        lib_filters.exercise_name = name;
+        let Some(date) = date else {
+            workouts.extend(
+                service
+                    .list_workouts(&lib_filters)
+                    .map_err(|e| e.to_string())?,
+            );
+            continue;
+        };
+        for utc_date in [date.pred_opt(), Some(date), date.succ_opt()]
+            .into_iter()
+            .flatten()
+        {
+            lib_filters.date = Some(utc_date);
+            let listed = service
+                .list_workouts(&lib_filters)
+                .map_err(|e| e.to_string())?;
+            workouts.extend(
+                listed
+                    .into_iter()
+                    .filter(|w| timezone::local_date(tz, w.timestamp) == date),
+            );
+        }
+    }
+    if let Some(targeted) = &targeting {
+        workouts.retain(|w| {
+            targeted
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&w.exercise_name))
+        });
+    }
+    workouts.sort_by(|a, b| (b.timestamp, b.id).cmp(&(a.timestamp, a.id)));
+    if let Some(limit) = filters.limit {
+        workouts.truncate(limit as usize);
+    }
+    Ok(workouts)
 }
 
 #[tauri::command]
@@ -272,102 +488,225 @@ async fn get_workout_dates_for_month(
     query: MonthYearQuery,
     state: tauri::State<'_, AppState>,
 ) -> Result<Vec<String>, String> {
-    let service = state.lock().map_err(|e| {
-        format!(
-            "Failed to lock state for get_workout_dates_for_month: {}",
-            e
-        )
-    })?;
-    service
-        .get_workout_dates_for_month(query.year, query.month)
-        .map_err(|e| format!("Error fetching workout dates from lib: {}", e))
+    let first = NaiveDate::from_ymd_opt(query.year, query.month, 1)
+        .ok_or_else(|| format!("Invalid month {}-{}", query.year, query.month))?;
+    let next_month = first
+        .checked_add_months(Months::new(1))
+        .ok_or_else(|| format!("Invalid month {}-{}", query.year, query.month))?;
+    run_blocking(&state, move |pool| {
+        let service = pool.read()?;
+        let tz = timezone::user_time_zone(&service);
+        // A day either side covers every time zone's offset from UTC.
+        let from = first.pred_opt().unwrap_or(first);
+        let to = next_month.succ_opt().unwrap_or(next_month);
+        let timestamps = records::workout_times_between(
+            &service,
+            from.and_time(NaiveTime::MIN).and_utc(),
+            to.and_time(NaiveTime::MIN).and_utc(),
+        )?;
+        let days: BTreeSet<NaiveDate> = timestamps
+            .into_iter()
+            .map(|timestamp| timezone::local_date(tz, timestamp))
+            .collect();
+        Ok(days
+            .into_iter()
+            .filter(|day| day.year() == query.year && day.month() == query.month)
+            .map(|day| day.format("%Y-%m-%d").to_string())
+            .collect())
+    })
+    .await
 }
 
 #[tauri::command]
-fn add_workout(
+async fn add_workout(
     params: AddWorkoutCmdParams,
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
 ) -> Result<(i64, Option<PBInfo>), String> {
-    let mut service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    let date = match params.date {
-        Some(date_str) => parse_datetime(&date_str)?,
-        None => Utc::now(),
-    };
-    let implicit_type = params
-        .implicit_type
-        .map(|s| parse_exercise_type(&s))
-        .transpose()?;
-    let lib_params = AddWorkoutParams {
-        exercise_identifier: &params.exercise_identifier,
-        date,
-        sets: params.sets,
-        reps: params.reps,
-        weight: params.weight,
-        duration: params.duration,
-        distance: params.distance,
-        notes: params.notes,
-        implicit_type,
-        implicit_muscles: params.implicit_muscles,
-        bodyweight_to_use: params.bodyweight_to_use,
-    };
-    service.add_workout(lib_params).map_err(|e| e.to_string())
+    run_blocking(&state, move |pool| {
+        let mut history = undo::lock(&app)?;
+        let mut service = pool.write()?;
+        let date = match params.date {
+            Some(date_str) => parse_datetime(&date_str)?,
+            None => Utc::now(),
+        };
+        let implicit_type = params
+            .implicit_type
+            .map(|s| parse_exercise_type(&s))
+            .transpose()?;
+        let lib_params = AddWorkoutParams {
+            exercise_identifier: &params.exercise_identifier,
+            date,
+            sets: params.sets,
+            reps: params.reps,
+            weight: params.weight,
+            duration: params.duration,
+            distance: params.distance,
+            notes: params.notes,
+            implicit_type,
+            implicit_muscles: params
+                .implicit_muscles
+                .and_then(|m| muscles::join(&muscles::parse_list(Some(&m)))),
+            bodyweight_to_use: params.bodyweight_to_use,
+        };
+        // The lib creates the exercise if it doesn't exist yet.
+        let exercise = [params.exercise_identifier.clone()];
+        let mut capture = Capture::take(&mut service, &[Scope::Exercises(&exercise)]);
+        let (id, pb_info) = service.add_workout(lib_params).map_err(|e| e.to_string())?;
+        capture.include_workouts(&[id]);
+        match records::find_workout(&service, id) {
+            Ok(workout) => history.record(Operation::AddedWorkout {
+                id,
+                snapshot: WorkoutSnapshot::from(&workout),
+            }),
+            Err(e) => eprintln!(
+                "Added workout {} but could not record it for undo: {}",
+                id, e
+            ),
+        }
+        capture.commit(&mut service, Origin::Local);
+        let day = timezone::local_date(timezone::user_time_zone(&service), date);
+        events::workouts_changed(&app, vec![id], vec![day]);
+        pb_notifications::notify(&app, &service, &[id]);
+        Ok((id, pb_info))
+    })
+    .await
 }
 
 #[tauri::command]
-fn edit_workout(
+async fn edit_workout(
     params: EditWorkoutCmdParams,
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
 ) -> Result<u64, String> {
-    let service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    let lib_params = EditWorkoutParams {
-        id: params.id,
-        new_exercise_identifier: params.new_exercise_identifier,
-        new_sets: params.new_sets,
-        new_reps: params.new_reps,
-        new_weight: params.new_weight,
-        new_bodyweight: params.new_bodyweight,
-        new_duration: params.new_duration,
-        new_distance_arg: params.new_distance_arg,
-        new_notes: params.new_notes,
-        new_date: params.new_date.map(|s| parse_naive_date(&s)).transpose()?,
-    };
-    service.edit_workout(lib_params).map_err(|e| e.to_string())
+    run_blocking(&state, move |pool| {
+        let mut history = undo::lock(&app)?;
+        let mut service = pool.write()?;
+        let before = WorkoutSnapshot::from(&records::find_workout(&service, params.id)?);
+        let tz = timezone::user_time_zone(&service);
+        let new_timestamp = match (params.new_date, params.new_timestamp) {
+            (Some(_), Some(_)) => {
+                return Err("Set either new_date or new_timestamp, not both".to_string())
+            }
+            (Some(date), None) => Some(timezone::on_local_date(
+                tz,
+                before.timestamp,
+                parse_naive_date(&date)?,
+            )),
+            (None, Some(timestamp)) => Some(parse_datetime(&timestamp)?),
+            (None, None) => None,
+        };
+        let capture = Capture::take(&mut service, &[Scope::Workouts(&[params.id])]);
+        let lib_params = EditWorkoutParams {
+            id: params.id,
+            new_exercise_identifier: params.new_exercise_identifier,
+            new_sets: params.new_sets,
+            new_reps: params.new_reps,
+            new_weight: params.new_weight,
+            new_bodyweight: params.new_bodyweight,
+            new_duration: params.new_duration,
+            new_distance_arg: params.new_distance_arg,
+            new_notes: params.new_notes,
+            new_date: None,
+        };
+        let updated = match records::edit_workout_at(&service, lib_params, new_timestamp) {
+            Ok(updated) => updated,
+            Err(e) => {
+                // The lib's part of the edit may have gone through; put it back.
+                let current = WorkoutSnapshot::from(&records::find_workout(&service, params.id)?);
+                let op = Operation::EditedWorkout {
+                    id: params.id,
+                    before: before.clone(),
+                    after: current,
+                };
+                if let Err(rollback) = history.roll_back(&mut service, vec![op]) {
+                    eprintln!("Failed to roll back workout edit: {}", rollback);
+                }
+                return Err(e);
+            }
+        };
+        let after = WorkoutSnapshot::from(&records::find_workout(&service, params.id)?);
+        let dates = vec![
+            timezone::local_date(tz, before.timestamp),
+            timezone::local_date(tz, after.timestamp),
+        ];
+        history.record(Operation::EditedWorkout {
+            id: params.id,
+            before,
+            after,
+        });
+        capture.commit(&mut service, Origin::Local);
+        events::workouts_changed(&app, vec![params.id], dates);
+        Ok(updated)
+    })
+    .await
 }
 
 #[tauri::command]
-fn delete_workouts(ids: Vec<i64>, state: tauri::State<'_, AppState>) -> Result<Vec<i64>, String> {
-    let service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service.delete_workouts(&ids).map_err(|e| e.to_string())
+async fn delete_workouts(
+    ids: Vec<i64>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<i64>, String> {
+    run_blocking(&state, move |pool| {
+        let mut history = undo::lock(&app)?;
+        let mut service = pool.write()?;
+        let capture = Capture::take(&mut service, &[Scope::Workouts(&ids)]);
+        let (deleted, entry_id) = trash::trash_workouts(&service, &ids)?;
+        history.record(Operation::TrashedWorkouts { entry_id });
+        capture.commit(&mut service, Origin::Local);
+        events::workouts_changed(&app, deleted.clone(), Vec::new());
+        Ok(deleted)
+    })
+    .await
 }
 
+/// Archived exercises are left out unless `include_archived` is set.
+/// `muscles_filter` keeps exercises working any of the given muscles or
+/// muscle groups, primarily or secondarily.
 #[tauri::command]
-fn list_exercises(
+async fn list_exercises(
     type_filter_str: Option<String>,
     muscles_filter: Option<Vec<String>>,
+    include_archived: Option<bool>,
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<ExerciseDefinition>, String> {
-    let muscle_refs: Option<Vec<&str>> = muscles_filter
-        .as_ref()
-        .map(|m| m.iter().map(|s| s.as_str()).collect());
-    let service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    let type_filter = type_filter_str
-        .map(|s| parse_exercise_type(&s))
-        .transpose()?;
-    service
-        .list_exercises(type_filter, muscle_refs)
-        .map_err(|e| e.to_string())
+) -> Result<Vec<ExerciseWithDetails>, String> {
+    run_blocking(&state, move |pool| {
+        let service = pool.read()?;
+        let type_filter = type_filter_str
+            .map(|s| parse_exercise_type(&s))
+            .transpose()?;
+        let mut exercises = service
+            .list_exercises(type_filter, None)
+            .map_err(|e| e.to_string())?;
+        if !include_archived.unwrap_or(false) {
+            exercises.retain(|def| def.is_active);
+        }
+        if let Some(filters) = muscles_filter.filter(|f| !f.is_empty()) {
+            exercises.retain(|def| {
+                muscles::matches_any(&muscles::parse_list(def.muscles.as_deref()), &filters)
+            });
+        }
+        let mut details = exercise_details::load_all(&service)?;
+        Ok(exercises
+            .into_iter()
+            .map(|definition| {
+                let mut details = details.remove(&definition.id).unwrap_or_default();
+                let targets = MuscleTargets::for_exercise(&definition, &details);
+                details.secondary_muscles = targets.secondary;
+                ExerciseWithDetails {
+                    primary_muscles: targets.primary,
+                    details,
+                    definition,
+                }
+            })
+            .collect())
+    })
+    .await
 }
 
 #[tauri::command]
-fn create_exercise(
+async fn create_exercise(
     name: String,
     type_str: String,
     muscles: Option<String>,
@@ -375,20 +714,46 @@ fn create_exercise(
     log_reps: Option<bool>,
     log_duration: Option<bool>,
     log_distance: Option<bool>,
+    instructions: Option<String>,
+    equipment: Option<String>,
+    reference_url: Option<String>,
+    secondary_muscles: Option<Vec<String>>,
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
 ) -> Result<i64, String> {
-    let service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    let ex_type = parse_exercise_type(&type_str)?;
-    let log_flags = Some((log_weight, log_reps, log_duration, log_distance));
-    service
-        .create_exercise(&name, ex_type, log_flags, muscles.as_deref())
-        .map_err(|e| e.to_string())
+    run_blocking(&state, move |pool| {
+        // `muscles` are the primary ones; the lib stores both lists together.
+        let targets = MuscleTargets::new(
+            &muscles::parse_list(muscles.as_deref()),
+            &secondary_muscles.unwrap_or_default(),
+        );
+        let details = DetailsPatch {
+            instructions: instructions.map(Some),
+            equipment: equipment.map(Some),
+            reference_url: reference_url.map(Some),
+            secondary_muscles: Some(targets.secondary.clone()),
+        };
+        details.check()?;
+        let mut history = undo::lock(&app)?;
+        let mut service = pool.write()?;
+        let ex_type = parse_exercise_type(&type_str)?;
+        let log_flags = Some((log_weight, log_reps, log_duration, log_distance));
+        let capture = Capture::take(&mut service, &[Scope::Exercises(&[name.clone()])]);
+        let id = service
+            .create_exercise(&name, ex_type, log_flags, targets.lib_string().as_deref())
+            .map_err(|e| e.to_string())?;
+        history.record(Operation::CreatedExercise { name });
+        let saved = exercise_details::update(&service, id, &details);
+        capture.commit(&mut service, Origin::Local);
+        events::exercises_changed(&app);
+        saved?;
+        Ok(id)
+    })
+    .await
 }
 
 #[tauri::command]
-fn edit_exercise(
+async fn edit_exercise(
     identifier: String,
     new_name: Option<String>,
     new_type_str: Option<String>,
@@ -397,261 +762,532 @@ fn edit_exercise(
     log_reps: Option<bool>,
     log_duration: Option<bool>,
     log_distance: Option<bool>,
+    new_instructions: Option<Option<String>>,
+    new_equipment: Option<Option<String>>,
+    new_reference_url: Option<Option<String>>,
+    new_secondary_muscles: Option<Vec<String>>,
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
 ) -> Result<u64, String> {
-    let mut service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    let new_type = new_type_str.map(|s| parse_exercise_type(&s)).transpose()?;
-    let new_muscles_ref = new_muscles.as_ref().map(|opt_s| opt_s.as_deref());
-    let log_flags = Some((log_weight, log_reps, log_duration, log_distance));
-    service
-        .edit_exercise(
-            &identifier,
-            new_name.as_deref(),
-            new_type,
-            log_flags,
-            new_muscles_ref,
-        )
-        .map_err(|e| e.to_string())
-}
-
-#[tauri::command]
-fn delete_exercise(
+    run_blocking(&state, move |pool| {
+        let mut details = DetailsPatch {
+            instructions: new_instructions,
+            equipment: new_equipment,
+            reference_url: new_reference_url,
+            secondary_muscles: None,
+        };
+        details.check()?;
+        let mut history = undo::lock(&app)?;
+        let mut service = pool.write()?;
+        let new_type = new_type_str.map(|s| parse_exercise_type(&s)).transpose()?;
+        // Details are stored by id, which a rename keeps.
+        let definition = records::resolve_exercise(&service, &identifier)?;
+        let exercise_id = definition.id;
+        let before = ExerciseSnapshot::new(
+            &definition,
+            Vec::new(),
+            exercise_details::load(&service, exercise_id)?,
+        );
+        // `new_muscles` replaces the primary muscles; whichever list isn't given
+        // is kept.
+        let new_muscles = if new_muscles.is_some() || new_secondary_muscles.is_some() {
+            let current = MuscleTargets::for_exercise(&definition, &before.details);
+            let targets = MuscleTargets::new(
+                &new_muscles.map_or(current.primary, |m| muscles::parse_list(m.as_deref())),
+                &new_secondary_muscles.unwrap_or(current.secondary),
+            );
+            details.secondary_muscles = Some(targets.secondary.clone());
+            Some(targets.lib_string())
+        } else {
+            None
+        };
+        let new_muscles_ref = new_muscles.as_ref().map(|opt_s| opt_s.as_deref());
+        let lib_change = new_name.is_some()
+            || new_type.is_some()
+            || new_muscles.is_some()
+            || [log_weight, log_reps, log_duration, log_distance]
+                .iter()
+                .any(Option::is_some);
+        let log_flags = Some((log_weight, log_reps, log_duration, log_distance));
+        // A rename rewrites the exercise's workouts and aliases.
+        let exercise = [exercise_id.to_string()];
+        let capture = Capture::take(
+            &mut service,
+            &[
+                Scope::Exercises(&exercise),
+                Scope::WorkoutsOf(&exercise),
+                Scope::Aliases,
+            ],
+        );
+        let mut updated = 0;
+        if lib_change || details.is_empty() {
+            updated = service
+                .edit_exercise(
+                    &identifier,
+                    new_name.as_deref(),
+                    new_type,
+                    log_flags,
+                    new_muscles_ref,
+                )
+                .map_err(|e| e.to_string())?;
+        }
+        let saved = exercise_details::update(&service, exercise_id, &details);
+        if !details.is_empty() {
+            updated = updated.max(1);
+        }
+        let edited = records::resolve_exercise(&service, &exercise[0]).and_then(|def| {
+            let details = exercise_details::load(&service, exercise_id)?;
+            Ok(ExerciseSnapshot::new(&def, Vec::new(), details))
+        });
+        match edited {
+            Ok(after) if after != before => {
+                history.record(Operation::EditedExercise { before, after })
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Failed to record the exercise edit for undo: {}", e),
+        }
+        capture.commit(&mut service, Origin::Local);
+        events::exercises_changed(&app);
+        // Workouts carry the exercise name, so a rename shows up in every list.
+        if new_name.is_some() {
+            events::workouts_changed(&app, Vec::new(), Vec::new());
+        }
+        saved?;
+        Ok(updated)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn delete_exercise(
     identifiers: Vec<String>,
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
 ) -> Result<u64, String> {
-    let mut service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service
-        .delete_exercise(&identifiers)
-        .map_err(|e| e.to_string())
+    run_blocking(&state, move |pool| {
+        let mut history = undo::lock(&app)?;
+        let mut service = pool.write()?;
+        let capture = Capture::take(
+            &mut service,
+            &[
+                Scope::Exercises(&identifiers),
+                Scope::WorkoutsOf(&identifiers),
+                Scope::Aliases,
+            ],
+        );
+        let (deleted, entry_id) = trash::trash_exercises(&mut service, &identifiers)?;
+        history.record(Operation::TrashedExercises { entry_id });
+        capture.commit(&mut service, Origin::Local);
+        events::exercises_changed(&app);
+        events::workouts_changed(&app, Vec::new(), Vec::new());
+        Ok(deleted)
+    })
+    .await
 }
 
+fn set_exercise_archived(
+    identifier: &str,
+    archived: bool,
+    app: &tauri::AppHandle,
+    pool: &ServicePool,
+) -> Result<(), String> {
+    let mut service = pool.write()?;
+    let definition = records::resolve_exercise(&service, identifier)?;
+    if definition.is_active != archived {
+        return Ok(());
+    }
+    let exercise = [definition.id.to_string()];
+    let capture = Capture::take(&mut service, &[Scope::Exercises(&exercise)]);
+    records::set_archived(
+        &service,
+        definition.id,
+        &definition.name,
+        definition.muscles.as_deref(),
+        archived,
+    )?;
+    capture.commit(&mut service, Origin::Local);
+    drop(service);
+    events::exercises_changed(app);
+    Ok(())
+}
+
+/// Hides an exercise from `list_exercises` and the exercise picker while
+/// keeping its workouts, stats and PBs.
 #[tauri::command]
-fn get_exercise_stats(
+async fn archive_exercise(
     identifier: String,
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
-) -> Result<ExerciseStats, String> {
-    let service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service
-        .get_exercise_stats(&identifier)
-        .map_err(|e| e.to_string())
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        set_exercise_archived(&identifier, true, &app, pool)
+    })
+    .await
 }
 
 #[tauri::command]
-fn get_data_for_graph(
+async fn unarchive_exercise(
+    identifier: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        set_exercise_archived(&identifier, false, &app, pool)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_exercise_stats(
+    identifier: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<ExerciseStatsPayload, String> {
+    run_blocking(&state, move |pool| {
+        let service = pool.read()?;
+        let mut stats = service
+            .get_exercise_stats(&identifier)
+            .map_err(|e| e.to_string())?;
+        // The lib counts streaks and first/last days on UTC dates.
+        let tz = timezone::user_time_zone(&service);
+        let workouts = records::workouts_for_exercise(&service, &stats.canonical_name)?;
+        let days = streaks::workout_days(&workouts, tz);
+        let streak = streaks::compute(&days, stats.streak_interval_days, timezone::today(tz));
+        stats.current_streak = streak.current;
+        stats.longest_streak = streak.longest;
+        stats.first_workout_date = days.first().copied();
+        stats.last_workout_date = days.last().copied();
+        let formula = one_rm::user_formula(&service);
+        let best_estimated_1rm = workouts
+            .iter()
+            .filter_map(|w| formula.estimate_workout(w))
+            .reduce(f64::max);
+        Ok(ExerciseStatsPayload {
+            stats,
+            best_estimated_1rm,
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+async fn get_data_for_graph(
     payload: GetDataForGraphPayload,
     state: tauri::State<'_, AppState>,
 ) -> Result<Vec<(NaiveDate, f64)>, String> {
-    let service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    let graph_type = match payload.graph_type_str.as_str() {
-        "Estimated1RM" => GraphType::Estimated1RM,
-        "MaxWeight" => GraphType::MaxWeight,
-        "MaxReps" => GraphType::MaxReps,
-        "WorkoutVolume" => GraphType::WorkoutVolume,
-        "WorkoutReps" => GraphType::WorkoutReps,
-        "WorkoutDuration" => GraphType::WorkoutDuration,
-        "WorkoutDistance" => GraphType::WorkoutDistance,
-        _ => return Err(format!("Invalid graph type: {}", payload.graph_type_str)),
-    };
+    run_blocking(&state, move |pool| graph_data(&pool.read()?, payload)).await
+}
+
+fn graph_data(
+    service: &AppService,
+    payload: GetDataForGraphPayload,
+) -> Result<Vec<(NaiveDate, f64)>, String> {
+    let metric = graphs::Metric::parse(&payload.graph_type_str, one_rm::user_formula(service))?;
     let start_date_filter = payload
         .start_date
         .map(|s| parse_naive_date(&s))
         .transpose()?;
     let end_date_filter = payload.end_date.map(|s| parse_naive_date(&s)).transpose()?;
-    service
-        .get_data_for_graph(
-            &payload.identifier,
-            graph_type,
-            start_date_filter,
-            end_date_filter,
-        )
-        .map_err(|e| e.to_string())
+    graphs::exercise_series(
+        service,
+        &payload.identifier,
+        metric,
+        timezone::user_time_zone(service),
+        start_date_filter,
+        end_date_filter,
+    )
 }
 
 #[tauri::command]
-fn list_aliases(
+async fn list_aliases(
     state: tauri::State<'_, AppState>,
 ) -> Result<std::collections::HashMap<String, String>, String> {
-    let service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service.list_aliases().map_err(|e| e.to_string())
+    run_blocking(&state, move |pool| {
+        let service = pool.read()?;
+        let mut aliases = service.list_aliases().map_err(|e| e.to_string())?;
+        aliases.retain(|alias, _| !exercise_details::is_carrier(alias));
+        Ok(aliases)
+    })
+    .await
 }
 
 #[tauri::command]
-fn create_alias(
+async fn create_alias(
     alias_name: String,
     exercise_identifier: String,
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
-    let service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service
-        .create_alias(&alias_name, &exercise_identifier)
-        .map_err(|e| e.to_string())
+    run_blocking(&state, move |pool| {
+        if exercise_details::is_carrier(&alias_name) {
+            return Err(format!("'{}' is reserved for exercise details", alias_name));
+        }
+        let mut service = pool.write()?;
+        let capture = Capture::take(&mut service, &[Scope::Aliases]);
+        service
+            .create_alias(&alias_name, &exercise_identifier)
+            .map_err(|e| e.to_string())?;
+        capture.commit(&mut service, Origin::Local);
+        events::exercises_changed(&app);
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-fn delete_alias(alias_name: String, state: tauri::State<'_, AppState>) -> Result<u64, String> {
-    let service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service.delete_alias(&alias_name).map_err(|e| e.to_string())
+async fn delete_alias(
+    alias_name: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<u64, String> {
+    run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
+        let capture = Capture::take(&mut service, &[Scope::Aliases]);
+        let deleted = service
+            .delete_alias(&alias_name)
+            .map_err(|e| e.to_string())?;
+        capture.commit(&mut service, Origin::Local);
+        events::exercises_changed(&app);
+        Ok(deleted)
+    })
+    .await
 }
 
 #[tauri::command]
-fn get_previous_workout_details(
+async fn get_previous_workout_details(
     payload: GetPreviousWorkoutDetailsPayload,
     state: tauri::State<'_, AppState>,
 ) -> Result<Vec<Workout>, String> {
-    let service = state.lock().map_err(|e| {
-        format!(
-            "Failed to lock state for get_previous_workout_details: {}",
-            e
-        )
-    })?;
     if payload.n == 0 {
         return Err("n must be greater than 0 for get_previous_workout_details".to_string());
     }
-    service
-        .list_workouts_for_exercise_on_nth_last_day(&payload.identifier, payload.n)
-        .map_err(|e| e.to_string())
+    run_blocking(&state, move |pool| {
+        let service = pool.read()?;
+        let mut workouts = service
+            .list_workouts_for_exercise_on_nth_last_day(&payload.identifier, payload.n)
+            .map_err(|e| e.to_string())?;
+        one_rm::apply(&mut workouts, one_rm::user_formula(&service));
+        Ok(workouts)
+    })
+    .await
 }
 
+/// Every muscle used by an exercise, mapped onto the taxonomy's names.
 #[tauri::command]
-fn list_all_muscles(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
-    let service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state for list_all_muscles: {}", e))?;
-    service
-        .list_all_muscles()
-        .map_err(|e| format!("Error fetching all muscles from lib: {}", e))
+async fn list_all_muscles(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
+    run_blocking(&state, move |pool| {
+        let service = pool.read()?;
+        let used = service
+            .list_all_muscles()
+            .map_err(|e| format!("Error fetching all muscles from lib: {}", e))?;
+        let mut all = muscles::normalize_all(used.iter().flat_map(|m| m.split(',')));
+        all.sort_by_key(|m| m.to_lowercase());
+        Ok(all)
+    })
+    .await
 }
 
 #[tauri::command]
-fn set_units(payload: SetUnitsPayload, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let mut service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    let units_enum = match payload.units.to_lowercase().as_str() {
-        "metric" => Units::Metric,
-        "imperial" => Units::Imperial,
-        _ => return Err(format!("Invalid units string: {}", payload.units)),
-    };
-    service.set_units(units_enum).map_err(|e| e.to_string())
+async fn set_units(
+    payload: SetUnitsPayload,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
+        let units_enum = match payload.units.to_lowercase().as_str() {
+            "metric" => Units::Metric,
+            "imperial" => Units::Imperial,
+            _ => return Err(format!("Invalid units string: {}", payload.units)),
+        };
+        let capture = Capture::take(&mut service, &[Scope::Config]);
+        service.set_units(units_enum).map_err(|e| e.to_string())?;
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::config_changed(&app);
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-fn set_streak_interval(days: u32, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let mut service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service.set_streak_interval(days).map_err(|e| e.to_string())
+async fn set_streak_interval(
+    days: u32,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
+        let capture = Capture::take(&mut service, &[Scope::Config]);
+        service
+            .set_streak_interval(days)
+            .map_err(|e| e.to_string())?;
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::config_changed(&app);
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-fn set_pb_notification_enabled(
+async fn set_pb_notification_enabled(
     enabled: bool,
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
-    let mut service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service
-        .set_pb_notification_enabled(enabled)
-        .map_err(|e| e.to_string())
+    run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
+        let capture = Capture::take(&mut service, &[Scope::Config]);
+        service
+            .set_pb_notification_enabled(enabled)
+            .map_err(|e| e.to_string())?;
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::config_changed(&app);
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-fn set_pb_notify_weight(enabled: bool, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let mut service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service
-        .set_pb_notify_weight(enabled)
-        .map_err(|e| e.to_string())
+async fn set_pb_notify_weight(
+    enabled: bool,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
+        let capture = Capture::take(&mut service, &[Scope::Config]);
+        service
+            .set_pb_notify_weight(enabled)
+            .map_err(|e| e.to_string())?;
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::config_changed(&app);
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-fn set_pb_notify_reps(enabled: bool, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let mut service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service
-        .set_pb_notify_reps(enabled)
-        .map_err(|e| e.to_string())
+async fn set_pb_notify_reps(
+    enabled: bool,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
+        let capture = Capture::take(&mut service, &[Scope::Config]);
+        service
+            .set_pb_notify_reps(enabled)
+            .map_err(|e| e.to_string())?;
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::config_changed(&app);
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-fn set_pb_notify_duration(enabled: bool, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let mut service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service
-        .set_pb_notify_duration(enabled)
-        .map_err(|e| e.to_string())
+async fn set_pb_notify_duration(
+    enabled: bool,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
+        let capture = Capture::take(&mut service, &[Scope::Config]);
+        service
+            .set_pb_notify_duration(enabled)
+            .map_err(|e| e.to_string())?;
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::config_changed(&app);
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-fn set_pb_notify_distance(enabled: bool, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let mut service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service
-        .set_pb_notify_distance(enabled)
-        .map_err(|e| e.to_string())
+async fn set_pb_notify_distance(
+    enabled: bool,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
+        let capture = Capture::take(&mut service, &[Scope::Config]);
+        service
+            .set_pb_notify_distance(enabled)
+            .map_err(|e| e.to_string())?;
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::config_changed(&app);
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-fn set_target_bodyweight(
+async fn set_target_bodyweight(
     weight: Option<f64>,
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
-    let mut service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    service
-        .set_target_bodyweight(weight)
-        .map_err(|e| e.to_string())
+    run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
+        let capture = Capture::take(&mut service, &[Scope::Config]);
+        service
+            .set_target_bodyweight(weight)
+            .map_err(|e| e.to_string())?;
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::config_changed(&app);
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-fn get_body_weights(
+async fn get_body_weights(
     state: tauri::State<'_, AppState>,
 ) -> Result<Vec<(i64, DateTime<Utc>, f64)>, String> {
-    let mut service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state: {}", e))?;
-    let bodyweights = service
-        .list_bodyweights(u32::MAX)
-        .map_err(|e| e.to_string())?;
-    Ok(bodyweights)
-}
-
-#[tauri::command]
-fn add_bodyweight_entry(weight: f64, state: tauri::State<'_, AppState>) -> Result<i64, String> {
-    let mut service = state
-        .lock()
-        .map_err(|e| format!("Failed to lock state for add_bodyweight_entry: {}", e))?;
-    let timestamp = Utc::now();
-    let entry_id = service
-        .add_bodyweight_entry(timestamp, weight)
-        .map_err(|e| format!("Failed to add bodyweight entry to log: {}", e.to_string()))?;
-    if let Err(e) = service.set_bodyweight(weight) {
-        eprintln!(
-            "Failed to update current_bodyweight in config after adding new entry: {}",
-            e.to_string()
-        );
-    }
-    Ok(entry_id)
+    run_blocking(&state, |pool| {
+        let mut service = pool.read()?;
+        service
+            .list_bodyweights(u32::MAX)
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
+
+#[tauri::command]
+async fn add_bodyweight_entry(
+    weight: f64,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<i64, String> {
+    run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
+        let capture = Capture::take(&mut service, &[Scope::Bodyweights, Scope::Config]);
+        let timestamp = Utc::now();
+        let entry_id = service
+            .add_bodyweight_entry(timestamp, weight)
+            .map_err(|e| format!("Failed to add bodyweight entry to log: {}", e.to_string()))?;
+        if let Err(e) = service.set_bodyweight(weight) {
+            eprintln!(
+                "Failed to update current_bodyweight in config after adding new entry: {}",
+                e.to_string()
+            );
+        }
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::bodyweight_changed(&app, vec![entry_id]);
+        events::config_changed(&app);
+        Ok(entry_id)
+    })
+    .await
 }
 
 // Main application entry point function
@@ -666,19 +1302,73 @@ pub fn run() {
         }
         Err(e) => {
             eprintln!("FATAL: Failed to initialize AppService: {:?}", e);
-            // In a real app, you might want to use Tauri's dialog API before exiting
-            // tauri::api::dialog::blocking::message(None::<&tauri::Window>, "Initialization Error", format!("Failed to initialize: {}", e));
             std::process::exit(1);
         }
     };
 
-    let app_state: AppState = Arc::new(Mutex::new(app_service));
+    let registry = match ProfileRegistry::load_or_create(&app_service) {
+        Ok(registry) => registry,
+        Err(e) => {
+            eprintln!("FATAL: Failed to load profiles: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let app_service = match registry.active_profile() {
+        Some(profile) if profile.name != profiles::DEFAULT_PROFILE => {
+            match profiles::open_profile_service(profile) {
+                Ok(service) => {
+                    println!("Active profile: '{}'", profile.name);
+                    println!("DB Path: {:?}", service.get_db_path());
+                    service
+                }
+                Err(e) => {
+                    eprintln!("{}. Falling back to the default profile.", e);
+                    app_service
+                }
+            }
+        }
+        _ => app_service,
+    };
+
+    let app_state: AppState = match ServicePool::new(app_service, state::DEFAULT_READER_COUNT) {
+        Ok(pool) => Arc::new(pool),
+        Err(e) => {
+            eprintln!("FATAL: Failed to open database handles: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let profiles_state: ProfilesState = Mutex::new(registry);
+    let undo_state: UndoState = Mutex::new(UndoHistory::default());
+    match app_state.write() {
+        Ok(service) => {
+            if let Err(e) = trash::purge_expired(&service) {
+                eprintln!("Failed to purge expired trash entries: {}", e);
+            }
+        }
+        Err(e) => eprintln!("{}", e),
+    }
 
     tauri::Builder::default()
         .manage(app_state)
-        .plugin(tauri_plugin_opener::init()) // Added from your initial lib.rs
+        .manage(profiles_state)
+        .manage(undo_state)
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
+        .setup(|app| {
+            if let Err(e) = audit::load_device_id(app.handle()) {
+                eprintln!("Failed to load the device id: {}", e);
+            }
+            let state = app.state::<AppState>();
+            let adopted = state
+                .write()
+                .and_then(|service| exercise_details::adopt_legacy_media(app.handle(), &service));
+            if let Err(e) = adopted {
+                eprintln!("Failed to move exercise media: {}", e);
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
-            greet, // Added from your initial lib.rs
+            greet,
             get_config,
             save_config,
             get_workout_dates_for_month,
@@ -691,15 +1381,27 @@ pub fn run() {
             create_exercise,
             edit_exercise,
             delete_exercise,
+            archive_exercise,
+            unarchive_exercise,
             get_exercise_stats,
             get_data_for_graph,
+            graphs::get_aggregate_graph,
+            graphs::get_activity_heatmap,
+            comparison::get_comparison_graph,
+            trends::get_graph_trends,
+            one_rm::calculate_rep_maxes,
+            one_rm::set_one_rm_formula,
+            personal_records::list_personal_records,
+            personal_records::get_pr_timeline,
+            streaks::get_streak_info,
+            summary::get_period_summary,
+            summary::get_period_comparison,
             list_aliases,
             create_alias,
             delete_alias,
             list_all_muscles,
             set_units,
             get_body_weights,
-            // set_bodyweight_prompt_enabled, // This was commented out in your original code
             set_streak_interval,
             set_pb_notification_enabled,
             set_pb_notify_weight,
@@ -710,8 +1412,135 @@ pub fn run() {
             get_previous_workout_details,
             add_bodyweight_entry,
             perform_sync,
-            set_sync_server_url
+            set_sync_server_url,
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::rename_profile,
+            profiles::delete_profile,
+            profiles::switch_profile,
+            database::move_database,
+            trash::list_trash,
+            trash::restore_from_trash,
+            trash::empty_trash,
+            trash::set_trash_retention_days,
+            undo::get_undo_status,
+            undo::undo,
+            undo::redo,
+            audit::get_record_history,
+            audit::list_recent_changes,
+            audit::revert_record,
+            batch::preview_bulk_edit,
+            batch::bulk_edit_workouts,
+            batch::bulk_delete_workouts,
+            batch::reorder_workouts,
+            copy::copy_workouts,
+            catalog::search_exercise_catalog,
+            catalog::import_catalog_exercises,
+            exercise_details::get_exercise_details,
+            exercise_details::add_exercise_media,
+            exercise_details::read_exercise_media,
+            exercise_details::remove_exercise_media,
+            merge::merge_exercises,
+            muscles::get_muscle_taxonomy,
+            muscles::normalize_exercise_muscles,
+            muscles::set_secondary_muscle_weight,
+            volume::get_muscle_volume,
+            timezone::set_time_zone,
+            timezone::list_time_zones
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn temp_service(name: &str) -> AppService {
+        let dir =
+            std::env::temp_dir().join(format!("task-athlete-gui-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        AppService::initialize_with_paths(&dir.join("config.toml"), &dir.join("workouts.sqlite"))
+            .unwrap()
+    }
+
+    fn log_at(service: &mut AppService, timestamp: DateTime<Utc>) -> i64 {
+        let snapshot = WorkoutSnapshot {
+            id: 0,
+            timestamp,
+            exercise_name: "Bench Press".to_string(),
+            exercise_type: Some("resistance".to_string()),
+            sets: Some(3),
+            reps: Some(5),
+            weight: Some(60.0),
+            duration_minutes: None,
+            distance: None,
+            bodyweight: None,
+            notes: None,
+        };
+        records::add_snapshot(service, &snapshot).unwrap().0
+    }
+
+    fn on_day(service: &AppService, date: &str, tz: Tz) -> Vec<i64> {
+        let filters = WorkoutFiltersCmdParams {
+            date: Some(date.to_string()),
+            ..Default::default()
+        };
+        list_workouts_in(service, &filters, tz)
+            .unwrap()
+            .iter()
+            .map(|w| w.id)
+            .collect()
+    }
+
+    #[test]
+    fn late_evening_workouts_list_under_their_local_day() {
+        let mut service = temp_service("local-day");
+        // 23:30 on May 10th in Los Angeles and in Tokyo.
+        let west = log_at(
+            &mut service,
+            Utc.with_ymd_and_hms(2024, 5, 11, 6, 30, 0).unwrap(),
+        );
+        let east = log_at(
+            &mut service,
+            Utc.with_ymd_and_hms(2024, 5, 10, 14, 30, 0).unwrap(),
+        );
+
+        let los_angeles = timezone::parse_time_zone("America/Los_Angeles").unwrap();
+        assert_eq!(
+            on_day(&service, "2024-05-10", los_angeles),
+            vec![west, east]
+        );
+        assert!(on_day(&service, "2024-05-11", los_angeles).is_empty());
+
+        let tokyo = timezone::parse_time_zone("Asia/Tokyo").unwrap();
+        assert_eq!(on_day(&service, "2024-05-10", tokyo), vec![east]);
+        assert_eq!(on_day(&service, "2024-05-11", tokyo), vec![west]);
+    }
+
+    #[test]
+    fn limit_keeps_the_most_recent_workouts() {
+        let mut service = temp_service("limit");
+        let ids: Vec<i64> = (0..3)
+            .map(|hour| {
+                log_at(
+                    &mut service,
+                    Utc.with_ymd_and_hms(2024, 5, 10, 8 + hour, 0, 0).unwrap(),
+                )
+            })
+            .collect();
+        let filters = WorkoutFiltersCmdParams {
+            date: Some("2024-05-10".to_string()),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let listed: Vec<i64> = list_workouts_in(&service, &filters, Tz::UTC)
+            .unwrap()
+            .iter()
+            .map(|w| w.id)
+            .collect();
+        assert_eq!(listed, vec![ids[2], ids[1]]);
+    }
+}
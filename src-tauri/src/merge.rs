@@ -0,0 +1,284 @@
+//! Merging duplicate exercises into one.
+//!
+//! Everything goes through the lib's own edit, delete and alias calls, so each
+//! change is marked for sync like any other edit.
+
+use serde::Serialize;
+
+use task_athlete_lib::{AppService, EditWorkoutParams, ExerciseDefinition, PersonalBests};
+
+use crate::audit::{Capture, Origin, Scope};
+use crate::events;
+use crate::records::{self, WorkoutSnapshot};
+use crate::state::run_blocking;
+use crate::trash;
+use crate::undo::{self, Operation, UndoHistory};
+use crate::{exercise_type_str, AppState};
+
+#[derive(Serialize, Default)]
+pub struct MergeResult {
+    pub target: String,
+    pub moved_workouts: Vec<i64>,
+    /// Names that now resolve to the target: the merged exercises and their
+    /// aliases.
+    pub new_aliases: Vec<String>,
+    /// The target's personal bests over the merged history, if it has any
+    /// workouts.
+    pub personal_bests: Option<PersonalBests>,
+}
+
+// Moves `source` into `target`, pushing each step onto `ops` as it succeeds.
+fn merge_one(
+    service: &mut AppService,
+    source: &ExerciseDefinition,
+    target: &ExerciseDefinition,
+    ops: &mut Vec<Operation>,
+    result: &mut MergeResult,
+) -> Result<(), String> {
+    for workout in records::workouts_for_exercise(service, &source.name)? {
+        let params = EditWorkoutParams {
+            id: workout.id,
+            new_exercise_identifier: Some(target.name.clone()),
+            new_sets: None,
+            new_reps: None,
+            new_weight: None,
+            new_bodyweight: None,
+            new_duration: None,
+            new_distance_arg: None,
+            new_notes: None,
+            new_date: None,
+        };
+        records::edit_workout_at(service, params, None)?;
+        let moved = records::find_workout(service, workout.id)?;
+        ops.push(Operation::EditedWorkout {
+            id: workout.id,
+            before: WorkoutSnapshot::from(&workout),
+            after: WorkoutSnapshot::from(&moved),
+        });
+        result.moved_workouts.push(workout.id);
+    }
+
+    // The source's aliases are removed before it goes to the trash, so
+    // restoring it on undo doesn't claim them back twice.
+    let aliases = records::aliases_of(service, &source.name)?;
+    for alias in &aliases {
+        service.delete_alias(alias).map_err(|e| e.to_string())?;
+        ops.push(Operation::RemovedAlias {
+            alias: alias.clone(),
+            target: source.name.clone(),
+        });
+    }
+    let (_, entry_id) = trash::trash_exercises(service, &[source.name.clone()])?;
+    ops.push(Operation::TrashedExercises { entry_id });
+
+    // The source's own name only frees up once it is deleted.
+    for alias in aliases.iter().chain([&source.name]) {
+        service
+            .create_alias(alias, &target.name)
+            .map_err(|e| format!("Failed to create alias '{}': {}", alias, e))?;
+        ops.push(Operation::AddedAlias {
+            alias: alias.clone(),
+            target: target.name.clone(),
+        });
+        result.new_aliases.push(alias.clone());
+    }
+    Ok(())
+}
+
+fn find_by_id(definitions: &[ExerciseDefinition], id: i64) -> Result<&ExerciseDefinition, String> {
+    definitions
+        .iter()
+        .find(|def| def.id == id)
+        .ok_or_else(|| format!("Exercise {} not found", id))
+}
+
+fn merge(
+    service: &mut AppService,
+    history: &mut UndoHistory,
+    source_ids: &[i64],
+    target_id: i64,
+) -> Result<MergeResult, String> {
+    let definitions = service
+        .list_exercises(None, None)
+        .map_err(|e| e.to_string())?;
+    let target = find_by_id(&definitions, target_id)?;
+    let mut sources: Vec<&ExerciseDefinition> = Vec::with_capacity(source_ids.len());
+    for &id in source_ids {
+        if id == target_id {
+            return Err("An exercise can't be merged into itself".to_string());
+        }
+        let source = find_by_id(&definitions, id)?;
+        // PBs only compare like with like, e.g. not a run's distance with a lift.
+        if exercise_type_str(&source.type_) != exercise_type_str(&target.type_) {
+            return Err(format!(
+                "Can't merge {} ({}) into {} ({}): the exercise types differ",
+                source.name,
+                exercise_type_str(&source.type_),
+                target.name,
+                exercise_type_str(&target.type_)
+            ));
+        }
+        if !sources.iter().any(|s| s.id == id) {
+            sources.push(source);
+        }
+    }
+
+    let mut result = MergeResult {
+        target: target.name.clone(),
+        ..Default::default()
+    };
+    let mut ops = Vec::new();
+    for source in sources {
+        if let Err(e) = merge_one(service, source, target, &mut ops, &mut result) {
+            if let Err(rollback) = history.roll_back(service, ops) {
+                eprintln!("Failed to roll back partial merge: {}", rollback);
+            }
+            return Err(format!("Failed to merge {}: {}", source.name, e));
+        }
+    }
+    history.record(Operation::Batch {
+        label: format!("Merge into {}", target.name),
+        ops,
+    });
+    // PBs are worked out from the workouts, which now all sit on the target.
+    result.personal_bests = service
+        .get_exercise_stats(&target.name)
+        .ok()
+        .map(|stats| stats.personal_bests);
+    Ok(result)
+}
+
+// --- Tauri Commands ---
+
+/// Merges the source exercises into the target: their workouts move to the
+/// target, their names and aliases become aliases of it, and the sources go
+/// to the trash. Undone as one step; on failure the steps done so far are
+/// rolled back.
+#[tauri::command]
+pub async fn merge_exercises(
+    source_ids: Vec<i64>,
+    target_id: i64,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<MergeResult, String> {
+    run_blocking(&state, move |pool| {
+        let mut history = undo::lock(&app)?;
+        let mut service = pool.write()?;
+        let sources: Vec<String> = source_ids.iter().map(i64::to_string).collect();
+        let exercises: Vec<String> = sources
+            .iter()
+            .cloned()
+            .chain([target_id.to_string()])
+            .collect();
+        let capture = Capture::take(
+            &mut service,
+            &[
+                Scope::Exercises(&exercises),
+                Scope::WorkoutsOf(&sources),
+                Scope::Aliases,
+            ],
+        );
+        let outcome = merge(&mut service, &mut history, &source_ids, target_id);
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::exercises_changed(&app);
+        if let Ok(result) = &outcome {
+            events::workouts_changed(&app, result.moved_workouts.clone(), Vec::new());
+        }
+        outcome
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exercise_details;
+    use chrono::{TimeZone, Utc};
+    use task_athlete_lib::ExerciseType;
+
+    fn temp_service(name: &str) -> AppService {
+        let dir =
+            std::env::temp_dir().join(format!("task-athlete-gui-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let service = AppService::initialize_with_paths(
+            &dir.join("config.toml"),
+            &dir.join("workouts.sqlite"),
+        )
+        .unwrap();
+        exercise_details::ensure_schema(&service).unwrap();
+        service
+    }
+
+    fn log(service: &mut AppService, exercise: &str, day: u32) -> i64 {
+        let snapshot = WorkoutSnapshot {
+            id: 0,
+            timestamp: Utc.with_ymd_and_hms(2024, 5, day, 12, 0, 0).unwrap(),
+            exercise_name: exercise.to_string(),
+            exercise_type: Some("resistance".to_string()),
+            sets: Some(3),
+            reps: Some(5),
+            weight: Some(80.0),
+            duration_minutes: None,
+            distance: None,
+            bodyweight: None,
+            notes: None,
+        };
+        records::add_snapshot(service, &snapshot).unwrap().0
+    }
+
+    fn create(service: &mut AppService, name: &str, type_: ExerciseType) -> i64 {
+        service.create_exercise(name, type_, None, None).unwrap()
+    }
+
+    #[test]
+    fn merge_moves_workouts_and_trashes_the_source() {
+        let mut service = temp_service("merge-moves");
+        let target = create(&mut service, "Bench Press", ExerciseType::Resistance);
+        let source = create(&mut service, "Barbell Bench", ExerciseType::Resistance);
+        service.create_alias("bb", "Barbell Bench").unwrap();
+        let kept = log(&mut service, "Bench Press", 1);
+        let moved = log(&mut service, "Barbell Bench", 2);
+
+        let mut history = UndoHistory::default();
+        let result = merge(&mut service, &mut history, &[source], target).unwrap();
+        assert_eq!(result.target, "Bench Press");
+        assert_eq!(result.moved_workouts, vec![moved]);
+        assert_eq!(result.new_aliases, vec!["bb", "Barbell Bench"]);
+        assert!(result.personal_bests.is_some());
+
+        let mut ids: Vec<i64> = records::workouts_for_exercise(&service, "Bench Press")
+            .unwrap()
+            .iter()
+            .map(|w| w.id)
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![kept, moved]);
+        let names: Vec<String> = service
+            .list_exercises(None, None)
+            .unwrap()
+            .into_iter()
+            .map(|d| d.name)
+            .collect();
+        assert_eq!(names, vec!["Bench Press"]);
+        for alias in ["bb", "Barbell Bench"] {
+            assert_eq!(
+                records::resolve_exercise(&service, alias).unwrap().id,
+                target
+            );
+        }
+    }
+
+    #[test]
+    fn merge_refuses_itself_and_other_types() {
+        let mut service = temp_service("merge-refuses");
+        let squat = create(&mut service, "Squat", ExerciseType::Resistance);
+        let run = create(&mut service, "Run", ExerciseType::Cardio);
+        let mut history = UndoHistory::default();
+        assert!(merge(&mut service, &mut history, &[squat], squat).is_err());
+        assert!(merge(&mut service, &mut history, &[run], squat).is_err());
+        assert!(merge(&mut service, &mut history, &[999], squat).is_err());
+        assert_eq!(service.list_exercises(None, None).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,471 @@
+//! The canonical muscle taxonomy.
+//!
+//! The lib stores an exercise's muscles as a free-form comma-separated string.
+//! Names are mapped onto the canonical muscles below on the way in, and
+//! filters take either a muscle or a group ("Legs" matches quads, hamstrings,
+//! glutes, ...). Names the taxonomy doesn't know are kept as custom muscles.
+//! A group's name is never read as one of its muscles: an exercise listing
+//! just "Back" keeps "Back", which a "Back" filter matches along with the
+//! group's muscles.
+//!
+//! The lib's string holds every muscle an exercise works, primary first, so
+//! other clients and sync still see them all. Which of them are secondary is
+//! kept in the exercise's details, which stay on this device.
+
+use serde::Serialize;
+use std::collections::HashSet;
+
+use task_athlete_lib::{AppService, ExerciseDefinition};
+
+use crate::audit::{Capture, Origin, Scope};
+use crate::events;
+use crate::exercise_details::{self, ExerciseDetails};
+use crate::settings::GuiSettings;
+use crate::state::run_blocking;
+use crate::AppState;
+
+pub struct MuscleGroup {
+    pub name: &'static str,
+    pub muscles: &'static [&'static str],
+}
+
+pub const GROUPS: &[MuscleGroup] = &[
+    MuscleGroup {
+        name: "Chest",
+        muscles: &["Chest"],
+    },
+    MuscleGroup {
+        name: "Back",
+        muscles: &["Lats", "Upper Back", "Traps", "Lower Back"],
+    },
+    MuscleGroup {
+        name: "Shoulders",
+        muscles: &["Shoulders", "Neck"],
+    },
+    MuscleGroup {
+        name: "Arms",
+        muscles: &["Biceps", "Triceps", "Forearms"],
+    },
+    MuscleGroup {
+        name: "Core",
+        muscles: &["Abs", "Obliques", "Hip Flexors"],
+    },
+    MuscleGroup {
+        name: "Legs",
+        muscles: &[
+            "Quads",
+            "Hamstrings",
+            "Glutes",
+            "Calves",
+            "Adductors",
+            "Abductors",
+        ],
+    },
+];
+
+// Other names people use, keyed the way `key` folds them.
+const SYNONYMS: &[(&str, &str)] = &[
+    ("pecs", "Chest"),
+    ("pectorals", "Chest"),
+    ("pectoralis", "Chest"),
+    ("pectoralis major", "Chest"),
+    ("upper chest", "Chest"),
+    ("lower chest", "Chest"),
+    ("lat", "Lats"),
+    ("latissimus", "Lats"),
+    ("latissimus dorsi", "Lats"),
+    ("rhomboids", "Upper Back"),
+    ("mid back", "Upper Back"),
+    ("middle back", "Upper Back"),
+    ("trap", "Traps"),
+    ("trapezius", "Traps"),
+    ("erectors", "Lower Back"),
+    ("spinal erectors", "Lower Back"),
+    ("erector spinae", "Lower Back"),
+    ("shoulder", "Shoulders"),
+    ("delts", "Shoulders"),
+    ("deltoids", "Shoulders"),
+    ("front delts", "Shoulders"),
+    ("side delts", "Shoulders"),
+    ("rear delts", "Shoulders"),
+    ("bicep", "Biceps"),
+    ("biceps brachii", "Biceps"),
+    ("tricep", "Triceps"),
+    ("triceps brachii", "Triceps"),
+    ("forearm", "Forearms"),
+    ("grip", "Forearms"),
+    ("abdominals", "Abs"),
+    ("rectus abdominis", "Abs"),
+    ("oblique", "Obliques"),
+    ("hip flexor", "Hip Flexors"),
+    ("iliopsoas", "Hip Flexors"),
+    ("quad", "Quads"),
+    ("quadriceps", "Quads"),
+    ("hamstring", "Hamstrings"),
+    ("hams", "Hamstrings"),
+    ("glute", "Glutes"),
+    ("gluteus", "Glutes"),
+    ("gluteus maximus", "Glutes"),
+    ("calf", "Calves"),
+    ("gastrocnemius", "Calves"),
+    ("soleus", "Calves"),
+    ("adductor", "Adductors"),
+    ("inner thigh", "Adductors"),
+    ("abductor", "Abductors"),
+    ("outer thigh", "Abductors"),
+];
+
+// Lowercase with `_`, `-` and runs of whitespace folded into single spaces.
+fn key(name: &str) -> String {
+    name.replace(['_', '-'], " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn all_muscles() -> impl Iterator<Item = &'static str> {
+    GROUPS
+        .iter()
+        .flat_map(|group| group.muscles.iter().copied())
+}
+
+/// The canonical name for `raw`, or `raw` tidied up if it isn't in the
+/// taxonomy. `None` for a blank name.
+pub fn normalize(raw: &str) -> Option<String> {
+    let key = key(raw);
+    if key.is_empty() {
+        return None;
+    }
+    let known = all_muscles()
+        .chain(GROUPS.iter().map(|group| group.name))
+        .find(|name| key == name.to_lowercase())
+        .or_else(|| {
+            SYNONYMS
+                .iter()
+                .find(|(synonym, _)| *synonym == key)
+                .map(|(_, muscle)| *muscle)
+        });
+    Some(match known {
+        Some(muscle) => muscle.to_string(),
+        None => raw.split_whitespace().collect::<Vec<_>>().join(" "),
+    })
+}
+
+/// Normalizes each name, dropping blanks and repeats but keeping the order.
+pub fn normalize_all<'a>(raw: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    raw.into_iter()
+        .filter_map(normalize)
+        .filter(|muscle| seen.insert(muscle.to_lowercase()))
+        .collect()
+}
+
+/// The muscles in a comma-separated string like the lib stores.
+pub fn parse_list(muscles: Option<&str>) -> Vec<String> {
+    normalize_all(muscles.unwrap_or_default().split(','))
+}
+
+pub fn join(muscles: &[String]) -> Option<String> {
+    (!muscles.is_empty()).then(|| muscles.join(", "))
+}
+
+/// The group a canonical muscle belongs to; `None` for custom muscles.
+pub fn group_of(muscle: &str) -> Option<&'static str> {
+    GROUPS
+        .iter()
+        .find(|group| group.muscles.iter().any(|m| m.eq_ignore_ascii_case(muscle)))
+        .map(|group| group.name)
+}
+
+/// The lowercased muscles a filter value stands for: a group's members and
+/// its name, or the muscle itself.
+pub fn expand(filter: &str) -> HashSet<String> {
+    let key = key(filter);
+    match GROUPS.iter().find(|group| key == group.name.to_lowercase()) {
+        Some(group) => group
+            .muscles
+            .iter()
+            .chain([&group.name])
+            .map(|m| m.to_lowercase())
+            .collect(),
+        None => normalize(filter)
+            .map(|m| m.to_lowercase())
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// Whether any of `muscles` is matched by any of `filters`.
+pub fn matches_any<S: AsRef<str>>(muscles: &[String], filters: &[S]) -> bool {
+    let wanted: HashSet<String> = filters.iter().flat_map(|f| expand(f.as_ref())).collect();
+    muscles.iter().any(|m| wanted.contains(&m.to_lowercase()))
+}
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct MuscleTargets {
+    pub primary: Vec<String>,
+    pub secondary: Vec<String>,
+}
+
+impl MuscleTargets {
+    /// Splits the lib's muscle string using the stored secondary list. The
+    /// lib's string wins: a secondary muscle it no longer lists is dropped,
+    /// and a muscle it lists that isn't marked secondary is primary.
+    pub fn of(muscles: Option<&str>, secondary: &[String]) -> Self {
+        let secondary: HashSet<String> = secondary.iter().map(|m| m.to_lowercase()).collect();
+        let (secondary, primary) = parse_list(muscles)
+            .into_iter()
+            .partition(|m| secondary.contains(&m.to_lowercase()));
+        MuscleTargets { primary, secondary }
+    }
+
+    pub fn for_exercise(definition: &ExerciseDefinition, details: &ExerciseDetails) -> Self {
+        Self::of(definition.muscles.as_deref(), &details.secondary_muscles)
+    }
+
+    /// Primary muscles first; a muscle listed as both counts as primary.
+    pub fn new(primary: &[String], secondary: &[String]) -> Self {
+        let primary = normalize_all(primary.iter().map(String::as_str));
+        let taken: HashSet<String> = primary.iter().map(|m| m.to_lowercase()).collect();
+        let secondary = normalize_all(secondary.iter().map(String::as_str))
+            .into_iter()
+            .filter(|m| !taken.contains(&m.to_lowercase()))
+            .collect();
+        MuscleTargets { primary, secondary }
+    }
+
+    /// The string to hand the lib: every muscle, primary first.
+    pub fn lib_string(&self) -> Option<String> {
+        join(&[self.primary.clone(), self.secondary.clone()].concat())
+    }
+
+    /// Each muscle with its share of the work: 1 for primary muscles and
+    /// `secondary_weight` for secondary ones.
+    pub fn weighted(&self, secondary_weight: f64) -> Vec<(&str, f64)> {
+        self.primary
+            .iter()
+            .map(|m| (m.as_str(), 1.0))
+            .chain(
+                self.secondary
+                    .iter()
+                    .map(|m| (m.as_str(), secondary_weight)),
+            )
+            .collect()
+    }
+}
+
+/// Names of the exercises working a muscle or group matched by `filter`.
+pub fn exercises_targeting(service: &AppService, filter: &str) -> Result<Vec<String>, String> {
+    let wanted = expand(filter);
+    Ok(service
+        .list_exercises(None, None)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|def| {
+            parse_list(def.muscles.as_deref())
+                .iter()
+                .any(|m| wanted.contains(&m.to_lowercase()))
+        })
+        .map(|def| def.name)
+        .collect())
+}
+
+fn normalize_one(
+    service: &AppService,
+    definition: &ExerciseDefinition,
+    muscles: Option<&str>,
+    details: &ExerciseDetails,
+) -> Result<(), String> {
+    if muscles != definition.muscles.as_deref() {
+        service
+            .edit_exercise(&definition.name, None, None, None, Some(muscles))
+            .map_err(|e| e.to_string())?;
+    }
+    exercise_details::save(service, definition.id, details)
+}
+
+#[derive(Serialize)]
+pub struct MuscleGroupPayload {
+    pub name: &'static str,
+    pub muscles: &'static [&'static str],
+}
+
+#[derive(Serialize)]
+pub struct MuscleNormalization {
+    pub exercise: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_muscle_taxonomy() -> Vec<MuscleGroupPayload> {
+    GROUPS
+        .iter()
+        .map(|group| MuscleGroupPayload {
+            name: group.name,
+            muscles: group.muscles,
+        })
+        .collect()
+}
+
+/// Rewrites every exercise's muscles onto the canonical names, e.g. "pecs,
+/// chest" becomes "Chest". With `dry_run` nothing is changed and the
+/// exercises that would be are returned.
+#[tauri::command]
+pub async fn normalize_exercise_muscles(
+    dry_run: Option<bool>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<MuscleNormalization>, String> {
+    run_blocking(&state, move |pool| {
+        let mut service = pool.write()?;
+        let definitions = service
+            .list_exercises(None, None)
+            .map_err(|e| e.to_string())?;
+        let mut details = exercise_details::load_all(&service)?;
+        let mut changes = Vec::new();
+        for definition in &definitions {
+            let mut stored = details.remove(&definition.id).unwrap_or_default();
+            let targets = MuscleTargets::for_exercise(definition, &stored);
+            let after = targets.lib_string();
+            let secondary_changed = stored.secondary_muscles != targets.secondary;
+            if after != definition.muscles || secondary_changed {
+                stored.secondary_muscles = targets.secondary;
+                changes.push((definition, after, stored));
+            }
+        }
+        let normalized: Vec<MuscleNormalization> = changes
+            .iter()
+            .map(|(definition, after, _)| MuscleNormalization {
+                exercise: definition.name.clone(),
+                before: definition.muscles.clone(),
+                after: after.clone(),
+            })
+            .collect();
+        if dry_run.unwrap_or(false) || changes.is_empty() {
+            return Ok(normalized);
+        }
+
+        let ids: Vec<String> = changes
+            .iter()
+            .map(|(definition, _, _)| definition.id.to_string())
+            .collect();
+        let capture = Capture::take(&mut service, &[Scope::Exercises(&ids)]);
+        let mut outcome = Ok(());
+        for (definition, after, stored) in &changes {
+            if let Err(e) = normalize_one(&service, definition, after.as_deref(), stored) {
+                outcome = Err(format!("Failed to normalize {}: {}", definition.name, e));
+                break;
+            }
+        }
+        capture.commit(&mut service, Origin::Local);
+        drop(service);
+        events::exercises_changed(&app);
+        outcome.map(|_| normalized)
+    })
+    .await
+}
+
+/// Sets how much of a set's work counts toward its exercise's secondary
+/// muscles, from 0 to 1. Primary muscles always count fully.
+#[tauri::command]
+pub async fn set_secondary_muscle_weight(
+    weight: f64,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        if !(0.0..=1.0).contains(&weight) {
+            return Err(format!(
+                "Secondary muscle weight must be between 0 and 1, got {}",
+                weight
+            ));
+        }
+        let service = pool.write()?;
+        let mut settings = GuiSettings::load(&service)?;
+        settings.secondary_muscle_weight = weight;
+        settings.save(&service)?;
+        drop(service);
+        events::config_changed(&app);
+        Ok(())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn names_map_onto_the_taxonomy() {
+        assert_eq!(normalize("quads").as_deref(), Some("Quads"));
+        assert_eq!(normalize("Rear_Delts").as_deref(), Some("Shoulders"));
+        assert_eq!(normalize("  latissimus   dorsi ").as_deref(), Some("Lats"));
+        assert_eq!(
+            normalize("serratus  anterior").as_deref(),
+            Some("serratus anterior")
+        );
+        assert_eq!(normalize("  "), None);
+    }
+
+    #[test]
+    fn group_names_stay_groups() {
+        assert_eq!(normalize("back").as_deref(), Some("Back"));
+        assert_eq!(normalize("CORE").as_deref(), Some("Core"));
+        assert_eq!(group_of("Back"), None);
+        assert_eq!(group_of("upper back"), Some("Back"));
+    }
+
+    #[test]
+    fn lists_drop_blanks_and_repeats() {
+        assert_eq!(
+            parse_list(Some("Chest, pecs,, triceps, Shoulders, delts")),
+            strings(&["Chest", "Triceps", "Shoulders"])
+        );
+        assert!(parse_list(None).is_empty());
+    }
+
+    #[test]
+    fn groups_expand_to_their_muscles_and_name() {
+        let back = expand("back");
+        for muscle in ["lats", "upper back", "traps", "lower back", "back"] {
+            assert!(back.contains(muscle), "{}", muscle);
+        }
+        assert_eq!(back.len(), 5);
+        assert_eq!(expand("pecs"), HashSet::from(["chest".to_string()]));
+        assert!(expand(" ").is_empty());
+    }
+
+    #[test]
+    fn filters_match_muscles_or_groups() {
+        let row = strings(&["Lats", "Biceps"]);
+        assert!(matches_any(&row, &["Back"]));
+        assert!(matches_any(&row, &["legs", "bicep"]));
+        assert!(!matches_any(&row, &["Legs"]));
+        assert!(matches_any(&strings(&["Back"]), &["back"]));
+        assert!(!matches_any(&strings(&["Back"]), &["Lats"]));
+        assert!(!matches_any::<&str>(&row, &[]));
+    }
+
+    #[test]
+    fn targets_split_primary_from_secondary() {
+        let targets = MuscleTargets::of(Some("Chest, Triceps, Shoulders"), &strings(&["triceps"]));
+        assert_eq!(targets.primary, strings(&["Chest", "Shoulders"]));
+        assert_eq!(targets.secondary, strings(&["Triceps"]));
+
+        let targets = MuscleTargets::new(&strings(&["pecs"]), &strings(&["Chest", "tricep"]));
+        assert_eq!(targets.secondary, strings(&["Triceps"]));
+        assert_eq!(targets.lib_string().as_deref(), Some("Chest, Triceps"));
+        assert_eq!(
+            targets.weighted(0.5),
+            vec![("Chest", 1.0), ("Triceps", 0.5)]
+        );
+    }
+}
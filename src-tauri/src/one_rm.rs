@@ -0,0 +1,267 @@
+//! One-rep max estimates with a choice of formula, used for workouts' and
+//! graphs' estimated 1RM and the rep-max calculator.
+//!
+//! The formula is `one_rm_formula` in `gui_settings.json`: the lib's `Config`
+//! has no field for it and can't be extended from this crate. Only estimates
+//! depend on it and nothing stored does, so it is fine for it to stay on this
+//! device rather than sync.
+
+use serde::{Deserialize, Serialize};
+
+use task_athlete_lib::{AppService, Workout};
+
+use crate::events;
+use crate::settings::GuiSettings;
+use crate::state::run_blocking;
+use crate::AppState;
+
+const TABLE_REPS: u32 = 15;
+const CHART_PERCENTAGES: [u32; 11] = [100, 95, 90, 85, 80, 75, 70, 65, 60, 55, 50];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OneRmFormula {
+    #[default]
+    Epley,
+    Brzycki,
+    Lombardi,
+    Mayhew,
+    #[serde(rename = "oconner", alias = "o_conner")]
+    OConner,
+    Wathan,
+}
+
+impl OneRmFormula {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase())).map_err(|_| {
+            format!(
+                "Invalid 1RM formula '{}'; use epley, brzycki, lombardi, mayhew, oconner or wathan",
+                s
+            )
+        })
+    }
+
+    /// The share of the 1RM that can be lifted for `reps`. A single is the
+    /// 1RM itself, whatever the formula.
+    pub fn fraction(self, reps: u32) -> Option<f64> {
+        if reps == 0 {
+            return None;
+        }
+        if reps == 1 {
+            return Some(1.0);
+        }
+        let r = f64::from(reps);
+        let fraction = match self {
+            OneRmFormula::Epley => 1.0 / (1.0 + r / 30.0),
+            OneRmFormula::Brzycki => (37.0 - r) / 36.0,
+            OneRmFormula::Lombardi => r.powf(-0.1),
+            OneRmFormula::Mayhew => (52.2 + 41.9 * (-0.055 * r).exp()) / 100.0,
+            OneRmFormula::OConner => 1.0 / (1.0 + 0.025 * r),
+            OneRmFormula::Wathan => (48.8 + 53.8 * (-0.075 * r).exp()) / 100.0,
+        };
+        // Brzycki breaks down past 36 reps.
+        (fraction > 0.0).then_some(fraction)
+    }
+
+    pub fn estimate(self, weight: f64, reps: u32) -> Option<f64> {
+        self.fraction(reps).map(|fraction| weight / fraction)
+    }
+
+    pub fn estimate_workout(self, workout: &Workout) -> Option<f64> {
+        let reps = u32::try_from(workout.reps?).ok()?;
+        self.estimate(workout.weight?, reps)
+    }
+}
+
+/// The formula chosen in the GUI settings, Epley when unset.
+pub fn user_formula(service: &AppService) -> OneRmFormula {
+    match GuiSettings::load(service) {
+        Ok(settings) => settings.one_rm_formula,
+        Err(e) => {
+            eprintln!("{}", e);
+            OneRmFormula::default()
+        }
+    }
+}
+
+/// Recomputes `estimated_1rm` with `formula`.
+pub fn apply(workouts: &mut [Workout], formula: OneRmFormula) {
+    for workout in workouts {
+        workout.estimated_1rm = formula.estimate_workout(workout);
+    }
+}
+
+#[derive(Serialize)]
+pub struct RepMax {
+    pub reps: u32,
+    pub weight: f64,
+    /// Percent of the 1RM.
+    pub percent: f64,
+}
+
+#[derive(Serialize)]
+pub struct PercentOfMax {
+    pub percent: u32,
+    pub weight: f64,
+    /// Reps the formula expects at this weight, rounded down.
+    pub reps: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct RepMaxTable {
+    pub formula: OneRmFormula,
+    pub one_rep_max: f64,
+    /// 1RM to 15RM.
+    pub rep_maxes: Vec<RepMax>,
+    /// 100% down to 50% of the 1RM in 5% steps.
+    pub percentages: Vec<PercentOfMax>,
+}
+
+fn rep_max_table(weight: f64, reps: u32, formula: OneRmFormula) -> Result<RepMaxTable, String> {
+    if weight.is_nan() || weight <= 0.0 {
+        return Err(format!("Weight must be positive, got {}", weight));
+    }
+    let one_rep_max = formula
+        .estimate(weight, reps)
+        .ok_or_else(|| format!("Can't estimate a 1RM from {} reps with this formula", reps))?;
+    let rep_maxes = (1..=TABLE_REPS)
+        .filter_map(|reps| {
+            let fraction = formula.fraction(reps)?;
+            Some(RepMax {
+                reps,
+                weight: one_rep_max * fraction,
+                percent: fraction * 100.0,
+            })
+        })
+        .collect();
+    let percentages = CHART_PERCENTAGES
+        .iter()
+        .map(|&percent| {
+            let fraction = f64::from(percent) / 100.0;
+            PercentOfMax {
+                percent,
+                weight: one_rep_max * fraction,
+                // Fractions shrink as reps grow, so count the reps still at
+                // or above this share.
+                reps: (1..=TABLE_REPS * 2)
+                    .take_while(|&r| formula.fraction(r).is_some_and(|f| f >= fraction))
+                    .last(),
+            }
+        })
+        .collect();
+    Ok(RepMaxTable {
+        formula,
+        one_rep_max,
+        rep_maxes,
+        percentages,
+    })
+}
+
+// --- Tauri Commands ---
+
+/// A 1RM estimate from a set of `reps` at `weight`, with the 1–15RM table and
+/// a percentage chart. Uses the configured formula unless one is given.
+#[tauri::command]
+pub async fn calculate_rep_maxes(
+    weight: f64,
+    reps: u32,
+    formula: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<RepMaxTable, String> {
+    run_blocking(&state, move |pool| {
+        let formula = match formula {
+            Some(name) => OneRmFormula::parse(&name)?,
+            None => user_formula(&pool.read()?),
+        };
+        rep_max_table(weight, reps, formula)
+    })
+    .await
+}
+
+/// Sets the formula used for estimated 1RMs in workouts, graphs and stats.
+#[tauri::command]
+pub async fn set_one_rm_formula(
+    formula: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        let formula = OneRmFormula::parse(&formula)?;
+        let service = pool.write()?;
+        let mut settings = GuiSettings::load(&service)?;
+        settings.one_rm_formula = formula;
+        settings.save(&service)?;
+        drop(service);
+        events::config_changed(&app);
+        // Estimated 1RMs in every list and graph change with the formula.
+        events::workouts_changed(&app, Vec::new(), Vec::new());
+        Ok(())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("an estimate");
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn formulas_estimate_ten_reps() {
+        use OneRmFormula::*;
+        assert_close(Epley.estimate(100.0, 10), 133.333);
+        assert_close(Brzycki.estimate(100.0, 10), 133.333);
+        assert_close(Lombardi.estimate(100.0, 10), 125.893);
+        assert_close(Mayhew.estimate(100.0, 10), 130.934);
+        assert_close(OConner.estimate(100.0, 10), 125.0);
+        assert_close(Wathan.estimate(100.0, 10), 134.747);
+    }
+
+    #[test]
+    fn a_single_is_the_one_rep_max() {
+        for formula in [
+            OneRmFormula::Epley,
+            OneRmFormula::Brzycki,
+            OneRmFormula::Lombardi,
+            OneRmFormula::Mayhew,
+            OneRmFormula::OConner,
+            OneRmFormula::Wathan,
+        ] {
+            assert_eq!(formula.estimate(80.0, 1), Some(80.0));
+        }
+    }
+
+    #[test]
+    fn no_estimate_without_reps_or_past_brzycki_range() {
+        assert_eq!(OneRmFormula::Epley.estimate(100.0, 0), None);
+        assert_eq!(OneRmFormula::Brzycki.estimate(100.0, 37), None);
+        assert!(OneRmFormula::Epley.estimate(100.0, 37).is_some());
+    }
+
+    #[test]
+    fn parse_accepts_names_in_any_case() {
+        assert_eq!(OneRmFormula::parse("Brzycki"), Ok(OneRmFormula::Brzycki));
+        assert_eq!(OneRmFormula::parse("OConner"), Ok(OneRmFormula::OConner));
+        assert_eq!(OneRmFormula::parse("o_conner"), Ok(OneRmFormula::OConner));
+        assert!(OneRmFormula::parse("guess").is_err());
+    }
+
+    #[test]
+    fn table_counts_reps_at_each_percentage() {
+        let table = rep_max_table(100.0, 1, OneRmFormula::Epley).unwrap();
+        assert_eq!(table.rep_maxes.len(), TABLE_REPS as usize);
+        assert_eq!(table.percentages[0].reps, Some(1));
+        // Epley puts 10 reps at 75% of the 1RM.
+        let at_75 = table.percentages.iter().find(|p| p.percent == 75).unwrap();
+        assert_eq!(at_75.reps, Some(10));
+        assert!(rep_max_table(0.0, 5, OneRmFormula::Epley).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Native notifications for personal bests, raised by the backend whenever
+//! workouts are added, copied or received through sync.
+
+use std::collections::BTreeSet;
+
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use task_athlete_lib::{AppService, Units};
+
+use crate::one_rm;
+use crate::personal_records::{self, RecordBreak, RecordMetric};
+use crate::records::{self, MILES_PER_KM};
+use crate::timezone;
+
+fn wanted(service: &AppService, metric: RecordMetric) -> bool {
+    let settings = &service.config.pb_notifications;
+    match metric {
+        RecordMetric::Weight => settings.notify_weight,
+        RecordMetric::Reps => settings.notify_reps,
+        RecordMetric::Duration => settings.notify_duration,
+        RecordMetric::Distance => settings.notify_distance,
+        _ => false,
+    }
+}
+
+// The lib stores weights as logged, in the user's units, and distances in km.
+fn format_value(metric: RecordMetric, value: f64, units: &Units) -> String {
+    match (metric, units) {
+        (RecordMetric::Weight, Units::Metric) => format!("{:.1} kg", value),
+        (RecordMetric::Weight, Units::Imperial) => format!("{:.1} lbs", value),
+        (RecordMetric::Distance, Units::Metric) => format!("{:.2} km", value),
+        (RecordMetric::Distance, Units::Imperial) => format!("{:.2} mi", value * MILES_PER_KM),
+        (RecordMetric::Duration, _) => format!("{} min", value),
+        _ => format!("{}", value),
+    }
+}
+
+fn describe(record: &RecordBreak, units: &Units) -> String {
+    let name = match record.metric {
+        RecordMetric::Weight => "Weight",
+        RecordMetric::Reps => "Reps",
+        RecordMetric::Duration => "Duration",
+        RecordMetric::Distance => "Distance",
+        _ => "Record",
+    };
+    let value = format_value(record.metric, record.value, units);
+    match record.previous_value {
+        Some(previous) => format!(
+            "{} {} (was {})",
+            name,
+            value,
+            format_value(record.metric, previous, units)
+        ),
+        None => format!("{} {}", name, value),
+    }
+}
+
+/// The standing records of each exercise that one of `workout_ids` now holds,
+/// limited to the metrics enabled in the PB notification settings. A first
+/// log of a metric is not a PB.
+fn new_records(
+    service: &AppService,
+    workout_ids: &[i64],
+) -> Result<Vec<(String, Vec<RecordBreak>)>, String> {
+    let exercises: BTreeSet<String> = records::find_workouts(service, workout_ids)?
+        .into_iter()
+        .map(|w| w.exercise_name)
+        .collect();
+    let formula = one_rm::user_formula(service);
+    let tz = timezone::user_time_zone(service);
+    let mut found = Vec::new();
+    for exercise in exercises {
+        let workouts = records::workouts_for_exercise(service, &exercise)?;
+        let breaks = personal_records::record_breaks(&workouts, formula, tz);
+        let records: Vec<RecordBreak> = personal_records::current_records(breaks)
+            .into_iter()
+            .filter(|r| {
+                workout_ids.contains(&r.workout_id)
+                    && r.previous_value.is_some()
+                    && wanted(service, r.metric)
+            })
+            .collect();
+        if !records.is_empty() {
+            found.push((exercise, records));
+        }
+    }
+    Ok(found)
+}
+
+/// Shows one notification per exercise whose PB the workouts set, when PB
+/// notifications are turned on. Best-effort: failures are only logged, like
+/// events.
+pub fn notify(app: &AppHandle, service: &AppService, workout_ids: &[i64]) {
+    // `None` means the user hasn't been asked yet; the page prompts for that.
+    if workout_ids.is_empty() || service.config.pb_notifications.enabled != Some(true) {
+        return;
+    }
+    let found = match new_records(service, workout_ids) {
+        Ok(found) => found,
+        Err(e) => {
+            eprintln!("Failed to check workouts for personal bests: {}", e);
+            return;
+        }
+    };
+    let units = &service.config.units;
+    for (exercise, records) in found {
+        let body = records
+            .iter()
+            .map(|r| describe(r, units))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if let Err(e) = app
+            .notification()
+            .builder()
+            .title(format!("New personal best: {}", exercise))
+            .body(body)
+            .show()
+        {
+            eprintln!("Failed to show personal best notification: {}", e);
+        }
+    }
+}
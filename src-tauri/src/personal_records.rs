@@ -0,0 +1,312 @@
+//! Personal records worked out from the whole workout history, so every PR and
+//! each time it was broken can be listed, not just the one `add_workout`
+//! reports.
+
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use task_athlete_lib::{AppService, Workout};
+
+use crate::one_rm::{self, OneRmFormula};
+use crate::records;
+use crate::state::run_blocking;
+use crate::timezone;
+use crate::AppState;
+
+/// Rep counts with their own rep-max record.
+const REP_MAXES: [u32; 6] = [1, 3, 5, 8, 10, 12];
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordMetric {
+    /// Heaviest weight in one entry, in the units weights are logged in.
+    Weight,
+    Reps,
+    Estimated1rm,
+    /// Sets × reps × weight of one entry, in the units weights are logged in.
+    Volume,
+    /// Minutes.
+    Duration,
+    /// Kilometres.
+    Distance,
+    /// Heaviest weight lifted for at least `reps` reps.
+    RepMax,
+}
+
+/// A record as it stood after one workout.
+#[derive(Serialize, Clone, Debug)]
+pub struct RecordBreak {
+    pub metric: RecordMetric,
+    /// The rep count of a rep-max record.
+    pub reps: Option<u32>,
+    pub value: f64,
+    pub workout_id: i64,
+    /// Local date of the workout.
+    pub date: NaiveDate,
+    /// The record it beat; `None` the first time the metric was logged.
+    pub previous_value: Option<f64>,
+    pub previous_date: Option<NaiveDate>,
+}
+
+#[derive(Serialize)]
+pub struct ExerciseRecords {
+    pub exercise: String,
+    /// One current record per metric, ordered by metric.
+    pub records: Vec<RecordBreak>,
+}
+
+#[derive(Serialize)]
+pub struct PrTimeline {
+    pub exercise: String,
+    /// Every record set or broken, oldest first.
+    pub breaks: Vec<RecordBreak>,
+}
+
+type RecordKey = (RecordMetric, Option<u32>);
+
+fn record_values(workout: &Workout, formula: OneRmFormula) -> Vec<(RecordKey, f64)> {
+    let mut values = Vec::new();
+    let reps = workout.reps.and_then(|r| u32::try_from(r).ok());
+    let weight = workout.weight.filter(|w| *w > 0.0);
+    if let Some(weight) = weight {
+        values.push(((RecordMetric::Weight, None), weight));
+    }
+    if let Some(reps) = reps.filter(|r| *r > 0) {
+        values.push(((RecordMetric::Reps, None), f64::from(reps)));
+    }
+    if let Some(estimate) = weight.and_then(|_| formula.estimate_workout(workout)) {
+        values.push(((RecordMetric::Estimated1rm, None), estimate));
+    }
+    if let (Some(weight), Some(reps)) = (weight, reps.filter(|r| *r > 0)) {
+        let sets = workout.sets.unwrap_or(1) as f64;
+        values.push((
+            (RecordMetric::Volume, None),
+            sets * f64::from(reps) * weight,
+        ));
+        for rep_max in REP_MAXES.into_iter().filter(|n| *n <= reps) {
+            values.push(((RecordMetric::RepMax, Some(rep_max)), weight));
+        }
+    }
+    if let Some(duration) = workout.duration_minutes.filter(|d| *d > 0) {
+        values.push(((RecordMetric::Duration, None), duration as f64));
+    }
+    if let Some(distance) = workout.distance.filter(|d| *d > 0.0) {
+        values.push(((RecordMetric::Distance, None), distance));
+    }
+    values
+}
+
+/// Every record set or broken by `workouts`, oldest first. A record only
+/// falls to a strictly better value.
+pub fn record_breaks(workouts: &[Workout], formula: OneRmFormula, tz: Tz) -> Vec<RecordBreak> {
+    let mut ordered: Vec<&Workout> = workouts.iter().collect();
+    ordered.sort_by_key(|w| (w.timestamp, w.id));
+    let mut best: BTreeMap<RecordKey, (f64, NaiveDate)> = BTreeMap::new();
+    let mut breaks = Vec::new();
+    for workout in ordered {
+        let date = timezone::local_date(tz, workout.timestamp);
+        for ((metric, reps), value) in record_values(workout, formula) {
+            let previous = best.get(&(metric, reps)).copied();
+            if previous.is_some_and(|(record, _)| value <= record) {
+                continue;
+            }
+            best.insert((metric, reps), (value, date));
+            breaks.push(RecordBreak {
+                metric,
+                reps,
+                value,
+                workout_id: workout.id,
+                date,
+                previous_value: previous.map(|(record, _)| record),
+                previous_date: previous.map(|(_, day)| day),
+            });
+        }
+    }
+    breaks
+}
+
+/// The standing record per metric: the last break of each.
+pub fn current_records(breaks: Vec<RecordBreak>) -> Vec<RecordBreak> {
+    let mut current: BTreeMap<RecordKey, RecordBreak> = BTreeMap::new();
+    for record in breaks {
+        current.insert((record.metric, record.reps), record);
+    }
+    current.into_values().collect()
+}
+
+fn all_records(service: &AppService) -> Result<Vec<ExerciseRecords>, String> {
+    let tz = timezone::user_time_zone(service);
+    let formula = one_rm::user_formula(service);
+    let mut by_exercise: BTreeMap<String, Vec<Workout>> = BTreeMap::new();
+    for workout in records::all_workouts(service)? {
+        by_exercise
+            .entry(workout.exercise_name.clone())
+            .or_default()
+            .push(workout);
+    }
+    Ok(by_exercise
+        .into_iter()
+        .map(|(exercise, workouts)| ExerciseRecords {
+            exercise,
+            records: current_records(record_breaks(&workouts, formula, tz)),
+        })
+        .filter(|exercise| !exercise.records.is_empty())
+        .collect())
+}
+
+// --- Tauri Commands ---
+
+/// The current PRs of every exercise with workouts: weight, reps, estimated
+/// 1RM, volume, duration, distance and the 1, 3, 5, 8, 10 and 12 rep maxes.
+#[tauri::command]
+pub async fn list_personal_records(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<ExerciseRecords>, String> {
+    run_blocking(&state, move |pool| all_records(&pool.read()?)).await
+}
+
+/// Every time one of the exercise's records was set or broken, with the value
+/// it beat.
+#[tauri::command]
+pub async fn get_pr_timeline(
+    identifier: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<PrTimeline, String> {
+    run_blocking(&state, move |pool| {
+        let service = pool.read()?;
+        let exercise = records::resolve_exercise(&service, &identifier)?.name;
+        let workouts = records::workouts_for_exercise(&service, &exercise)?;
+        let breaks = record_breaks(
+            &workouts,
+            one_rm::user_formula(&service),
+            timezone::user_time_zone(&service),
+        );
+        Ok(PrTimeline { exercise, breaks })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    use crate::records::WorkoutSnapshot;
+
+    fn temp_service(name: &str) -> AppService {
+        let dir =
+            std::env::temp_dir().join(format!("task-athlete-gui-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        AppService::initialize_with_paths(&dir.join("config.toml"), &dir.join("workouts.sqlite"))
+            .unwrap()
+    }
+
+    fn log(service: &mut AppService, day: u32, sets: i64, reps: i64, weight: f64) -> i64 {
+        let snapshot = WorkoutSnapshot {
+            id: 0,
+            timestamp: Utc.with_ymd_and_hms(2024, 5, day, 6, 30, 0).unwrap(),
+            exercise_name: "Bench Press".to_string(),
+            exercise_type: Some("resistance".to_string()),
+            sets: Some(sets),
+            reps: Some(reps),
+            weight: Some(weight),
+            duration_minutes: None,
+            distance: None,
+            bodyweight: None,
+            notes: None,
+        };
+        records::add_snapshot(service, &snapshot).unwrap().0
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+    }
+
+    #[test]
+    fn only_strictly_better_values_break_records() {
+        let mut service = temp_service("record-breaks");
+        let first = log(&mut service, 1, 3, 5, 60.0);
+        log(&mut service, 2, 3, 5, 60.0);
+        let heavier = log(&mut service, 3, 3, 3, 65.0);
+        let workouts = records::all_workouts(&service).unwrap();
+        let breaks = record_breaks(&workouts, OneRmFormula::Epley, Tz::UTC);
+
+        let set_by = |id: i64| -> Vec<(RecordMetric, Option<u32>)> {
+            breaks
+                .iter()
+                .filter(|b| b.workout_id == id)
+                .map(|b| (b.metric, b.reps))
+                .collect()
+        };
+        assert_eq!(
+            set_by(first),
+            vec![
+                (RecordMetric::Weight, None),
+                (RecordMetric::Reps, None),
+                (RecordMetric::Estimated1rm, None),
+                (RecordMetric::Volume, None),
+                (RecordMetric::RepMax, Some(1)),
+                (RecordMetric::RepMax, Some(3)),
+                (RecordMetric::RepMax, Some(5)),
+            ]
+        );
+        assert!(breaks
+            .iter()
+            .all(|b| b.workout_id == first || b.workout_id == heavier));
+        assert_eq!(
+            set_by(heavier),
+            vec![
+                (RecordMetric::Weight, None),
+                (RecordMetric::Estimated1rm, None),
+                (RecordMetric::RepMax, Some(1)),
+                (RecordMetric::RepMax, Some(3)),
+            ]
+        );
+
+        let weight = breaks
+            .iter()
+            .find(|b| b.workout_id == heavier && b.metric == RecordMetric::Weight)
+            .unwrap();
+        assert_eq!(weight.value, 65.0);
+        assert_eq!(weight.previous_value, Some(60.0));
+        assert_eq!(weight.date, date(3));
+        assert_eq!(weight.previous_date, Some(date(1)));
+    }
+
+    #[test]
+    fn current_records_keep_the_last_break() {
+        let mut service = temp_service("current-records");
+        log(&mut service, 1, 3, 5, 60.0);
+        let heavier = log(&mut service, 3, 3, 3, 65.0);
+        let workouts = records::all_workouts(&service).unwrap();
+        let current = current_records(record_breaks(&workouts, OneRmFormula::Epley, Tz::UTC));
+
+        let value = |metric: RecordMetric, reps: Option<u32>| {
+            current
+                .iter()
+                .find(|r| r.metric == metric && r.reps == reps)
+                .map(|r| (r.value, r.workout_id))
+        };
+        assert_eq!(value(RecordMetric::Weight, None), Some((65.0, heavier)));
+        assert_eq!(value(RecordMetric::Reps, None).map(|(v, _)| v), Some(5.0));
+        assert_eq!(
+            value(RecordMetric::RepMax, Some(5)).map(|(v, _)| v),
+            Some(60.0)
+        );
+        assert_eq!(value(RecordMetric::RepMax, Some(8)), None);
+    }
+
+    #[test]
+    fn record_dates_are_local_days() {
+        let mut service = temp_service("record-dates");
+        log(&mut service, 2, 1, 1, 100.0);
+        let workouts = records::all_workouts(&service).unwrap();
+        // 06:30 UTC is still the evening before in Los Angeles.
+        let tz = timezone::parse_time_zone("America/Los_Angeles").unwrap();
+        let breaks = record_breaks(&workouts, OneRmFormula::Epley, tz);
+        assert!(breaks.iter().all(|b| b.date == date(1)));
+    }
+}
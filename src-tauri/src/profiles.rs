@@ -0,0 +1,445 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tauri::Manager;
+
+use task_athlete_lib::AppService;
+
+use crate::database;
+use crate::events::{self, ChangeSource};
+use crate::exercise_details;
+use crate::state::{blocking, run_blocking};
+use crate::undo;
+use crate::AppState;
+
+pub const DEFAULT_PROFILE: &str = "default";
+const REGISTRY_FILE_NAME: &str = "profiles.json";
+const PROFILES_DIR_NAME: &str = "profiles";
+
+// Managed separately from AppState. When both are needed, always lock the
+// registry first and the service second.
+pub type ProfilesState = Mutex<ProfileRegistry>;
+
+/// The profile registry, for commands running on the blocking pool.
+pub fn lock(app: &tauri::AppHandle) -> Result<MutexGuard<'_, ProfileRegistry>, String> {
+    app.state::<ProfilesState>()
+        .inner()
+        .lock()
+        .map_err(|e| format!("Failed to lock profiles: {}", e))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Profile {
+    pub name: String,
+    pub config_path: PathBuf,
+    pub db_path: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProfileRegistry {
+    pub active: String,
+    pub profiles: Vec<Profile>,
+    // Directory holding profiles.json and the per-profile folders.
+    #[serde(skip)]
+    base_dir: PathBuf,
+}
+
+impl ProfileRegistry {
+    /// Loads the registry stored next to the default config file, creating it
+    /// with a single "default" profile (pointing at `default_service`'s paths)
+    /// when it does not exist yet.
+    pub fn load_or_create(default_service: &AppService) -> Result<Self, String> {
+        let default_config_path = default_service.get_config_path().to_path_buf();
+        let base_dir = default_config_path
+            .parent()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| "Config path has no parent directory".to_string())?;
+        let registry_path = base_dir.join(REGISTRY_FILE_NAME);
+
+        let mut registry = if registry_path.exists() {
+            let raw = fs::read_to_string(&registry_path)
+                .map_err(|e| format!("Failed to read {:?}: {}", registry_path, e))?;
+            serde_json::from_str::<ProfileRegistry>(&raw)
+                .map_err(|e| format!("Failed to parse {:?}: {}", registry_path, e))?
+        } else {
+            ProfileRegistry {
+                active: DEFAULT_PROFILE.to_string(),
+                profiles: Vec::new(),
+                base_dir: PathBuf::new(),
+            }
+        };
+        registry.base_dir = base_dir;
+
+        if registry.get(DEFAULT_PROFILE).is_none() {
+            registry.profiles.insert(
+                0,
+                Profile {
+                    name: DEFAULT_PROFILE.to_string(),
+                    config_path: default_config_path,
+                    db_path: default_service.get_db_path().to_path_buf(),
+                },
+            );
+        }
+        if registry.get(&registry.active).is_none() {
+            registry.active = DEFAULT_PROFILE.to_string();
+        }
+        registry.save()?;
+        Ok(registry)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let registry_path = self.base_dir.join(REGISTRY_FILE_NAME);
+        let raw = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize profiles: {}", e))?;
+        fs::write(&registry_path, raw)
+            .map_err(|e| format!("Failed to write {:?}: {}", registry_path, e))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Profile> {
+        self.profiles.iter_mut().find(|p| p.name == name)
+    }
+
+    pub fn active_profile(&self) -> Option<&Profile> {
+        self.get(&self.active)
+    }
+
+    /// Registers a new profile with its own folder under `profiles/`. The
+    /// config and database files keep the default profile's file names.
+    fn create(&mut self, name: &str) -> Result<Profile, String> {
+        validate_profile_name(name)?;
+        if self.get(name).is_some() {
+            return Err(format!("Profile '{}' already exists", name));
+        }
+        let default = self
+            .get(DEFAULT_PROFILE)
+            .ok_or_else(|| "Default profile is missing".to_string())?;
+        let config_file = default
+            .config_path
+            .file_name()
+            .map(|f| f.to_os_string())
+            .ok_or_else(|| "Default config path has no file name".to_string())?;
+        let db_file = default
+            .db_path
+            .file_name()
+            .map(|f| f.to_os_string())
+            .ok_or_else(|| "Default database path has no file name".to_string())?;
+
+        let profile_dir = self.unused_profile_dir(name);
+        fs::create_dir_all(&profile_dir).map_err(|e| {
+            format!(
+                "Failed to create profile directory {:?}: {}",
+                profile_dir, e
+            )
+        })?;
+
+        let profile = Profile {
+            name: name.to_string(),
+            config_path: profile_dir.join(config_file),
+            db_path: profile_dir.join(db_file),
+        };
+        self.profiles.push(profile.clone());
+        Ok(profile)
+    }
+
+    /// Unregisters a profile other than the default or active one, removing
+    /// its files too when `delete_data` is set.
+    fn remove(&mut self, name: &str, delete_data: bool) -> Result<(), String> {
+        if name == DEFAULT_PROFILE {
+            return Err("The default profile cannot be deleted".to_string());
+        }
+        if self.active == name {
+            return Err(
+                "Cannot delete the active profile; switch to another profile first".to_string(),
+            );
+        }
+        let profile = self
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Profile '{}' not found", name))?;
+        self.profiles.retain(|p| p.name != name);
+        self.save()?;
+
+        if delete_data {
+            // The database may have been moved out of the profile's directory.
+            database::remove_database_files(&profile.db_path);
+            let profiles_root = self.base_dir.join(PROFILES_DIR_NAME);
+            // The config sits in the profile's own directory together with the
+            // GUI's files, unless it is a profile set up outside the registry's.
+            match profile
+                .config_path
+                .parent()
+                .filter(|dir| dir.starts_with(&profiles_root) && *dir != profiles_root)
+            {
+                Some(dir) => {
+                    if let Err(e) = fs::remove_dir_all(dir) {
+                        eprintln!(
+                            "Failed to remove {:?} of deleted profile '{}': {}",
+                            dir, name, e
+                        );
+                    }
+                }
+                None => {
+                    if profile.config_path.exists() {
+                        if let Err(e) = fs::remove_file(&profile.config_path) {
+                            eprintln!(
+                                "Failed to remove {:?} of deleted profile '{}': {}",
+                                profile.config_path, name, e
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Folder names are derived from the profile name but never reused, so a
+    // renamed profile's old folder can't collide with a new one.
+    fn unused_profile_dir(&self, name: &str) -> PathBuf {
+        let slug: String = name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let root = self.base_dir.join(PROFILES_DIR_NAME);
+        let mut candidate = root.join(&slug);
+        let mut suffix = 1;
+        while candidate.exists() {
+            suffix += 1;
+            candidate = root.join(format!("{}_{}", slug, suffix));
+        }
+        candidate
+    }
+}
+
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    if trimmed != name {
+        return Err("Profile name cannot start or end with whitespace".to_string());
+    }
+    if name
+        .chars()
+        .any(|c| c.is_control() || c == '/' || c == '\\')
+    {
+        return Err(format!("Invalid characters in profile name '{}'", name));
+    }
+    Ok(())
+}
+
+/// Opens (and, for a new profile, creates) the config and database of `profile`.
+pub fn open_profile_service(profile: &Profile) -> Result<AppService, String> {
+    AppService::initialize_with_paths(&profile.config_path, &profile.db_path).map_err(|e| {
+        format!(
+            "Failed to initialize profile '{}' ({:?}): {}",
+            profile.name, profile.db_path, e
+        )
+    })
+}
+
+#[derive(Serialize)]
+pub struct ProfileInfo {
+    name: String,
+    config_path: PathBuf,
+    db_path: PathBuf,
+    is_active: bool,
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub async fn list_profiles(app: tauri::AppHandle) -> Result<Vec<ProfileInfo>, String> {
+    blocking(move || {
+        let registry = lock(&app)?;
+        Ok(registry
+            .profiles
+            .iter()
+            .map(|p| ProfileInfo {
+                name: p.name.clone(),
+                config_path: p.config_path.clone(),
+                db_path: p.db_path.clone(),
+                is_active: p.name == registry.active,
+            })
+            .collect())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn create_profile(name: String, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(move || {
+        let mut registry = lock(&app)?;
+        let profile = registry.create(&name)?;
+        // Initializing once creates the config file and database schema up front,
+        // so a broken location is reported now rather than on first switch.
+        if let Err(e) = open_profile_service(&profile) {
+            registry.profiles.retain(|p| p.name != profile.name);
+            return Err(e);
+        }
+        registry.save()
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn rename_profile(
+    old_name: String,
+    new_name: String,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    blocking(move || {
+        let mut registry = lock(&app)?;
+        if old_name == DEFAULT_PROFILE {
+            return Err("The default profile cannot be renamed".to_string());
+        }
+        validate_profile_name(&new_name)?;
+        if registry.get(&new_name).is_some() {
+            return Err(format!("Profile '{}' already exists", new_name));
+        }
+        let profile = registry
+            .get_mut(&old_name)
+            .ok_or_else(|| format!("Profile '{}' not found", old_name))?;
+        profile.name = new_name.clone();
+        if registry.active == old_name {
+            registry.active = new_name;
+        }
+        registry.save()
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_profile(
+    name: String,
+    delete_data: Option<bool>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    blocking(move || lock(&app)?.remove(&name, delete_data.unwrap_or(false))).await
+}
+
+#[tauri::command]
+pub async fn switch_profile(
+    name: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        let mut registry = lock(&app)?;
+        let profile = registry
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| format!("Profile '{}' not found", name))?;
+
+        // Open the new service before touching the current one so a failure
+        // leaves the active profile untouched.
+        let new_service = open_profile_service(&profile)?;
+        let mut history = undo::lock(&app)?;
+        pool.replace(new_service)?;
+        // Recorded ids belong to the previous profile's database.
+        history.clear();
+        let adopted = pool
+            .write()
+            .and_then(|service| exercise_details::adopt_legacy_media(&app, &service));
+        if let Err(e) = adopted {
+            eprintln!("Failed to move exercise media: {}", e);
+        }
+        registry.active = profile.name;
+        registry.save()?;
+        events::all_changed(&app, ChangeSource::Profile);
+        Ok(())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_registry(name: &str) -> (PathBuf, ProfileRegistry) {
+        let dir =
+            std::env::temp_dir().join(format!("task-athlete-gui-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let service = AppService::initialize_with_paths(
+            &dir.join("config.toml"),
+            &dir.join("workouts.sqlite"),
+        )
+        .unwrap();
+        let registry = ProfileRegistry::load_or_create(&service).unwrap();
+        (dir, registry)
+    }
+
+    fn add_profile(registry: &mut ProfileRegistry, name: &str) -> Profile {
+        let profile = registry.create(name).unwrap();
+        open_profile_service(&profile).unwrap();
+        fs::write(profile.config_path.with_file_name("trash.json"), "{}").unwrap();
+        profile
+    }
+
+    #[test]
+    fn deleting_data_removes_the_profile_directory() {
+        let (dir, mut registry) = temp_registry("profiles-delete-data");
+        let profile = add_profile(&mut registry, "Work");
+        let profile_dir = profile.config_path.parent().unwrap().to_path_buf();
+        assert!(profile.db_path.exists());
+
+        registry.remove("Work", true).unwrap();
+        assert!(registry.get("Work").is_none());
+        assert!(!profile_dir.exists());
+        // The default profile's files next to the registry stay.
+        assert!(dir.join("workouts.sqlite").exists());
+
+        let service = AppService::initialize_with_paths(
+            &dir.join("config.toml"),
+            &dir.join("workouts.sqlite"),
+        )
+        .unwrap();
+        let reloaded = ProfileRegistry::load_or_create(&service).unwrap();
+        assert!(reloaded.get("Work").is_none());
+    }
+
+    #[test]
+    fn deleting_data_follows_a_moved_database() {
+        let (dir, mut registry) = temp_registry("profiles-delete-moved");
+        let profile = add_profile(&mut registry, "Work");
+        let moved = dir.join("elsewhere.sqlite");
+        fs::rename(&profile.db_path, &moved).unwrap();
+        registry.get_mut("Work").unwrap().db_path = moved.clone();
+
+        registry.remove("Work", true).unwrap();
+        assert!(!moved.exists());
+    }
+
+    #[test]
+    fn keeping_data_leaves_the_files() {
+        let (_, mut registry) = temp_registry("profiles-keep-data");
+        let profile = add_profile(&mut registry, "Work");
+        registry.remove("Work", false).unwrap();
+        assert!(registry.get("Work").is_none());
+        assert!(profile.db_path.exists());
+        assert!(profile.config_path.with_file_name("trash.json").exists());
+    }
+
+    #[test]
+    fn default_active_and_unknown_profiles_are_refused() {
+        let (_, mut registry) = temp_registry("profiles-refused");
+        add_profile(&mut registry, "Work");
+        assert!(registry.remove(DEFAULT_PROFILE, true).is_err());
+        registry.active = "Work".to_string();
+        assert!(registry.remove("Work", true).is_err());
+        assert!(registry.remove("Nobody", true).is_err());
+        assert_eq!(registry.profiles.len(), 2);
+    }
+}
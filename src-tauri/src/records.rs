@@ -0,0 +1,911 @@
+//! Serializable copies of workouts and exercises, used to put records back
+//! after they were deleted or edited (trash bin, undo).
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use task_athlete_lib::{
+    AddWorkoutParams, AppService, EditWorkoutParams, ExerciseDefinition, ExerciseType, PBInfo,
+    Workout, WorkoutFilters,
+};
+
+use crate::exercise_details::{self, ExerciseDetails};
+use crate::{exercise_type_str, parse_exercise_type};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WorkoutSnapshot {
+    pub id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub exercise_name: String,
+    pub exercise_type: Option<String>,
+    pub sets: Option<i64>,
+    pub reps: Option<i64>,
+    pub weight: Option<f64>,
+    pub duration_minutes: Option<i64>,
+    pub distance: Option<f64>,
+    pub bodyweight: Option<f64>,
+    pub notes: Option<String>,
+}
+
+impl From<&Workout> for WorkoutSnapshot {
+    fn from(workout: &Workout) -> Self {
+        WorkoutSnapshot {
+            id: workout.id,
+            timestamp: workout.timestamp,
+            exercise_name: workout.exercise_name.clone(),
+            exercise_type: workout.exercise_type.as_ref().map(exercise_type_str),
+            sets: workout.sets,
+            reps: workout.reps,
+            weight: workout.weight,
+            duration_minutes: workout.duration_minutes,
+            distance: workout.distance,
+            bodyweight: workout.bodyweight,
+            notes: workout.notes.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ExerciseSnapshot {
+    pub name: String,
+    pub exercise_type: String,
+    pub muscles: Option<String>,
+    pub log_weight: bool,
+    pub log_reps: bool,
+    pub log_duration: bool,
+    pub log_distance: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "ExerciseDetails::is_empty")]
+    pub details: ExerciseDetails,
+}
+
+impl ExerciseSnapshot {
+    pub fn new(
+        definition: &ExerciseDefinition,
+        aliases: Vec<String>,
+        details: ExerciseDetails,
+    ) -> Self {
+        ExerciseSnapshot {
+            name: definition.name.clone(),
+            exercise_type: exercise_type_str(&definition.type_),
+            muscles: definition.muscles.clone(),
+            log_weight: definition.log_weight,
+            log_reps: definition.log_reps,
+            log_duration: definition.log_duration,
+            log_distance: definition.log_distance,
+            archived: !definition.is_active,
+            aliases,
+            details,
+        }
+    }
+}
+
+pub fn all_workouts(service: &AppService) -> Result<Vec<Workout>, String> {
+    let filters = WorkoutFilters {
+        exercise_name: None,
+        date: None,
+        exercise_type: None,
+        muscle: None,
+        limit: None,
+    };
+    service.list_workouts(&filters).map_err(|e| e.to_string())
+}
+
+pub fn workouts_for_exercise(
+    service: &AppService,
+    exercise_name: &str,
+) -> Result<Vec<Workout>, String> {
+    let filters = WorkoutFilters {
+        exercise_name: Some(exercise_name),
+        date: None,
+        exercise_type: None,
+        muscle: None,
+        limit: None,
+    };
+    service.list_workouts(&filters).map_err(|e| e.to_string())
+}
+
+/// Looks workouts up by id. The lib has no lookup by id, so this reads the
+/// UTC dates of their rows and lists only those days, falling back to the
+/// whole list if a stored timestamp isn't a date SQLite understands.
+pub fn find_workouts(service: &AppService, ids: &[i64]) -> Result<Vec<Workout>, String> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let list: Vec<String> = ids.iter().map(i64::to_string).collect();
+    let mut stmt = service
+        .conn
+        .prepare(&format!(
+            "SELECT DISTINCT date(timestamp) FROM workouts WHERE id IN ({})",
+            list.join(", ")
+        ))
+        .map_err(|e| e.to_string())?;
+    let dates = stmt
+        .query_map([], |row| row.get::<_, Option<String>>(0))
+        .map_err(|e| e.to_string())?
+        .map(|date| {
+            let date = date.map_err(|e| e.to_string())?;
+            Ok(date.and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok()))
+        })
+        .collect::<Result<Option<Vec<NaiveDate>>, String>>()?;
+    let Some(dates) = dates else {
+        return Ok(all_workouts(service)?
+            .into_iter()
+            .filter(|w| ids.contains(&w.id))
+            .collect());
+    };
+    let mut found = Vec::new();
+    for date in dates {
+        let filters = WorkoutFilters {
+            exercise_name: None,
+            date: Some(date),
+            exercise_type: None,
+            muscle: None,
+            limit: None,
+        };
+        let listed = service.list_workouts(&filters).map_err(|e| e.to_string())?;
+        found.extend(listed.into_iter().filter(|w| ids.contains(&w.id)));
+    }
+    Ok(found)
+}
+
+pub fn find_workout(service: &AppService, id: i64) -> Result<Workout, String> {
+    find_workouts(service, &[id])?
+        .pop()
+        .ok_or_else(|| format!("Workout {} not found", id))
+}
+
+/// When the workouts logged from `from` up to `to` were done, in one query.
+/// SQLite normalizes the stored timestamps, whatever their format.
+pub fn workout_times_between(
+    service: &AppService,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<DateTime<Utc>>, String> {
+    let mut stmt = service
+        .conn
+        .prepare(
+            "SELECT strftime('%Y-%m-%dT%H:%M:%fZ', timestamp) FROM workouts
+             WHERE julianday(timestamp) >= julianday(?1) AND julianday(timestamp) < julianday(?2)",
+        )
+        .map_err(|e| e.to_string())?;
+    let times = stmt
+        .query_map((from.to_rfc3339(), to.to_rfc3339()), |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| e.to_string())?
+        .map(|time| {
+            let time = time.map_err(|e| e.to_string())?;
+            DateTime::parse_from_rfc3339(&time)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|e| format!("Unreadable workout time {}: {}", time, e))
+        })
+        .collect();
+    times
+}
+
+/// The highest id in use in one of the lib's tables, 0 when it is empty.
+/// Records added later get higher ids.
+pub fn max_id(service: &AppService, table: &str) -> Result<i64, String> {
+    service
+        .conn
+        .query_row(
+            &format!("SELECT COALESCE(MAX(id), 0) FROM {}", table),
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// Ids above `id` in the table, i.e. of the records added since `max_id`
+/// gave it.
+pub fn ids_after(service: &AppService, table: &str, id: i64) -> Result<Vec<i64>, String> {
+    let mut stmt = service
+        .conn
+        .prepare(&format!(
+            "SELECT id FROM {} WHERE id > ?1 ORDER BY id",
+            table
+        ))
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map([id], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|e| e.to_string());
+    ids
+}
+
+/// Resolves an identifier the way the lib does: numeric id, name or alias.
+pub fn resolve_exercise(
+    service: &AppService,
+    identifier: &str,
+) -> Result<ExerciseDefinition, String> {
+    let definitions = service
+        .list_exercises(None, None)
+        .map_err(|e| e.to_string())?;
+    let aliases = service.list_aliases().map_err(|e| e.to_string())?;
+    let id = find_exercise_id(&definitions, &aliases, identifier);
+    definitions
+        .into_iter()
+        .find(|def| Some(def.id) == id)
+        .ok_or_else(|| format!("Exercise '{}' not found", identifier))
+}
+
+/// `resolve_exercise` against exercise and alias lists already fetched.
+pub fn find_exercise_id<'a>(
+    definitions: &[ExerciseDefinition],
+    aliases: impl IntoIterator<Item = (&'a String, &'a String)>,
+    identifier: &str,
+) -> Option<i64> {
+    let target_name = aliases
+        .into_iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(identifier))
+        .map(|(_, target)| target.as_str())
+        .unwrap_or(identifier);
+    let id = identifier.parse::<i64>().ok();
+    definitions
+        .iter()
+        .find(|def| Some(def.id) == id || def.name.eq_ignore_ascii_case(target_name))
+        .map(|def| def.id)
+}
+
+pub fn aliases_of(service: &AppService, exercise_name: &str) -> Result<Vec<String>, String> {
+    let mut aliases: Vec<String> = service
+        .list_aliases()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|(alias, target)| {
+            target.eq_ignore_ascii_case(exercise_name) && !exercise_details::is_carrier(alias)
+        })
+        .map(|(alias, _)| alias)
+        .collect();
+    aliases.sort();
+    Ok(aliases)
+}
+
+/// Adds the snapshot back as a new workout and returns its new id.
+pub fn readd_workout(service: &mut AppService, snapshot: &WorkoutSnapshot) -> Result<i64, String> {
+    add_snapshot(service, snapshot)
+        .map(|(id, _)| id)
+        .map_err(|e| format!("Failed to re-add workout: {}", e))
+}
+
+/// Adds a new workout with the snapshot's values, returning its id and any
+/// personal bests it set.
+pub fn add_snapshot(
+    service: &mut AppService,
+    snapshot: &WorkoutSnapshot,
+) -> Result<(i64, Option<PBInfo>), String> {
+    let implicit_type = snapshot
+        .exercise_type
+        .as_deref()
+        .map(parse_exercise_type)
+        .transpose()?;
+    // add_workout adds the bodyweight on top of the given weight for
+    // body-weight exercises, but the stored weight already includes it.
+    let weight = match (implicit_type.as_ref(), snapshot.bodyweight) {
+        (Some(ExerciseType::BodyWeight), Some(bodyweight)) => {
+            snapshot.weight.map(|w| w - bodyweight)
+        }
+        _ => snapshot.weight,
+    };
+    let params = AddWorkoutParams {
+        exercise_identifier: &snapshot.exercise_name,
+        date: snapshot.timestamp,
+        sets: snapshot.sets,
+        reps: snapshot.reps,
+        weight,
+        duration: snapshot.duration_minutes,
+        distance: snapshot.distance,
+        notes: snapshot.notes.clone(),
+        implicit_type,
+        implicit_muscles: None,
+        bodyweight_to_use: snapshot.bodyweight,
+    };
+    let (id, pb_info) = service.add_workout(params).map_err(|e| e.to_string())?;
+    if let Some(km) = snapshot.distance {
+        if let Err(e) = set_distance(service, id, km) {
+            if let Err(cleanup) = service.delete_workouts(&[id]) {
+                eprintln!("Failed to remove workout {}: {}", id, cleanup);
+            }
+            return Err(e);
+        }
+    }
+    Ok((id, pb_info))
+}
+
+/// For distances entered in miles; the lib stores km.
+pub const MILES_PER_KM: f64 = 0.621371;
+
+/// Stores a distance in km as is. The lib takes distances in the user's units
+/// and converts them, so stored values can't go back through it.
+pub fn set_distance(service: &AppService, id: i64, km: f64) -> Result<(), String> {
+    service
+        .conn
+        .execute("UPDATE workouts SET distance = ?1 WHERE id = ?2", (km, id))
+        .map(|_| ())
+        .map_err(|e| format!("Failed to set the distance of workout {}: {}", id, e))
+}
+
+// Which optional fields of a workout are set, by column name.
+fn set_columns(snapshot: &WorkoutSnapshot) -> [(&'static str, bool); 7] {
+    [
+        ("sets", snapshot.sets.is_some()),
+        ("reps", snapshot.reps.is_some()),
+        ("weight", snapshot.weight.is_some()),
+        ("duration_minutes", snapshot.duration_minutes.is_some()),
+        ("distance", snapshot.distance.is_some()),
+        ("bodyweight", snapshot.bodyweight.is_some()),
+        ("notes", snapshot.notes.is_some()),
+    ]
+}
+
+fn table_columns(service: &AppService, table: &str) -> Result<Vec<String>, String> {
+    let mut stmt = service
+        .conn
+        .prepare("SELECT name FROM pragma_table_info(?1)")
+        .map_err(|e| e.to_string())?;
+    let columns = stmt
+        .query_map([table], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| e.to_string())?;
+    if columns.is_empty() {
+        return Err(format!("Table {} not found", table));
+    }
+    Ok(columns)
+}
+
+/// Overwrites workout `id` with the values in `snapshot`, including clearing
+/// fields the snapshot doesn't have.
+pub fn overwrite_workout(
+    service: &AppService,
+    id: i64,
+    snapshot: &WorkoutSnapshot,
+) -> Result<(), String> {
+    let current = WorkoutSnapshot::from(&find_workout(service, id)?);
+    let params = EditWorkoutParams {
+        id,
+        new_exercise_identifier: Some(snapshot.exercise_name.clone()),
+        new_sets: snapshot.sets,
+        new_reps: snapshot.reps,
+        new_weight: snapshot.weight,
+        new_bodyweight: snapshot.bodyweight,
+        new_duration: snapshot.duration_minutes,
+        new_distance_arg: snapshot.distance,
+        new_notes: snapshot.notes.clone(),
+        new_date: None,
+    };
+    edit_workout_at(service, params, Some(snapshot.timestamp))
+        .map_err(|e| format!("Failed to restore workout {}: {}", id, e))?;
+    if let Some(km) = snapshot.distance {
+        set_distance(service, id, km)?;
+    }
+
+    // `None` in `EditWorkoutParams` keeps a field, so the edit above can't
+    // clear one. Those columns are nulled directly; the edit already marked
+    // the row for sync.
+    let cleared: Vec<&str> = set_columns(&current)
+        .into_iter()
+        .zip(set_columns(snapshot))
+        .filter(|((_, was_set), (_, is_set))| *was_set && !is_set)
+        .map(|((column, _), _)| column)
+        .collect();
+    if cleared.is_empty() {
+        return Ok(());
+    }
+    let columns = table_columns(service, "workouts")?;
+    if let Some(missing) = cleared.iter().find(|c| !columns.iter().any(|n| n == *c)) {
+        return Err(format!(
+            "Failed to restore workout {}: no {} column to clear",
+            id, missing
+        ));
+    }
+    let assignments: Vec<String> = cleared.iter().map(|c| format!("{} = NULL", c)).collect();
+    service
+        .conn
+        .execute(
+            &format!(
+                "UPDATE workouts SET {} WHERE id = ?1",
+                assignments.join(", ")
+            ),
+            [id],
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to restore workout {}: {}", id, e))
+}
+
+/// Runs `f` inside a SQLite savepoint named `name`, releasing it if `f`
+/// succeeds and rolling everything back to it if `f` fails. Savepoints nest,
+/// so this also works inside a transaction that is already open.
+pub fn in_savepoint<T>(
+    service: &AppService,
+    name: &'static str,
+    f: impl FnOnce(&AppService) -> Result<T, String>,
+) -> Result<T, String> {
+    service
+        .conn
+        .execute_batch(&format!("SAVEPOINT {}", name))
+        .map_err(|e| format!("Failed to start savepoint {}: {}", name, e))?;
+    let result = f(service).and_then(|value| {
+        service
+            .conn
+            .execute_batch(&format!("RELEASE {}", name))
+            .map(|_| value)
+            .map_err(|e| format!("Failed to release savepoint {}: {}", name, e))
+    });
+    if let Err(e) = &result {
+        if let Err(rollback) = service
+            .conn
+            .execute_batch(&format!("ROLLBACK TO {0}; RELEASE {0}", name))
+        {
+            return Err(format!("{}; rolling back also failed: {}", e, rollback));
+        }
+    }
+    result
+}
+
+/// `edit_workout` with an exact timestamp instead of a date. The lib only
+/// takes a date, so it makes its change (and marks the row for sync) with the
+/// new date, and the time is then set directly, written in the same format as
+/// the stored value. If that last step fails the lib's change stays; callers
+/// put the workout back from a snapshot.
+pub fn edit_workout_at(
+    service: &AppService,
+    mut params: EditWorkoutParams,
+    timestamp: Option<DateTime<Utc>>,
+) -> Result<u64, String> {
+    let id = params.id;
+    let stored = match timestamp {
+        Some(timestamp) => {
+            let stored: String = service
+                .conn
+                .query_row(
+                    "SELECT timestamp FROM workouts WHERE id = ?1",
+                    [id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to read the time of workout {}: {}", id, e))?;
+            let formatted = format_like(&stored, timestamp).ok_or_else(|| {
+                format!(
+                    "Workout {} has a timestamp in an unknown format: {}",
+                    id, stored
+                )
+            })?;
+            params.new_date = Some(timestamp.date_naive());
+            Some(formatted)
+        }
+        None => None,
+    };
+    let updated = service.edit_workout(params).map_err(|e| e.to_string())?;
+    if let Some(formatted) = stored {
+        service
+            .conn
+            .execute(
+                "UPDATE workouts SET timestamp = ?1 WHERE id = ?2",
+                (formatted, id),
+            )
+            .map_err(|e| format!("Failed to set the time of workout {}: {}", id, e))?;
+    }
+    Ok(updated)
+}
+
+/// `timestamp` written the way `stored` is: the same separator between date
+/// and time, at least as many fractional digits and the same kind of UTC
+/// suffix. `None` if `stored` isn't a timestamp this understands.
+fn format_like(stored: &str, timestamp: DateTime<Utc>) -> Option<String> {
+    let separator = match stored.as_bytes().get(10)? {
+        b'T' => 'T',
+        b' ' => ' ',
+        _ => return None,
+    };
+    let (seconds, rest) = (stored.get(..19)?, stored.get(19..)?);
+    NaiveDateTime::parse_from_str(seconds, &format!("%Y-%m-%d{}%H:%M:%S", separator)).ok()?;
+    let (stored_digits, suffix) = match rest.strip_prefix('.') {
+        Some(fraction) => {
+            let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+            (digits, &fraction[digits..])
+        }
+        None => (0, rest),
+    };
+    let signed = suffix.starts_with(['+', '-']);
+    let suffix = match suffix.len() {
+        0 => "",
+        1 if suffix == "Z" => "Z",
+        6 if signed && suffix.as_bytes()[3] == b':' => "+00:00",
+        5 if signed => "+0000",
+        _ => return None,
+    };
+    // Keep the new value's precision even where the stored one had less.
+    let nanos = timestamp.timestamp_subsec_nanos();
+    let needed = match nanos {
+        0 => 0,
+        n if n % 1_000_000 == 0 => 3,
+        n if n % 1_000 == 0 => 6,
+        _ => 9,
+    };
+    let digits = stored_digits.max(needed);
+    let mut fraction = format!("{:09}", nanos);
+    fraction.truncate(digits);
+    let fraction = if digits == 0 {
+        String::new()
+    } else {
+        format!(".{:0<width$}", fraction, width = digits)
+    };
+    Some(format!(
+        "{}{}{}{}{}",
+        timestamp.format("%Y-%m-%d"),
+        separator,
+        timestamp.format("%H:%M:%S"),
+        fraction,
+        suffix
+    ))
+}
+
+/// Recreates a deleted exercise together with its aliases.
+pub fn recreate_exercise(service: &AppService, snapshot: &ExerciseSnapshot) -> Result<i64, String> {
+    let ex_type = parse_exercise_type(&snapshot.exercise_type)?;
+    let log_flags = Some((
+        Some(snapshot.log_weight),
+        Some(snapshot.log_reps),
+        Some(snapshot.log_duration),
+        Some(snapshot.log_distance),
+    ));
+    let id = service
+        .create_exercise(
+            &snapshot.name,
+            ex_type,
+            log_flags,
+            snapshot.muscles.as_deref(),
+        )
+        .map_err(|e| format!("Failed to recreate exercise '{}': {}", snapshot.name, e))?;
+    restore_aliases_and_details(service, id, snapshot)?;
+    if snapshot.archived {
+        set_archived(
+            service,
+            id,
+            &snapshot.name,
+            snapshot.muscles.as_deref(),
+            true,
+        )?;
+    }
+    Ok(id)
+}
+
+/// Puts a deleted exercise back from its stored row, under its old id, together
+/// with its aliases.
+pub fn restore_exercise(
+    service: &AppService,
+    snapshot: &ExerciseSnapshot,
+    row: &StoredRow,
+) -> Result<i64, String> {
+    let id = row_id(row).ok_or("Stored exercise row has no id")?;
+    write_row(service, "exercises", row)?;
+    // An edit that keeps the muscles marks the row for sync like the lib's own.
+    if let Err(e) = service.edit_exercise(
+        &snapshot.name,
+        None,
+        None,
+        None,
+        Some(snapshot.muscles.as_deref()),
+    ) {
+        let _ = service.delete_exercise(&[snapshot.name.clone()]);
+        return Err(format!(
+            "Failed to restore exercise '{}': {}",
+            snapshot.name, e
+        ));
+    }
+    restore_aliases_and_details(service, id, snapshot)?;
+    Ok(id)
+}
+
+fn restore_aliases_and_details(
+    service: &AppService,
+    id: i64,
+    snapshot: &ExerciseSnapshot,
+) -> Result<(), String> {
+    for alias in &snapshot.aliases {
+        if let Err(e) = service.create_alias(alias, &snapshot.name) {
+            eprintln!("Failed to recreate alias '{}': {}", alias, e);
+        }
+    }
+    if !snapshot.details.is_empty() {
+        exercise_details::save(service, id, &snapshot.details)?;
+    }
+    Ok(())
+}
+
+/// Puts an existing exercise back to `snapshot`'s name, type, logged fields,
+/// muscles and details. Its aliases and workouts follow a rename through the
+/// lib; the archived flag is left alone.
+pub fn overwrite_exercise(
+    service: &AppService,
+    current_name: &str,
+    snapshot: &ExerciseSnapshot,
+) -> Result<(), String> {
+    let definition = resolve_exercise(service, current_name)?;
+    let ex_type = parse_exercise_type(&snapshot.exercise_type)?;
+    let new_name = (definition.name != snapshot.name).then_some(snapshot.name.as_str());
+    let log_flags = Some((
+        Some(snapshot.log_weight),
+        Some(snapshot.log_reps),
+        Some(snapshot.log_duration),
+        Some(snapshot.log_distance),
+    ));
+    in_savepoint(service, "overwrite_exercise", |service| {
+        service
+            .edit_exercise(
+                &definition.name,
+                new_name,
+                Some(ex_type),
+                log_flags,
+                Some(snapshot.muscles.as_deref()),
+            )
+            .map_err(|e| format!("Failed to edit exercise '{}': {}", definition.name, e))?;
+        exercise_details::save(service, definition.id, &snapshot.details)
+    })
+}
+
+/// Archives or unarchives an exercise. The lib has no call for `is_active`, so
+/// the flag is set directly, followed by an `edit_exercise` that leaves the
+/// muscles as they are but marks the row for sync like the lib's own edits.
+pub fn set_archived(
+    service: &AppService,
+    id: i64,
+    name: &str,
+    muscles: Option<&str>,
+    archived: bool,
+) -> Result<(), String> {
+    service
+        .conn
+        .execute(
+            "UPDATE exercises SET is_active = ?1 WHERE id = ?2",
+            (!archived, id),
+        )
+        .map_err(|e| format!("Failed to update exercise '{}': {}", name, e))?;
+    if let Err(e) = service.edit_exercise(name, None, None, None, Some(muscles)) {
+        // Put the flag back so the local row doesn't drift from what syncs.
+        service
+            .conn
+            .execute(
+                "UPDATE exercises SET is_active = ?1 WHERE id = ?2",
+                (archived, id),
+            )
+            .map_err(|e| format!("Failed to update exercise '{}': {}", name, e))?;
+        return Err(e.to_string());
+    }
+    Ok(())
+}
+
+/// A row exactly as the database holds it, every column as the SQL literal
+/// SQLite's `quote()` gives for it. Writing it back restores the record under
+/// its old id with whatever columns the lib keeps for sync.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StoredRow {
+    pub columns: Vec<String>,
+    pub values: Vec<String>,
+}
+
+// Only what `quote()` produces: NULL, a number, a string or a blob literal.
+fn is_sql_literal(value: &str) -> bool {
+    let quoted_body = |s: &str| s.split("''").all(|part| !part.contains('\''));
+    if value == "NULL" {
+        true
+    } else if let Some(text) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        quoted_body(text)
+    } else if let Some(hex) = value.strip_prefix("X'").and_then(|v| v.strip_suffix('\'')) {
+        hex.chars().all(|c| c.is_ascii_hexdigit())
+    } else {
+        !value.is_empty()
+            && value
+                .chars()
+                .all(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+            && value.parse::<f64>().is_ok()
+    }
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Reads row `id` of `table`, if there is one.
+pub fn read_row(service: &AppService, table: &str, id: i64) -> Result<Option<StoredRow>, String> {
+    let columns = table_columns(service, table)?;
+    let selected: Vec<String> = columns
+        .iter()
+        .map(|c| format!("quote({})", quote_identifier(c)))
+        .collect();
+    let sql = format!(
+        "SELECT {} FROM {} WHERE id = ?1",
+        selected.join(", "),
+        quote_identifier(table)
+    );
+    let mut stmt = service.conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let mut rows = stmt
+        .query_map([id], |row| {
+            (0..columns.len())
+                .map(|i| row.get::<_, String>(i))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?;
+    rows.next()
+        .transpose()
+        .map_err(|e| e.to_string())
+        .map(|values| values.map(|values| StoredRow { columns, values }))
+}
+
+/// Puts a row read by `read_row` back. Whatever is left under its id (a row
+/// the lib only marked as deleted) is replaced; a clash with any other row
+/// fails without changing anything.
+pub fn write_row(service: &AppService, table: &str, row: &StoredRow) -> Result<(), String> {
+    let id = row_id(row).ok_or_else(|| format!("Stored {} row has no id", table))?;
+    let known = table_columns(service, table)?;
+    if row.columns.len() != row.values.len()
+        || row.columns.iter().any(|c| !known.contains(c))
+        || !row.values.iter().all(|v| is_sql_literal(v))
+    {
+        return Err(format!("Stored {} row doesn't match the database", table));
+    }
+    let table = quote_identifier(table);
+    let columns: Vec<String> = row.columns.iter().map(|c| quote_identifier(c)).collect();
+    // A savepoint rather than a transaction, so this also works inside one.
+    let write = || -> Result<(), String> {
+        service
+            .conn
+            .execute(&format!("DELETE FROM {} WHERE id = ?1", table), [id])
+            .map_err(|e| e.to_string())?;
+        service
+            .conn
+            .execute(
+                &format!(
+                    "INSERT INTO {} ({}) VALUES ({})",
+                    table,
+                    columns.join(", "),
+                    row.values.join(", ")
+                ),
+                [],
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    };
+    service
+        .conn
+        .execute_batch("SAVEPOINT write_row")
+        .map_err(|e| e.to_string())?;
+    let result = write();
+    let end = if result.is_ok() {
+        "RELEASE write_row"
+    } else {
+        "ROLLBACK TO write_row; RELEASE write_row"
+    };
+    service.conn.execute_batch(end).map_err(|e| e.to_string())?;
+    result.map_err(|e| format!("Failed to write {} row: {}", table, e))
+}
+
+/// The row's `id` column.
+pub fn row_id(row: &StoredRow) -> Option<i64> {
+    let index = row.columns.iter().position(|c| c == "id")?;
+    row.values[index].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn temp_service(name: &str) -> AppService {
+        let dir =
+            std::env::temp_dir().join(format!("task-athlete-gui-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        AppService::initialize_with_paths(&dir.join("config.toml"), &dir.join("workouts.sqlite"))
+            .unwrap()
+    }
+
+    fn log_run(service: &mut AppService, km: f64) -> i64 {
+        let snapshot = WorkoutSnapshot {
+            id: 0,
+            timestamp: Utc.with_ymd_and_hms(2024, 5, 10, 7, 0, 0).unwrap(),
+            exercise_name: "Running".to_string(),
+            exercise_type: Some("cardio".to_string()),
+            sets: None,
+            reps: None,
+            weight: None,
+            duration_minutes: Some(30),
+            distance: Some(km),
+            bodyweight: None,
+            notes: None,
+        };
+        add_snapshot(service, &snapshot).unwrap().0
+    }
+
+    fn at(h: u32, m: u32, s: u32, millis: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 2, h, m, s).unwrap()
+            + chrono::Duration::milliseconds(i64::from(millis))
+    }
+
+    #[test]
+    fn format_like_keeps_the_stored_shape() {
+        assert_eq!(
+            format_like("2024-03-01T09:00:00.123456+00:00", at(10, 30, 0, 500)).as_deref(),
+            Some("2024-03-02T10:30:00.500000+00:00")
+        );
+        assert_eq!(
+            format_like("2024-03-01 09:00:00", at(10, 30, 5, 0)).as_deref(),
+            Some("2024-03-02 10:30:05")
+        );
+        assert_eq!(
+            format_like("2024-03-01T09:00:00Z", at(7, 0, 0, 0)).as_deref(),
+            Some("2024-03-02T07:00:00Z")
+        );
+    }
+
+    #[test]
+    fn format_like_never_drops_precision() {
+        assert_eq!(
+            format_like("2024-03-01T09:00:00+0000", at(7, 0, 0, 250)).as_deref(),
+            Some("2024-03-02T07:00:00.250+0000")
+        );
+    }
+
+    #[test]
+    fn format_like_rejects_unknown_formats() {
+        assert_eq!(format_like("1709283600", at(7, 0, 0, 0)), None);
+        assert_eq!(format_like("2024-03-01/09:00:00", at(7, 0, 0, 0)), None);
+        assert_eq!(format_like("2024-03-01T09:00:00 UTC", at(7, 0, 0, 0)), None);
+    }
+
+    #[test]
+    fn sql_literals_are_what_quote_produces() {
+        for literal in [
+            "NULL", "42", "-3", "1.5e3", "'text'", "'it''s'", "''", "X'0aFF'",
+        ] {
+            assert!(is_sql_literal(literal), "{} should be accepted", literal);
+        }
+    }
+
+    #[test]
+    fn sql_literals_reject_anything_else() {
+        for value in [
+            "",
+            "'",
+            "'a'b'",
+            "'x' OR 1=1 --'",
+            "X'zz'",
+            "1; DROP TABLE workouts",
+            "e",
+            "NaN",
+            "null",
+            "(SELECT 1)",
+        ] {
+            assert!(!is_sql_literal(value), "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn savepoint_keeps_all_changes_or_none() {
+        let mut service = temp_service("savepoint");
+        let first = log_run(&mut service, 5.0);
+        let second = log_run(&mut service, 8.0);
+
+        let failed = in_savepoint(&service, "test_edit", |service| {
+            set_distance(service, first, 6.0)?;
+            set_distance(service, second, 9.0)?;
+            Err::<(), _>("stop".to_string())
+        });
+        assert_eq!(failed, Err("stop".to_string()));
+        assert_eq!(find_workout(&service, first).unwrap().distance, Some(5.0));
+        assert_eq!(find_workout(&service, second).unwrap().distance, Some(8.0));
+
+        in_savepoint(&service, "test_edit", |service| {
+            set_distance(service, first, 6.0)?;
+            set_distance(service, second, 9.0)
+        })
+        .unwrap();
+        assert_eq!(find_workout(&service, first).unwrap().distance, Some(6.0));
+        assert_eq!(find_workout(&service, second).unwrap().distance, Some(9.0));
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use task_athlete_lib::AppService;
+
+use crate::one_rm::OneRmFormula;
+
+const SETTINGS_FILE_NAME: &str = "gui_settings.json";
+
+fn default_trash_retention_days() -> u32 {
+    30
+}
+
+fn default_secondary_muscle_weight() -> f64 {
+    0.5
+}
+
+/// Settings that only the GUI uses. They live next to the profile's
+/// `config.toml` so the CLI/TUI config format stays untouched, and are merged
+/// into the `get_config` payload.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GuiSettings {
+    /// Trash entries older than this are purged. 0 keeps them forever.
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+    /// IANA name such as "Europe/Berlin". `None` follows the system.
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    /// Share of a set's work credited to each secondary muscle of its
+    /// exercise, from 0 to 1.
+    #[serde(default = "default_secondary_muscle_weight")]
+    pub secondary_muscle_weight: f64,
+    #[serde(default)]
+    pub one_rm_formula: OneRmFormula,
+}
+
+impl Default for GuiSettings {
+    fn default() -> Self {
+        GuiSettings {
+            trash_retention_days: default_trash_retention_days(),
+            time_zone: None,
+            secondary_muscle_weight: default_secondary_muscle_weight(),
+            one_rm_formula: OneRmFormula::default(),
+        }
+    }
+}
+
+/// Path of a GUI-owned file stored alongside the active profile's config.
+pub fn profile_file(service: &AppService, file_name: &str) -> PathBuf {
+    service
+        .get_config_path()
+        .parent()
+        .map(|dir| dir.join(file_name))
+        .unwrap_or_else(|| PathBuf::from(file_name))
+}
+
+// Settings per file as last read, with the file's modification time. Day
+// grouping asks for the time zone on every call, so the file is only read
+// again when it changed.
+type Cache = HashMap<PathBuf, (SystemTime, GuiSettings)>;
+
+fn cache() -> &'static Mutex<Cache> {
+    static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl GuiSettings {
+    pub fn load(service: &AppService) -> Result<Self, String> {
+        let path = profile_file(service, SETTINGS_FILE_NAME);
+        if !path.exists() {
+            return Ok(GuiSettings::default());
+        }
+        let modified = modified(&path);
+        let mut cache = cache()
+            .lock()
+            .map_err(|e| format!("Failed to lock GUI settings: {}", e))?;
+        if let Some((cached_at, settings)) = cache.get(&path) {
+            if Some(*cached_at) == modified {
+                return Ok(settings.clone());
+            }
+        }
+        let raw =
+            fs::read_to_string(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        let settings: GuiSettings =
+            serde_json::from_str(&raw).map_err(|e| format!("Failed to parse {:?}: {}", path, e))?;
+        match modified {
+            Some(modified) => cache.insert(path, (modified, settings.clone())),
+            None => cache.remove(&path),
+        };
+        Ok(settings)
+    }
+
+    pub fn save(&self, service: &AppService) -> Result<(), String> {
+        let path = profile_file(service, SETTINGS_FILE_NAME);
+        let raw = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize GUI settings: {}", e))?;
+        let mut cache = cache()
+            .lock()
+            .map_err(|e| format!("Failed to lock GUI settings: {}", e))?;
+        // Dropped first: a write within the clock's resolution keeps the old
+        // modification time.
+        cache.remove(&path);
+        fs::write(&path, raw).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+        if let Some(modified) = modified(&path) {
+            cache.insert(path, (modified, self.clone()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+
+use task_athlete_lib::{AppService, Config};
+
+use crate::audit;
+use crate::exercise_details;
+
+/// Number of read-only services opened next to the writer.
+pub const DEFAULT_READER_COUNT: usize = 4;
+
+pub struct ReaderSlot {
+    service: AppService,
+    // Config version this reader last copied from the writer.
+    config_version: u64,
+}
+
+/// Shared backend state: one writable `AppService` plus a few read-only ones
+/// opened on the same database file. The database runs in WAL mode, so
+/// readers never wait for a write (or for each other) and a long stats query
+/// no longer holds up logging a set.
+///
+/// The lib keeps `Config` inside `AppService`, so every write publishes the
+/// writer's config and readers pick it up the next time they are handed out.
+pub struct ServicePool {
+    writer: Mutex<AppService>,
+    readers: Vec<Mutex<ReaderSlot>>,
+    next_reader: AtomicUsize,
+    config: RwLock<Config>,
+    config_version: AtomicU64,
+}
+
+pub type AppState = Arc<ServicePool>;
+
+fn open_reader(writer: &AppService) -> Result<AppService, String> {
+    let reader = AppService::initialize_with_paths(writer.get_config_path(), writer.get_db_path())
+        .map_err(|e| format!("Failed to open read-only database handle: {}", e))?;
+    reader
+        .conn
+        .execute_batch("PRAGMA query_only = ON;")
+        .map_err(|e| format!("Failed to make database handle read-only: {}", e))?;
+    Ok(reader)
+}
+
+fn open_readers(writer: &AppService, count: usize) -> Result<Vec<Mutex<ReaderSlot>>, String> {
+    writer
+        .conn
+        .query_row("PRAGMA journal_mode = WAL;", [], |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| format!("Failed to enable WAL mode: {}", e))?;
+    (0..count)
+        .map(|_| {
+            open_reader(writer).map(|service| {
+                Mutex::new(ReaderSlot {
+                    service,
+                    config_version: 0,
+                })
+            })
+        })
+        .collect()
+}
+
+impl ServicePool {
+    pub fn new(writer: AppService, reader_count: usize) -> Result<Self, String> {
+        // Readers are query-only, so GUI tables must exist before they open.
+        audit::ensure_schema(&writer)?;
+        exercise_details::ensure_schema(&writer)?;
+        exercise_details::adopt_carriers(&writer)?;
+        let readers = open_readers(&writer, reader_count)?;
+        Ok(ServicePool {
+            config: RwLock::new(writer.config.clone()),
+            writer: Mutex::new(writer),
+            readers,
+            next_reader: AtomicUsize::new(0),
+            config_version: AtomicU64::new(0),
+        })
+    }
+
+    /// Exclusive access to the writable service. Config changes made through
+    /// the guard become visible to readers when it is dropped.
+    pub fn write(&self) -> Result<WriteGuard<'_>, String> {
+        let service = self
+            .writer
+            .lock()
+            .map_err(|e| format!("Failed to lock state: {}", e))?;
+        Ok(WriteGuard {
+            pool: self,
+            service,
+        })
+    }
+
+    /// A read-only service. Tries every reader without blocking before
+    /// waiting on one, and falls back to the writer when no readers exist.
+    pub fn read(&self) -> Result<ReadGuard<'_>, String> {
+        if self.readers.is_empty() {
+            return self.write().map(ReadGuard::Writer);
+        }
+        let start = self.next_reader.fetch_add(1, Ordering::Relaxed);
+        let count = self.readers.len();
+        let slot = (0..count)
+            .find_map(|offset| self.readers[(start + offset) % count].try_lock().ok())
+            .map(Ok)
+            .unwrap_or_else(|| self.readers[start % count].lock())
+            .map_err(|e| format!("Failed to lock read state: {}", e))?;
+        Ok(ReadGuard::Reader(self.refresh_config(slot)?))
+    }
+
+    /// A snapshot of the current config without touching any service.
+    pub fn config(&self) -> Result<Config, String> {
+        self.config
+            .read()
+            .map(|config| config.clone())
+            .map_err(|e| format!("Failed to read config: {}", e))
+    }
+
+    fn refresh_config<'a>(
+        &self,
+        mut slot: MutexGuard<'a, ReaderSlot>,
+    ) -> Result<MutexGuard<'a, ReaderSlot>, String> {
+        let version = self.config_version.load(Ordering::Acquire);
+        if slot.config_version != version {
+            slot.service.config = self.config()?;
+            slot.config_version = version;
+        }
+        Ok(slot)
+    }
+
+    fn publish_config(&self, config: &Config) {
+        match self.config.write() {
+            Ok(mut shared) => {
+                *shared = config.clone();
+                self.config_version.fetch_add(1, Ordering::Release);
+            }
+            Err(e) => eprintln!("Failed to publish config to readers: {}", e),
+        }
+    }
+
+    /// Swaps in a service opened on another database. See `WriteGuard::replace`.
+    pub fn replace(&self, new_writer: AppService) -> Result<AppService, String> {
+        self.write()?.replace(new_writer)
+    }
+}
+
+pub struct WriteGuard<'a> {
+    pool: &'a ServicePool,
+    service: MutexGuard<'a, AppService>,
+}
+
+impl WriteGuard<'_> {
+    /// Replaces the writer and reopens every reader against its database.
+    /// Nothing changes if a reader fails to open. Returns the previous writer;
+    /// the old readers are already closed, so the caller may remove the old
+    /// database files once it drops it.
+    pub fn replace(&mut self, new_writer: AppService) -> Result<AppService, String> {
+        audit::ensure_schema(&new_writer)?;
+        exercise_details::ensure_schema(&new_writer)?;
+        exercise_details::adopt_carriers(&new_writer)?;
+        let mut new_readers = Vec::with_capacity(self.pool.readers.len());
+        for _ in 0..self.pool.readers.len() {
+            new_readers.push(open_reader(&new_writer)?);
+        }
+        for (slot, reader) in self.pool.readers.iter().zip(new_readers) {
+            let mut slot = slot
+                .lock()
+                .map_err(|e| format!("Failed to lock read state: {}", e))?;
+            slot.service = reader;
+            slot.config_version = 0;
+        }
+        Ok(std::mem::replace(&mut *self.service, new_writer))
+    }
+}
+
+impl Deref for WriteGuard<'_> {
+    type Target = AppService;
+
+    fn deref(&self) -> &AppService {
+        &self.service
+    }
+}
+
+impl DerefMut for WriteGuard<'_> {
+    fn deref_mut(&mut self) -> &mut AppService {
+        &mut self.service
+    }
+}
+
+impl Drop for WriteGuard<'_> {
+    fn drop(&mut self) {
+        self.pool.publish_config(&self.service.config);
+    }
+}
+
+pub enum ReadGuard<'a> {
+    Reader(MutexGuard<'a, ReaderSlot>),
+    Writer(WriteGuard<'a>),
+}
+
+impl Deref for ReadGuard<'_> {
+    type Target = AppService;
+
+    fn deref(&self) -> &AppService {
+        match self {
+            ReadGuard::Reader(slot) => &slot.service,
+            ReadGuard::Writer(guard) => &**guard,
+        }
+    }
+}
+
+impl DerefMut for ReadGuard<'_> {
+    fn deref_mut(&mut self) -> &mut AppService {
+        match self {
+            ReadGuard::Reader(slot) => &mut slot.service,
+            ReadGuard::Writer(guard) => &mut **guard,
+        }
+    }
+}
+
+/// Runs `f` on Tauri's blocking thread pool so SQLite work never stalls the
+/// async runtime.
+pub async fn run_blocking<T, F>(state: &AppState, f: F) -> Result<T, String>
+where
+    F: FnOnce(&ServicePool) -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    let pool = Arc::clone(state);
+    blocking(move || f(&pool)).await
+}
+
+/// `run_blocking` for work that doesn't go through the services, such as
+/// profile and media files.
+pub async fn blocking<T, F>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Background task failed: {}", e))?
+}
@@ -0,0 +1,199 @@
+//! Workout streaks over local calendar days.
+
+use chrono::{Duration, NaiveDate};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+use task_athlete_lib::{AppService, Workout};
+
+use crate::graphs::Period;
+use crate::records;
+use crate::state::run_blocking;
+use crate::timezone;
+use crate::AppState;
+
+const DEFAULT_CONSISTENCY_WEEKS: u32 = 12;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Streaks {
+    /// Workout days in the running streak; 0 once it has lapsed.
+    pub current: u32,
+    pub longest: u32,
+    pub current_start: Option<NaiveDate>,
+    pub last_day: Option<NaiveDate>,
+}
+
+/// The distinct local days the workouts fall on.
+pub fn workout_days(workouts: &[Workout], tz: Tz) -> BTreeSet<NaiveDate> {
+    workouts
+        .iter()
+        .map(|w| timezone::local_date(tz, w.timestamp))
+        .collect()
+}
+
+/// A streak goes on while each workout day comes at most `interval_days` after
+/// the previous one, and is current while `today` is still within
+/// `interval_days` of its last day.
+pub fn compute(days: &BTreeSet<NaiveDate>, interval_days: u32, today: NaiveDate) -> Streaks {
+    let interval = i64::from(interval_days.max(1));
+    let mut longest = 0;
+    let mut length = 0;
+    let mut start = None;
+    let mut previous: Option<NaiveDate> = None;
+    for &day in days {
+        match previous {
+            Some(prev) if (day - prev).num_days() <= interval => length += 1,
+            _ => {
+                length = 1;
+                start = Some(day);
+            }
+        }
+        longest = longest.max(length);
+        previous = Some(day);
+    }
+    let running = previous.is_some_and(|last| (today - last).num_days() <= interval);
+    Streaks {
+        current: if running { length } else { 0 },
+        longest,
+        current_start: if running { start } else { None },
+        last_day: previous,
+    }
+}
+
+#[derive(Serialize)]
+pub struct StreakInfo {
+    pub interval_days: u32,
+    /// Workout days in the running streak; 0 once it has lapsed.
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub streak_start: Option<NaiveDate>,
+    pub last_workout_date: Option<NaiveDate>,
+    /// Days left to train before the streak breaks: 0 means today is the
+    /// last chance. `None` without a running streak.
+    pub days_until_break: Option<i64>,
+    /// Weeks looked back over, this week included.
+    pub weeks: u32,
+    /// Weeks in that span with at least one workout.
+    pub weeks_trained: u32,
+    pub weekly_consistency_percent: f64,
+}
+
+pub fn streak_info(service: &AppService, weeks: u32) -> Result<StreakInfo, String> {
+    let tz = timezone::user_time_zone(service);
+    let today = timezone::today(tz);
+    let interval_days = service.config.streak_interval_days.max(1);
+    let days = workout_days(&records::all_workouts(service)?, tz);
+    let streaks = compute(&days, interval_days, today);
+
+    let weeks = weeks.max(1);
+    let this_week = Period::Week.start_of(today);
+    let first_week = Duration::try_weeks(i64::from(weeks) - 1)
+        .and_then(|span| this_week.checked_sub_signed(span))
+        .ok_or_else(|| format!("Can't look back {} weeks", weeks))?;
+    let weeks_trained = days
+        .range(first_week..=today)
+        .map(|day| Period::Week.start_of(*day))
+        .collect::<BTreeSet<_>>()
+        .len() as u32;
+
+    Ok(StreakInfo {
+        interval_days,
+        current_streak: streaks.current,
+        longest_streak: streaks.longest,
+        streak_start: streaks.current_start,
+        last_workout_date: streaks.last_day,
+        days_until_break: streaks
+            .current_start
+            .and(streaks.last_day)
+            .map(|last| i64::from(interval_days) - (today - last).num_days()),
+        weeks,
+        weeks_trained,
+        weekly_consistency_percent: f64::from(weeks_trained) / f64::from(weeks) * 100.0,
+    })
+}
+
+// --- Tauri Commands ---
+
+/// Streaks over all workouts under the configured interval, and the share of
+/// the last `weeks` weeks (12 by default) with a workout, on local days.
+#[tauri::command]
+pub async fn get_streak_info(
+    weeks: Option<u32>,
+    state: tauri::State<'_, AppState>,
+) -> Result<StreakInfo, String> {
+    let weeks = weeks.unwrap_or(DEFAULT_CONSISTENCY_WEEKS);
+    run_blocking(&state, move |pool| streak_info(&pool.read()?, weeks)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_service(name: &str) -> AppService {
+        let dir =
+            std::env::temp_dir().join(format!("task-athlete-gui-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        AppService::initialize_with_paths(&dir.join("config.toml"), &dir.join("workouts.sqlite"))
+            .unwrap()
+    }
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, d).unwrap()
+    }
+
+    fn days(list: &[u32]) -> BTreeSet<NaiveDate> {
+        list.iter().map(|&d| date(d)).collect()
+    }
+
+    #[test]
+    fn no_workouts_means_no_streak() {
+        assert_eq!(compute(&BTreeSet::new(), 1, date(10)), Streaks::default());
+    }
+
+    #[test]
+    fn consecutive_days_make_a_running_streak() {
+        let streaks = compute(&days(&[1, 3, 4, 5]), 1, date(5));
+        assert_eq!(
+            streaks,
+            Streaks {
+                current: 3,
+                longest: 3,
+                current_start: Some(date(3)),
+                last_day: Some(date(5)),
+            }
+        );
+    }
+
+    #[test]
+    fn streak_lapses_once_the_interval_has_passed() {
+        let set = days(&[1, 2, 3]);
+        assert_eq!(compute(&set, 2, date(5)).current, 3);
+        let lapsed = compute(&set, 2, date(6));
+        assert_eq!(lapsed.current, 0);
+        assert_eq!(lapsed.current_start, None);
+        assert_eq!(lapsed.longest, 3);
+        assert_eq!(lapsed.last_day, Some(date(3)));
+    }
+
+    #[test]
+    fn interval_allows_rest_days() {
+        let set = days(&[1, 3, 5, 9]);
+        assert_eq!(compute(&set, 2, date(9)).longest, 3);
+        assert_eq!(compute(&set, 2, date(9)).current, 1);
+        assert_eq!(compute(&set, 4, date(9)).current, 4);
+    }
+
+    #[test]
+    fn looking_back_too_far_is_an_error() {
+        let service = temp_service("streak-weeks");
+        assert!(streak_info(&service, u32::MAX).is_err());
+        assert_eq!(streak_info(&service, 0).unwrap().weeks, 1);
+    }
+
+    #[test]
+    fn zero_interval_counts_as_one_day() {
+        assert_eq!(compute(&days(&[1, 2]), 0, date(3)).current, 2);
+    }
+}
@@ -0,0 +1,321 @@
+//! Week, month or year in review: totals, top exercises and muscles, PRs,
+//! bodyweight and streaks over a local date range, optionally against the
+//! period just before it.
+
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use task_athlete_lib::{AppService, Workout};
+
+use crate::comparison;
+use crate::one_rm;
+use crate::personal_records::{self, RecordBreak};
+use crate::records;
+use crate::state::run_blocking;
+use crate::streaks;
+use crate::timezone;
+use crate::volume::{self, MuscleVolume, MuscleVolumeQuery};
+use crate::{parse_naive_date, AppState};
+
+const TOP_COUNT: usize = 5;
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct PeriodTotals {
+    /// Days with at least one workout.
+    pub sessions: u32,
+    /// Logged workout entries.
+    pub workouts: u32,
+    pub sets: f64,
+    pub reps: f64,
+    /// Sets × reps × weight, in the units weights are logged in.
+    pub volume: f64,
+    pub duration_minutes: f64,
+    pub distance: f64,
+}
+
+impl PeriodTotals {
+    fn add(&mut self, workout: &Workout) {
+        let sets = workout.sets.unwrap_or(1) as f64;
+        let reps = sets * workout.reps.unwrap_or(0) as f64;
+        self.workouts += 1;
+        self.sets += sets;
+        self.reps += reps;
+        self.volume += reps * workout.weight.unwrap_or(0.0);
+        self.duration_minutes += workout.duration_minutes.unwrap_or(0) as f64;
+        self.distance += workout.distance.unwrap_or(0.0);
+    }
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct ExerciseTotals {
+    pub exercise: String,
+    pub workouts: u32,
+    pub sets: f64,
+    pub volume: f64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PeriodRecord {
+    pub exercise: String,
+    #[serde(flatten)]
+    pub record: RecordBreak,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct BodyweightChange {
+    /// First and last weights logged in the period, in the units they were logged in.
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+    pub change: Option<f64>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct PeriodStreaks {
+    /// The streak as it stood on the last day of the period.
+    pub streak_at_end: u32,
+    /// Longest streak counting only the period's days.
+    pub longest_in_period: u32,
+}
+
+#[derive(Serialize)]
+pub struct PeriodSummary {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub totals: PeriodTotals,
+    /// By sets, most first.
+    pub top_exercises: Vec<ExerciseTotals>,
+    /// By sets, most first; secondary muscles count partially.
+    pub top_muscles: Vec<MuscleVolume>,
+    /// Records broken in the period, oldest first. A metric's first log
+    /// doesn't count.
+    pub personal_records: Vec<PeriodRecord>,
+    pub bodyweight: BodyweightChange,
+    pub streaks: PeriodStreaks,
+}
+
+/// Percent change from the previous period; `None` where it was 0.
+#[derive(Serialize, Default)]
+pub struct TotalsChange {
+    pub sessions: Option<f64>,
+    pub workouts: Option<f64>,
+    pub sets: Option<f64>,
+    pub reps: Option<f64>,
+    pub volume: Option<f64>,
+    pub duration_minutes: Option<f64>,
+    pub distance: Option<f64>,
+    pub personal_records: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct PeriodComparison {
+    pub current: PeriodSummary,
+    /// The same number of days right before `current`.
+    pub previous: PeriodSummary,
+    pub change_percent: TotalsChange,
+}
+
+fn percent_change(previous: f64, current: f64) -> Option<f64> {
+    (previous != 0.0).then(|| (current - previous) / previous * 100.0)
+}
+
+fn by_sets_desc(a: f64, b: f64) -> std::cmp::Ordering {
+    b.total_cmp(&a)
+}
+
+pub fn period_summary(
+    service: &mut AppService,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<PeriodSummary, String> {
+    if end < start {
+        return Err(format!("End date {} is before start date {}", end, start));
+    }
+    let tz = timezone::user_time_zone(service);
+    let all = records::all_workouts(service)?;
+    let in_period: Vec<&Workout> = all
+        .iter()
+        .filter(|w| (start..=end).contains(&timezone::local_date(tz, w.timestamp)))
+        .collect();
+
+    let mut totals = PeriodTotals::default();
+    let mut exercises: BTreeMap<String, ExerciseTotals> = BTreeMap::new();
+    for workout in &in_period {
+        totals.add(workout);
+        let exercise = exercises
+            .entry(workout.exercise_name.clone())
+            .or_insert_with(|| ExerciseTotals {
+                exercise: workout.exercise_name.clone(),
+                ..Default::default()
+            });
+        let sets = workout.sets.unwrap_or(1) as f64;
+        exercise.workouts += 1;
+        exercise.sets += sets;
+        exercise.volume += sets * workout.reps.unwrap_or(0) as f64 * workout.weight.unwrap_or(0.0);
+    }
+    let period_days: BTreeSet<NaiveDate> = in_period
+        .iter()
+        .map(|w| timezone::local_date(tz, w.timestamp))
+        .collect();
+    totals.sessions = period_days.len() as u32;
+    let mut top_exercises: Vec<ExerciseTotals> = exercises.into_values().collect();
+    top_exercises.sort_by(|a, b| by_sets_desc(a.sets, b.sets));
+    top_exercises.truncate(TOP_COUNT);
+
+    let mut top_muscles = volume::muscle_volume(
+        service,
+        &MuscleVolumeQuery {
+            start_date: Some(start.format("%Y-%m-%d").to_string()),
+            end_date: Some(end.format("%Y-%m-%d").to_string()),
+            ..Default::default()
+        },
+    )?
+    .totals;
+    top_muscles.sort_by(|a, b| by_sets_desc(a.sets, b.sets));
+    top_muscles.truncate(TOP_COUNT);
+
+    // Records need each exercise's whole history to know what was beaten.
+    let formula = one_rm::user_formula(service);
+    let mut history: BTreeMap<&str, Vec<Workout>> = BTreeMap::new();
+    for workout in &all {
+        history
+            .entry(workout.exercise_name.as_str())
+            .or_default()
+            .push(workout.clone());
+    }
+    let mut personal_records: Vec<PeriodRecord> = history
+        .iter()
+        .flat_map(|(exercise, workouts)| {
+            personal_records::record_breaks(workouts, formula, tz)
+                .into_iter()
+                .filter(|r| r.previous_value.is_some() && (start..=end).contains(&r.date))
+                .map(|record| PeriodRecord {
+                    exercise: exercise.to_string(),
+                    record,
+                })
+        })
+        .collect();
+    personal_records.sort_by_key(|r| r.record.date);
+
+    let weights: Vec<f64> = comparison::bodyweights(service)?
+        .into_iter()
+        .filter(|(day, _)| (start..=end).contains(day))
+        .map(|(_, weight)| weight)
+        .collect();
+    let bodyweight = BodyweightChange {
+        start: weights.first().copied(),
+        end: weights.last().copied(),
+        change: weights.first().zip(weights.last()).map(|(a, b)| b - a),
+    };
+
+    let interval = service.config.streak_interval_days;
+    let days_to_end: BTreeSet<NaiveDate> = streaks::workout_days(&all, tz)
+        .into_iter()
+        .filter(|day| *day <= end)
+        .collect();
+    let streaks = PeriodStreaks {
+        streak_at_end: streaks::compute(&days_to_end, interval, end).current,
+        longest_in_period: streaks::compute(&period_days, interval, end).longest,
+    };
+
+    Ok(PeriodSummary {
+        start,
+        end,
+        totals,
+        top_exercises,
+        top_muscles,
+        personal_records,
+        bodyweight,
+        streaks,
+    })
+}
+
+pub fn compare_periods(
+    service: &mut AppService,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<PeriodComparison, String> {
+    let current = period_summary(service, start, end)?;
+    let previous_end = start - Duration::days(1);
+    let previous_start = previous_end - (end - start);
+    let previous = period_summary(service, previous_start, previous_end)?;
+    let (a, b) = (&previous.totals, &current.totals);
+    let change_percent = TotalsChange {
+        sessions: percent_change(f64::from(a.sessions), f64::from(b.sessions)),
+        workouts: percent_change(f64::from(a.workouts), f64::from(b.workouts)),
+        sets: percent_change(a.sets, b.sets),
+        reps: percent_change(a.reps, b.reps),
+        volume: percent_change(a.volume, b.volume),
+        duration_minutes: percent_change(a.duration_minutes, b.duration_minutes),
+        distance: percent_change(a.distance, b.distance),
+        personal_records: percent_change(
+            previous.personal_records.len() as f64,
+            current.personal_records.len() as f64,
+        ),
+    };
+    Ok(PeriodComparison {
+        current,
+        previous,
+        change_percent,
+    })
+}
+
+// --- Tauri Commands ---
+
+/// Totals, top exercises and muscles, PRs, bodyweight change and streaks for
+/// a local date range, both ends inclusive.
+#[tauri::command]
+pub async fn get_period_summary(
+    start: String,
+    end: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<PeriodSummary, String> {
+    let start = parse_naive_date(&start)?;
+    let end = parse_naive_date(&end)?;
+    run_blocking(&state, move |pool| {
+        period_summary(&mut pool.read()?, start, end)
+    })
+    .await
+}
+
+/// `get_period_summary` for the range and for the same number of days before
+/// it, with the percent change of each total.
+#[tauri::command]
+pub async fn get_period_comparison(
+    start: String,
+    end: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<PeriodComparison, String> {
+    let start = parse_naive_date(&start)?;
+    let end = parse_naive_date(&end)?;
+    run_blocking(&state, move |pool| {
+        compare_periods(&mut pool.read()?, start, end)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_change_is_relative_to_the_previous_period() {
+        assert_eq!(percent_change(4.0, 5.0), Some(25.0));
+        assert_eq!(percent_change(4.0, 2.0), Some(-50.0));
+        assert_eq!(percent_change(3.0, 3.0), Some(0.0));
+        assert_eq!(percent_change(2.0, 0.0), Some(-100.0));
+    }
+
+    #[test]
+    fn percent_change_from_nothing_is_none() {
+        assert_eq!(percent_change(0.0, 5.0), None);
+        assert_eq!(percent_change(0.0, 0.0), None);
+    }
+
+    #[test]
+    fn sets_sort_most_first() {
+        let mut sets = vec![2.0, 10.0, 5.0];
+        sets.sort_by(|a, b| by_sets_desc(*a, *b));
+        assert_eq!(sets, vec![10.0, 5.0, 2.0]);
+    }
+}
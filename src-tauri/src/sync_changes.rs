@@ -0,0 +1,122 @@
+//! What a sync brings in, read from the server response and the database
+//! rather than from the audit log, so follow-ups such as PB notifications
+//! still happen when logging fails.
+
+use serde_json::Value;
+
+use task_athlete_lib::AppService;
+
+use crate::audit::Scope;
+use crate::records;
+
+/// Ids of the records under `field` ("workouts", "exercises", ...) of a sync
+/// payload. `None` when they can't all be told, e.g. an entry without an id.
+pub fn payload_ids(payload: &Value, field: &str) -> Option<Vec<i64>> {
+    match payload.get(field)? {
+        Value::Null => Some(Vec::new()),
+        Value::Array(entries) => entries
+            .iter()
+            .map(|entry| entry.get("id").and_then(Value::as_i64))
+            .collect(),
+        _ => None,
+    }
+}
+
+/// The workouts and exercises a sync may add or change. Taken before the
+/// changes are applied; the id lists read the result afterwards.
+pub struct SyncChanges {
+    // Named by the response; `None` when it doesn't say.
+    named: Option<(Vec<i64>, Vec<String>)>,
+    highest_workout: Option<i64>,
+    highest_exercise: Option<i64>,
+}
+
+fn highest(service: &AppService, table: &str) -> Option<i64> {
+    records::max_id(service, table)
+        .map_err(|e| eprintln!("Failed to read the latest {} id: {}", table, e))
+        .ok()
+}
+
+fn added(service: &AppService, table: &str, highest: Option<i64>) -> Vec<i64> {
+    let Some(highest) = highest else {
+        return Vec::new();
+    };
+    records::ids_after(service, table, highest).unwrap_or_else(|e| {
+        eprintln!("Failed to read synced {}: {}", table, e);
+        Vec::new()
+    })
+}
+
+impl SyncChanges {
+    /// `payload` is the server's data, serialized.
+    pub fn before(service: &AppService, payload: &Value) -> Self {
+        let workouts = payload_ids(payload, "workouts");
+        let exercises = payload_ids(payload, "exercises");
+        SyncChanges {
+            named: workouts.zip(exercises).map(|(workouts, exercises)| {
+                (workouts, exercises.iter().map(i64::to_string).collect())
+            }),
+            highest_workout: highest(service, "workouts"),
+            highest_exercise: highest(service, "exercises"),
+        }
+    }
+
+    /// What the audit log needs to capture: the records the response names,
+    /// or all of them when it doesn't say which.
+    pub fn scopes(&self) -> Vec<Scope<'_>> {
+        match &self.named {
+            Some((workouts, exercises)) => vec![
+                Scope::Workouts(workouts),
+                Scope::Exercises(exercises),
+                // A renamed exercise rewrites its workouts.
+                Scope::WorkoutsOf(exercises),
+                Scope::Aliases,
+                Scope::Bodyweights,
+                Scope::Config,
+            ],
+            None => vec![Scope::All],
+        }
+    }
+
+    /// Workouts the response named plus any added since `before`, in case
+    /// the lib gave them ids of their own.
+    pub fn workout_ids(&self, service: &AppService) -> Vec<i64> {
+        let mut ids = self
+            .named
+            .as_ref()
+            .map(|(workouts, _)| workouts.clone())
+            .unwrap_or_default();
+        ids.extend(added(service, "workouts", self.highest_workout));
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Exercises added since `before`, by id.
+    pub fn added_exercises(&self, service: &AppService) -> Vec<String> {
+        added(service, "exercises", self.highest_exercise)
+            .iter()
+            .map(i64::to_string)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn ids_come_from_each_entry() {
+        let payload = json!({
+            "workouts": [{ "id": 4, "sets": 3 }, { "id": 9 }],
+            "aliases": [{ "alias": "bench" }],
+            "config": null,
+        });
+        assert_eq!(payload_ids(&payload, "workouts"), Some(vec![4, 9]));
+        assert_eq!(payload_ids(&payload, "aliases"), None);
+        assert_eq!(payload_ids(&payload, "config"), Some(Vec::new()));
+        assert_eq!(payload_ids(&payload, "bodyweights"), None);
+        assert_eq!(payload_ids(&Value::Null, "workouts"), None);
+    }
+}
@@ -0,0 +1,202 @@
+//! The user's time zone and conversions between stored UTC timestamps and
+//! local calendar days.
+//!
+//! Workouts are stored in UTC and the lib groups them by UTC date. Everything
+//! the GUI groups by day (date filters, calendar, streaks, graphs) goes
+//! through here instead, so a late-evening workout lands on the day it was
+//! logged.
+//!
+//! The zone is kept in `gui_settings.json`, not the lib's `Config`, which
+//! lives in the lib and can't take new fields from here. That file isn't
+//! synced, so each device keeps its own zone.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use task_athlete_lib::AppService;
+
+use crate::events;
+use crate::settings::GuiSettings;
+use crate::state::run_blocking;
+use crate::AppState;
+
+pub fn parse_time_zone(name: &str) -> Result<Tz, String> {
+    name.parse::<Tz>()
+        .map_err(|_| format!("Unknown time zone: {}", name))
+}
+
+/// The system's time zone, or UTC when it can't be detected.
+pub fn system_time_zone() -> Tz {
+    match iana_time_zone::get_timezone() {
+        Ok(name) => parse_time_zone(&name).unwrap_or_else(|e| {
+            eprintln!("{}; falling back to UTC", e);
+            Tz::UTC
+        }),
+        Err(e) => {
+            eprintln!("Failed to detect the system time zone: {}; using UTC", e);
+            Tz::UTC
+        }
+    }
+}
+
+/// The time zone set in the GUI settings, or the system's when none is.
+pub fn user_time_zone(service: &AppService) -> Tz {
+    let configured = match GuiSettings::load(service) {
+        Ok(settings) => settings.time_zone,
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    };
+    match configured.as_deref().map(parse_time_zone) {
+        Some(Ok(tz)) => tz,
+        Some(Err(e)) => {
+            eprintln!("{}; using the system time zone", e);
+            system_time_zone()
+        }
+        None => system_time_zone(),
+    }
+}
+
+/// The local calendar day `timestamp` falls on.
+pub fn local_date(tz: Tz, timestamp: DateTime<Utc>) -> NaiveDate {
+    timestamp.with_timezone(&tz).date_naive()
+}
+
+pub fn today(tz: Tz) -> NaiveDate {
+    local_date(tz, Utc::now())
+}
+
+/// Resolves a local wall-clock time. An ambiguous time (clocks going back)
+/// takes the earlier instant; a skipped one (clocks going forward) is moved
+/// past the gap.
+pub fn from_local(tz: Tz, naive: NaiveDateTime) -> DateTime<Utc> {
+    let resolved = tz.from_local_datetime(&naive).earliest().or_else(|| {
+        tz.from_local_datetime(&(naive + Duration::hours(1)))
+            .earliest()
+    });
+    match resolved {
+        Some(local) => local.with_timezone(&Utc),
+        None => Utc.from_utc_datetime(&naive),
+    }
+}
+
+/// Moves `timestamp` to `date`, keeping its local time of day.
+pub fn on_local_date(tz: Tz, timestamp: DateTime<Utc>, date: NaiveDate) -> DateTime<Utc> {
+    let time = timestamp.with_timezone(&tz).time();
+    from_local(tz, date.and_time(time))
+}
+
+// --- Tauri Commands ---
+
+/// Sets the time zone used for day boundaries; `None` follows the system.
+#[tauri::command]
+pub async fn set_time_zone(
+    time_zone: Option<String>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        if let Some(name) = &time_zone {
+            parse_time_zone(name)?;
+        }
+        let service = pool.write()?;
+        let mut settings = GuiSettings::load(&service)?;
+        settings.time_zone = time_zone;
+        settings.save(&service)?;
+        drop(service);
+        // Day buckets move with the zone, so every date-based view is stale.
+        events::config_changed(&app);
+        events::workouts_changed(&app, Vec::new(), Vec::new());
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn list_time_zones() -> Vec<&'static str> {
+    chrono_tz::TZ_VARIANTS.iter().map(|tz| tz.name()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn berlin() -> Tz {
+        parse_time_zone("Europe/Berlin").unwrap()
+    }
+
+    fn naive(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, mo, d)
+            .unwrap()
+            .and_hms_opt(h, mi, 0)
+            .unwrap()
+    }
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&naive(y, mo, d, h, mi))
+    }
+
+    #[test]
+    fn skipped_time_moves_past_the_gap() {
+        // Clocks jump from 02:00 to 03:00 on 2024-03-31; 02:30 is 03:30 CEST.
+        assert_eq!(
+            from_local(berlin(), naive(2024, 3, 31, 2, 30)),
+            utc(2024, 3, 31, 1, 30)
+        );
+    }
+
+    #[test]
+    fn repeated_time_takes_the_earlier_instant() {
+        // Clocks go back from 03:00 to 02:00 on 2024-10-27; 02:30 happens in
+        // CEST first.
+        assert_eq!(
+            from_local(berlin(), naive(2024, 10, 27, 2, 30)),
+            utc(2024, 10, 27, 0, 30)
+        );
+    }
+
+    #[test]
+    fn moving_across_a_change_keeps_the_local_time() {
+        let evening = utc(2024, 3, 30, 17, 0); // 18:00 CET
+        let moved = on_local_date(
+            berlin(),
+            evening,
+            NaiveDate::from_ymd_opt(2024, 4, 1).unwrap(),
+        );
+        assert_eq!(moved, utc(2024, 4, 1, 16, 0)); // 18:00 CEST
+    }
+
+    #[test]
+    fn moving_into_the_gap_lands_after_it() {
+        let night = utc(2024, 3, 30, 1, 30); // 02:30 CET
+        let moved = on_local_date(
+            berlin(),
+            night,
+            NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+        );
+        assert_eq!(moved, utc(2024, 3, 31, 1, 30));
+    }
+
+    #[test]
+    fn late_evening_west_of_utc_stays_on_its_day() {
+        let tz = parse_time_zone("America/Los_Angeles").unwrap();
+        let timestamp = from_local(tz, naive(2024, 5, 10, 23, 30));
+        assert_eq!(timestamp, utc(2024, 5, 11, 6, 30));
+        assert_eq!(
+            local_date(tz, timestamp),
+            NaiveDate::from_ymd_opt(2024, 5, 10).unwrap()
+        );
+    }
+
+    #[test]
+    fn early_morning_east_of_utc_stays_on_its_day() {
+        let tz = parse_time_zone("Asia/Tokyo").unwrap();
+        let timestamp = from_local(tz, naive(2024, 5, 10, 7, 30));
+        assert_eq!(timestamp, utc(2024, 5, 9, 22, 30));
+        assert_eq!(
+            local_date(tz, timestamp),
+            NaiveDate::from_ymd_opt(2024, 5, 10).unwrap()
+        );
+    }
+}
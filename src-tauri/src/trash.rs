@@ -0,0 +1,458 @@
+//! Trash bin for deleted workouts and exercises.
+//!
+//! Deleting still goes through the lib, so the deletion is recorded for sync
+//! and other devices drop the record as before. The record's row is kept
+//! verbatim in `trash.json`; restoring writes it back under its old id, with
+//! the lib's own columns intact, and edits it through the lib so the restore
+//! syncs as a change to the same record. Only when the id has been taken
+//! since (or for entries from before rows were kept) is the record re-created
+//! under a new id.
+//!
+//! So this is not a soft delete: the lib never sees a deleted-at flag, other
+//! devices drop the record at the next sync, and the copy in `trash.json`
+//! stays on this device. Its row is written back with plain SQL, which only
+//! works while the lib's `workouts` and `exercises` tables keep their columns.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use task_athlete_lib::AppService;
+
+use crate::audit::{Capture, Origin, Scope};
+use crate::events;
+use crate::exercise_details;
+use crate::records::{self, ExerciseSnapshot, StoredRow, WorkoutSnapshot};
+use crate::settings::{profile_file, GuiSettings};
+use crate::state::run_blocking;
+use crate::undo::{self, Operation};
+use crate::AppState;
+
+const TRASH_FILE_NAME: &str = "trash.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TrashedWorkout {
+    #[serde(flatten)]
+    pub snapshot: WorkoutSnapshot,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row: Option<StoredRow>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TrashedExercise {
+    #[serde(flatten)]
+    pub snapshot: ExerciseSnapshot,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row: Option<StoredRow>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TrashedRecords {
+    Workouts { workouts: Vec<TrashedWorkout> },
+    Exercises { exercises: Vec<TrashedExercise> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TrashEntry {
+    pub id: u64,
+    pub deleted_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub records: TrashedRecords,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct TrashBin {
+    next_id: u64,
+    entries: Vec<TrashEntry>,
+}
+
+/// What a restore put back: `(old id, new id)` per workout, the same id
+/// unless it had to be re-created, and the names of restored exercises.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct RestoredRecords {
+    pub workout_ids: Vec<(i64, i64)>,
+    pub exercise_names: Vec<String>,
+}
+
+impl TrashBin {
+    fn load(service: &AppService) -> Result<Self, String> {
+        let path = profile_file(service, TRASH_FILE_NAME);
+        if !path.exists() {
+            return Ok(TrashBin::default());
+        }
+        let raw =
+            fs::read_to_string(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        serde_json::from_str(&raw).map_err(|e| format!("Failed to parse {:?}: {}", path, e))
+    }
+
+    fn save(&self, service: &AppService) -> Result<(), String> {
+        let path = profile_file(service, TRASH_FILE_NAME);
+        let raw = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize trash: {}", e))?;
+        fs::write(&path, raw).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+
+    fn push(&mut self, records: TrashedRecords) -> u64 {
+        self.next_id += 1;
+        self.entries.push(TrashEntry {
+            id: self.next_id,
+            deleted_at: Utc::now(),
+            records,
+        });
+        self.next_id
+    }
+
+    fn take(&mut self, entry_id: u64) -> Result<TrashEntry, String> {
+        let index = self
+            .entries
+            .iter()
+            .position(|e| e.id == entry_id)
+            .ok_or_else(|| format!("Trash entry {} not found", entry_id))?;
+        Ok(self.entries.remove(index))
+    }
+
+    /// Drops the entries `discard` picks and returns them.
+    fn drop_entries(&mut self, discard: impl Fn(&TrashEntry) -> bool) -> Vec<TrashEntry> {
+        let (dropped, kept) = std::mem::take(&mut self.entries)
+            .into_iter()
+            .partition(discard);
+        self.entries = kept;
+        dropped
+    }
+
+    /// Drops entries older than the retention period and returns them.
+    fn purge_expired(&mut self, retention_days: u32) -> Vec<TrashEntry> {
+        if retention_days == 0 {
+            return Vec::new();
+        }
+        let cutoff = Utc::now() - Duration::days(i64::from(retention_days));
+        self.drop_entries(|e| e.deleted_at < cutoff)
+    }
+}
+
+/// Deletes the media files of exercises in entries that left the trash for
+/// good. They were kept so a restore could bring them back.
+fn delete_media(service: &AppService, entries: &[TrashEntry]) -> Result<(), String> {
+    let files: Vec<String> = entries
+        .iter()
+        .filter_map(|entry| match &entry.records {
+            TrashedRecords::Exercises { exercises } => Some(exercises),
+            TrashedRecords::Workouts { .. } => None,
+        })
+        .flatten()
+        .flat_map(|trashed| trashed.snapshot.details.media.iter().cloned())
+        .collect();
+    exercise_details::delete_unused_media(service, &files)
+}
+
+/// Loads the trash, purging expired entries on the way.
+fn load_purged(service: &AppService) -> Result<TrashBin, String> {
+    let mut bin = TrashBin::load(service)?;
+    let retention = GuiSettings::load(service)?.trash_retention_days;
+    let purged = bin.purge_expired(retention);
+    if !purged.is_empty() {
+        bin.save(service)?;
+        delete_media(service, &purged)?;
+    }
+    Ok(bin)
+}
+
+pub fn purge_expired(service: &AppService) -> Result<(), String> {
+    load_purged(service).map(|_| ())
+}
+
+/// Deletes the given workouts through the lib and keeps copies in the trash.
+/// Returns the ids actually deleted and the trash entry id.
+pub fn trash_workouts(service: &AppService, ids: &[i64]) -> Result<(Vec<i64>, u64), String> {
+    let trashed = records::find_workouts(service, ids)?
+        .iter()
+        .map(|workout| {
+            Ok(TrashedWorkout {
+                snapshot: WorkoutSnapshot::from(workout),
+                row: records::read_row(service, "workouts", workout.id)?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let mut bin = load_purged(service)?;
+    let deleted = service.delete_workouts(ids).map_err(|e| e.to_string())?;
+    let kept: Vec<TrashedWorkout> = trashed
+        .into_iter()
+        .filter(|t| deleted.contains(&t.snapshot.id))
+        .collect();
+    let entry_id = bin.push(TrashedRecords::Workouts { workouts: kept });
+    bin.save(service)?;
+    Ok((deleted, entry_id))
+}
+
+/// Deletes the given exercises through the lib and keeps copies (including
+/// their aliases) in the trash. Returns the lib's deleted count and the entry id.
+pub fn trash_exercises(
+    service: &mut AppService,
+    identifiers: &[String],
+) -> Result<(u64, u64), String> {
+    let mut trashed = Vec::with_capacity(identifiers.len());
+    let mut ids = Vec::with_capacity(identifiers.len());
+    for identifier in identifiers {
+        let definition = records::resolve_exercise(service, identifier)?;
+        let aliases = records::aliases_of(service, &definition.name)?;
+        let details = exercise_details::load(service, definition.id)?;
+        trashed.push(TrashedExercise {
+            snapshot: ExerciseSnapshot::new(&definition, aliases, details),
+            row: records::read_row(service, "exercises", definition.id)?,
+        });
+        ids.push(definition.id);
+    }
+    let mut bin = load_purged(service)?;
+    let deleted = service
+        .delete_exercise(identifiers)
+        .map_err(|e| e.to_string())?;
+    // The snapshots carry the details; a later exercise may reuse the id.
+    for id in ids {
+        exercise_details::forget(service, id)?;
+    }
+    let entry_id = bin.push(TrashedRecords::Exercises { exercises: trashed });
+    bin.save(service)?;
+    Ok((deleted, entry_id))
+}
+
+fn restore_workout(service: &mut AppService, trashed: &TrashedWorkout) -> Result<i64, String> {
+    let snapshot = &trashed.snapshot;
+    let row = match &trashed.row {
+        // A newer workout may have been given the id since.
+        Some(row) if records::find_workouts(service, &[snapshot.id])?.is_empty() => row,
+        _ => return records::readd_workout(service, snapshot),
+    };
+    records::write_row(service, "workouts", row)?;
+    // Editing it through the lib marks the row for sync.
+    if let Err(e) = records::overwrite_workout(service, snapshot.id, snapshot) {
+        let _ = service.delete_workouts(&[snapshot.id]);
+        return Err(e);
+    }
+    Ok(snapshot.id)
+}
+
+fn restore_exercise(service: &AppService, trashed: &TrashedExercise) -> Result<(), String> {
+    let stored = trashed
+        .row
+        .as_ref()
+        .and_then(|row| Some((row, records::row_id(row)?)));
+    if let Some((row, id)) = stored {
+        // A newer exercise may have been given the id since.
+        let id_taken = service
+            .list_exercises(None, None)
+            .map_err(|e| e.to_string())?
+            .iter()
+            .any(|def| def.id == id);
+        if !id_taken {
+            return records::restore_exercise(service, &trashed.snapshot, row).map(|_| ());
+        }
+    }
+    records::recreate_exercise(service, &trashed.snapshot).map(|_| ())
+}
+
+// Handles items front to back, removing each once `f` succeeds, so on error
+// `items` holds exactly the ones still to do.
+fn restore_each<T>(
+    items: &mut Vec<T>,
+    mut f: impl FnMut(&T) -> Result<(), String>,
+) -> Result<(), String> {
+    while let Some(item) = items.first() {
+        f(item)?;
+        items.remove(0);
+    }
+    Ok(())
+}
+
+/// Puts back everything in a trash entry and removes the entry. If a record
+/// fails, the ones not yet restored stay in the trash.
+pub fn restore(service: &mut AppService, entry_id: u64) -> Result<RestoredRecords, String> {
+    let mut bin = load_purged(service)?;
+    let mut entry = bin.take(entry_id)?;
+    let mut restored = RestoredRecords::default();
+    let outcome = match &mut entry.records {
+        TrashedRecords::Workouts { workouts } => restore_each(workouts, |trashed| {
+            let new_id = restore_workout(service, trashed)?;
+            restored.workout_ids.push((trashed.snapshot.id, new_id));
+            Ok(())
+        }),
+        TrashedRecords::Exercises { exercises } => restore_each(exercises, |trashed| {
+            restore_exercise(service, trashed)?;
+            restored.exercise_names.push(trashed.snapshot.name.clone());
+            Ok(())
+        }),
+    };
+    if let Err(e) = outcome {
+        bin.entries.push(entry);
+        bin.save(service)?;
+        return Err(e);
+    }
+    bin.save(service)?;
+    Ok(restored)
+}
+
+pub fn emit_restored(app: &tauri::AppHandle, restored: &RestoredRecords) {
+    if !restored.workout_ids.is_empty() {
+        let ids = restored.workout_ids.iter().map(|(_, new)| *new).collect();
+        events::workouts_changed(app, ids, Vec::new());
+    }
+    if !restored.exercise_names.is_empty() {
+        events::exercises_changed(app);
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub async fn list_trash(state: tauri::State<'_, AppState>) -> Result<Vec<TrashEntry>, String> {
+    run_blocking(&state, move |pool| {
+        let service = pool.write()?;
+        let mut entries = load_purged(&service)?.entries;
+        entries.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+        Ok(entries)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn restore_from_trash(
+    entry_ids: Vec<u64>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<RestoredRecords, String> {
+    run_blocking(&state, move |pool| {
+        let mut history = undo::lock(&app)?;
+        let mut service = pool.write()?;
+        let mut capture = Capture::take(&mut service, &[Scope::Aliases]);
+        let mut all = RestoredRecords::default();
+        for entry_id in entry_ids {
+            let restored = match restore(&mut service, entry_id) {
+                Ok(restored) => restored,
+                Err(e) => {
+                    // Entries restored before the failure stay restored.
+                    capture.commit(&mut service, Origin::Local);
+                    emit_restored(&app, &all);
+                    return Err(e);
+                }
+            };
+            let ids: Vec<i64> = restored.workout_ids.iter().map(|&(_, new)| new).collect();
+            capture.include_workouts(&ids);
+            capture.include_exercises(&restored.exercise_names);
+            history.remap_workouts(&restored.workout_ids);
+            history.record(Operation::Restored(restored.clone()));
+            all.workout_ids.extend(restored.workout_ids);
+            all.exercise_names.extend(restored.exercise_names);
+        }
+        capture.commit(&mut service, Origin::Local);
+        emit_restored(&app, &all);
+        Ok(all)
+    })
+    .await
+}
+
+/// Permanently removes the given entries, or everything when `entry_ids` is
+/// omitted. Returns the number of entries removed.
+#[tauri::command]
+pub async fn empty_trash(
+    entry_ids: Option<Vec<u64>>,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, String> {
+    run_blocking(&state, move |pool| {
+        let service = pool.write()?;
+        let mut bin = load_purged(&service)?;
+        let emptied = match entry_ids {
+            Some(ids) => bin.drop_entries(|e| ids.contains(&e.id)),
+            None => bin.drop_entries(|_| true),
+        };
+        bin.save(&service)?;
+        delete_media(&service, &emptied)?;
+        Ok(emptied.len())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_trash_retention_days(
+    days: u32,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    run_blocking(&state, move |pool| {
+        let service = pool.write()?;
+        let mut settings = GuiSettings::load(&service)?;
+        settings.trash_retention_days = days;
+        settings.save(&service)?;
+        purge_expired(&service)?;
+        drop(service);
+        events::config_changed(&app);
+        Ok(())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u64, days_ago: i64) -> TrashEntry {
+        TrashEntry {
+            id,
+            deleted_at: Utc::now() - Duration::days(days_ago),
+            records: TrashedRecords::Workouts {
+                workouts: Vec::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn purge_drops_only_expired_entries() {
+        let mut bin = TrashBin {
+            next_id: 3,
+            entries: vec![entry(1, 40), entry(2, 29), entry(3, 0)],
+        };
+        assert_eq!(bin.purge_expired(30).len(), 1);
+        let ids: Vec<u64> = bin.entries.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+    }
+
+    #[test]
+    fn zero_retention_keeps_everything() {
+        let mut bin = TrashBin {
+            next_id: 1,
+            entries: vec![entry(1, 10_000)],
+        };
+        assert!(bin.purge_expired(0).is_empty());
+        assert_eq!(bin.entries.len(), 1);
+    }
+
+    #[test]
+    fn entries_without_rows_still_load() {
+        let raw = r#"{
+            "next_id": 1,
+            "entries": [{
+                "id": 1,
+                "deleted_at": "2024-03-01T10:00:00Z",
+                "kind": "workouts",
+                "workouts": [{
+                    "id": 7,
+                    "timestamp": "2024-03-01T09:00:00Z",
+                    "exercise_name": "Squat",
+                    "exercise_type": null,
+                    "sets": 3,
+                    "reps": 5,
+                    "weight": 100.0,
+                    "duration_minutes": null,
+                    "distance": null,
+                    "bodyweight": null,
+                    "notes": null
+                }]
+            }]
+        }"#;
+        let bin: TrashBin = serde_json::from_str(raw).unwrap();
+        let TrashedRecords::Workouts { workouts } = &bin.entries[0].records else {
+            panic!("expected workouts");
+        };
+        assert_eq!(workouts[0].snapshot.id, 7);
+        assert!(workouts[0].row.is_none());
+    }
+}
@@ -0,0 +1,357 @@
+//! Smoothing, weekly resampling and regression lines computed from a graph's
+//! raw points, so the frontend doesn't have to.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::graphs::Period;
+use crate::state::run_blocking;
+use crate::{graph_data, parse_naive_date, AppState, GetDataForGraphPayload};
+
+const DEFAULT_WINDOW: usize = 7;
+
+type Points = Vec<(NaiveDate, f64)>;
+
+#[derive(Deserialize, Default)]
+pub struct TrendOptions {
+    /// "sma" (simple) or "ema" (exponential).
+    pub moving_average: Option<String>,
+    /// Points averaged by the SMA, or the EMA's span (smoothing factor
+    /// 2 / (window + 1)). Defaults to 7.
+    pub window: Option<usize>,
+    /// "max" or "mean": one point per week, dated by its Monday.
+    pub weekly: Option<String>,
+    /// "linear" or "log".
+    pub regression: Option<String>,
+    /// Local date to project the regression line to.
+    pub project_to: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct TrendGraphPayload {
+    #[serde(flatten)]
+    pub graph: GetDataForGraphPayload,
+    #[serde(flatten)]
+    pub options: TrendOptions,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RegressionKind {
+    /// value = intercept + slope × days
+    Linear,
+    /// value = intercept + slope × ln(days + 1), for progress that levels off.
+    Log,
+}
+
+#[derive(Serialize)]
+pub struct Regression {
+    pub kind: RegressionKind,
+    /// Change per week: constant for a linear fit, at the last point for a
+    /// log fit.
+    pub slope_per_week: f64,
+    pub r_squared: f64,
+    /// The fitted value on each raw point's date.
+    pub line: Points,
+    pub projection: Option<(NaiveDate, f64)>,
+}
+
+#[derive(Serialize)]
+pub struct TrendGraph {
+    pub raw: Points,
+    pub moving_average: Option<Points>,
+    pub weekly: Option<Points>,
+    /// `None` when asked for but there are fewer than two distinct days.
+    pub regression: Option<Regression>,
+}
+
+fn simple_moving_average(points: &[(NaiveDate, f64)], window: usize) -> Points {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, (day, _))| {
+            let from = (i + 1).saturating_sub(window);
+            let slice = &points[from..=i];
+            let mean = slice.iter().map(|(_, v)| v).sum::<f64>() / slice.len() as f64;
+            (*day, mean)
+        })
+        .collect()
+}
+
+fn exponential_moving_average(points: &[(NaiveDate, f64)], window: usize) -> Points {
+    let alpha = 2.0 / (window as f64 + 1.0);
+    let mut average = None;
+    points
+        .iter()
+        .map(|&(day, value)| {
+            let next = match average {
+                Some(previous) => alpha * value + (1.0 - alpha) * previous,
+                None => value,
+            };
+            average = Some(next);
+            (day, next)
+        })
+        .collect()
+}
+
+fn resample_weekly(points: &[(NaiveDate, f64)], max: bool) -> Points {
+    let mut weeks: BTreeMap<NaiveDate, Vec<f64>> = BTreeMap::new();
+    for &(day, value) in points {
+        weeks
+            .entry(Period::Week.start_of(day))
+            .or_default()
+            .push(value);
+    }
+    weeks
+        .into_iter()
+        .map(|(week, values)| {
+            let value = if max {
+                values.iter().copied().fold(f64::MIN, f64::max)
+            } else {
+                values.iter().sum::<f64>() / values.len() as f64
+            };
+            (week, value)
+        })
+        .collect()
+}
+
+fn fit_regression(
+    points: &[(NaiveDate, f64)],
+    kind: RegressionKind,
+    project_to: Option<NaiveDate>,
+) -> Option<Regression> {
+    let first = points.first()?.0;
+    let x_of = |day: NaiveDate| {
+        let days = (day - first).num_days() as f64;
+        match kind {
+            RegressionKind::Linear => days,
+            RegressionKind::Log => (days.max(0.0) + 1.0).ln(),
+        }
+    };
+    let n = points.len() as f64;
+    let xs: Vec<f64> = points.iter().map(|(day, _)| x_of(*day)).collect();
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let sxx: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+    if sxx == 0.0 {
+        return None;
+    }
+    let sxy: f64 = xs
+        .iter()
+        .zip(points)
+        .map(|(x, (_, y))| (x - mean_x) * (y - mean_y))
+        .sum();
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+    let fit = |day: NaiveDate| intercept + slope * x_of(day);
+
+    let ss_total: f64 = points.iter().map(|(_, y)| (y - mean_y).powi(2)).sum();
+    let ss_residual: f64 = points.iter().map(|(day, y)| (y - fit(*day)).powi(2)).sum();
+    let r_squared = if ss_total == 0.0 {
+        1.0
+    } else {
+        1.0 - ss_residual / ss_total
+    };
+    let slope_per_day = match kind {
+        RegressionKind::Linear => slope,
+        // d/dx of slope × ln(days + 1) is slope / (days + 1).
+        RegressionKind::Log => {
+            let last_days = (points.last()?.0 - first).num_days() as f64;
+            slope / (last_days + 1.0)
+        }
+    };
+    Some(Regression {
+        kind,
+        slope_per_week: slope_per_day * 7.0,
+        r_squared,
+        line: points.iter().map(|(day, _)| (*day, fit(*day))).collect(),
+        projection: project_to.map(|day| (day, fit(day))),
+    })
+}
+
+pub fn trends(raw: Points, options: &TrendOptions) -> Result<TrendGraph, String> {
+    let window = options.window.unwrap_or(DEFAULT_WINDOW).max(1);
+    let moving_average = match options.moving_average.as_deref() {
+        None => None,
+        Some("sma") => Some(simple_moving_average(&raw, window)),
+        Some("ema") => Some(exponential_moving_average(&raw, window)),
+        Some(other) => {
+            return Err(format!(
+                "Invalid moving average '{}'; use sma or ema",
+                other
+            ))
+        }
+    };
+    let weekly = match options.weekly.as_deref() {
+        None => None,
+        Some("max") => Some(resample_weekly(&raw, true)),
+        Some("mean") => Some(resample_weekly(&raw, false)),
+        Some(other) => {
+            return Err(format!(
+                "Invalid weekly resampling '{}'; use max or mean",
+                other
+            ))
+        }
+    };
+    let kind = match options.regression.as_deref() {
+        None => None,
+        Some("linear") => Some(RegressionKind::Linear),
+        Some("log") => Some(RegressionKind::Log),
+        Some(other) => return Err(format!("Invalid regression '{}'; use linear or log", other)),
+    };
+    let project_to = options
+        .project_to
+        .as_deref()
+        .map(parse_naive_date)
+        .transpose()?;
+    let regression = kind.and_then(|kind| fit_regression(&raw, kind, project_to));
+    Ok(TrendGraph {
+        raw,
+        moving_average,
+        weekly,
+        regression,
+    })
+}
+
+// --- Tauri Commands ---
+
+/// `get_data_for_graph` plus the requested moving average, weekly resampling
+/// and regression, each computed from the raw points.
+#[tauri::command]
+pub async fn get_graph_trends(
+    payload: TrendGraphPayload,
+    state: tauri::State<'_, AppState>,
+) -> Result<TrendGraph, String> {
+    run_blocking(&state, move |pool| {
+        let raw = graph_data(&pool.read()?, payload.graph)?;
+        trends(raw, &payload.options)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        // May 6th 2024 is a Monday.
+        NaiveDate::from_ymd_opt(2024, 5, d).unwrap()
+    }
+
+    fn values(points: &[(NaiveDate, f64)]) -> Vec<f64> {
+        points.iter().map(|(_, v)| *v).collect()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn sma_averages_up_to_the_window() {
+        let points = vec![
+            (day(6), 10.0),
+            (day(7), 20.0),
+            (day(8), 30.0),
+            (day(9), 40.0),
+        ];
+        let sma = simple_moving_average(&points, 2);
+        assert_eq!(values(&sma), vec![10.0, 15.0, 25.0, 35.0]);
+        assert_eq!(sma[3].0, day(9));
+        // A window larger than the series averages everything so far.
+        assert_eq!(
+            values(&simple_moving_average(&points, 10)),
+            vec![10.0, 15.0, 20.0, 25.0]
+        );
+    }
+
+    #[test]
+    fn ema_starts_at_the_first_value() {
+        let points = vec![(day(6), 10.0), (day(7), 20.0), (day(8), 20.0)];
+        // Window 3: alpha = 0.5.
+        let ema = exponential_moving_average(&points, 3);
+        assert_eq!(values(&ema), vec![10.0, 15.0, 17.5]);
+        assert_eq!(
+            values(&exponential_moving_average(&points[..1], 30)),
+            vec![10.0]
+        );
+    }
+
+    #[test]
+    fn weekly_resampling_dates_weeks_by_monday() {
+        // The 12th is the Sunday ending the first week.
+        let points = vec![
+            (day(7), 50.0),
+            (day(9), 70.0),
+            (day(12), 60.0),
+            (day(14), 80.0),
+        ];
+        let max = resample_weekly(&points, true);
+        assert_eq!(max, vec![(day(6), 70.0), (day(13), 80.0)]);
+        let mean = resample_weekly(&points, false);
+        assert_eq!(mean, vec![(day(6), 60.0), (day(13), 80.0)]);
+    }
+
+    #[test]
+    fn linear_regression_fits_a_straight_line() {
+        let points = vec![(day(6), 100.0), (day(8), 102.0), (day(10), 104.0)];
+        let fit = fit_regression(&points, RegressionKind::Linear, Some(day(20))).unwrap();
+        assert!(close(fit.slope_per_week, 7.0));
+        assert!(close(fit.r_squared, 1.0));
+        assert!(close(fit.line[1].1, 102.0));
+        let (projected_day, projected) = fit.projection.unwrap();
+        assert_eq!(projected_day, day(20));
+        assert!(close(projected, 114.0));
+    }
+
+    #[test]
+    fn log_regression_flattens_out() {
+        // value = 10 + 5 × ln(days + 1)
+        let points: Points = [0, 3, 9]
+            .into_iter()
+            .map(|days: u32| {
+                let value = 10.0 + 5.0 * (f64::from(days) + 1.0).ln();
+                (day(6 + days), value)
+            })
+            .collect();
+        let fit = fit_regression(&points, RegressionKind::Log, None).unwrap();
+        assert!(close(fit.r_squared, 1.0));
+        assert!(close(fit.line[0].1, 10.0));
+        // The slope at the last point, 9 days in.
+        assert!(close(fit.slope_per_week, 5.0 / 10.0 * 7.0));
+        assert!(fit.projection.is_none());
+    }
+
+    #[test]
+    fn regression_needs_two_days() {
+        assert!(fit_regression(&[], RegressionKind::Linear, None).is_none());
+        let single = [(day(6), 100.0)];
+        assert!(fit_regression(&single, RegressionKind::Linear, None).is_none());
+        let same_day = [(day(6), 100.0), (day(6), 110.0)];
+        assert!(fit_regression(&same_day, RegressionKind::Log, None).is_none());
+    }
+
+    #[test]
+    fn a_single_point_passes_through() {
+        let options = TrendOptions {
+            moving_average: Some("sma".to_string()),
+            window: Some(30),
+            weekly: Some("max".to_string()),
+            regression: Some("linear".to_string()),
+            project_to: None,
+        };
+        let graph = trends(vec![(day(8), 42.0)], &options).unwrap();
+        assert_eq!(graph.moving_average, Some(vec![(day(8), 42.0)]));
+        assert_eq!(graph.weekly, Some(vec![(day(6), 42.0)]));
+        assert!(graph.regression.is_none());
+    }
+
+    #[test]
+    fn unknown_options_are_rejected() {
+        let options = TrendOptions {
+            moving_average: Some("wma".to_string()),
+            ..TrendOptions::default()
+        };
+        assert!(trends(Vec::new(), &options).is_err());
+    }
+}
@@ -0,0 +1,417 @@
+//! Session-level undo/redo for workout and exercise changes.
+//!
+//! Each recorded `Operation` knows how to revert itself and returns the
+//! operation that reverts the revert, which goes on the opposite stack.
+//! Re-created workouts get new ids, so the history keeps an old -> new id map
+//! and resolves ids through it before touching the database.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use tauri::Manager;
+
+use task_athlete_lib::AppService;
+
+use crate::audit::{Capture, Origin, Scope};
+use crate::events;
+use crate::records::{self, ExerciseSnapshot, WorkoutSnapshot};
+use crate::state::run_blocking;
+use crate::trash::{self, RestoredRecords};
+use crate::AppState;
+
+const MAX_HISTORY: usize = 50;
+
+// Lock before AppState when both are needed.
+pub type UndoState = Mutex<UndoHistory>;
+
+/// The undo history, for commands running on the blocking pool.
+pub fn lock(app: &tauri::AppHandle) -> Result<MutexGuard<'_, UndoHistory>, String> {
+    app.state::<UndoState>()
+        .inner()
+        .lock()
+        .map_err(|e| format!("Failed to lock undo history: {}", e))
+}
+
+#[derive(Clone, Debug)]
+pub enum Operation {
+    AddedWorkout {
+        id: i64,
+        snapshot: WorkoutSnapshot,
+    },
+    // Removed outright by an undo; redo adds it back.
+    RemovedWorkout {
+        snapshot: WorkoutSnapshot,
+    },
+    EditedWorkout {
+        id: i64,
+        before: WorkoutSnapshot,
+        after: WorkoutSnapshot,
+    },
+    CreatedExercise {
+        name: String,
+    },
+    // Exercise ids can change through the trash, so edits go by name.
+    EditedExercise {
+        before: ExerciseSnapshot,
+        after: ExerciseSnapshot,
+    },
+    TrashedWorkouts {
+        entry_id: u64,
+    },
+    TrashedExercises {
+        entry_id: u64,
+    },
+    Restored(RestoredRecords),
+    AddedAlias {
+        alias: String,
+        target: String,
+    },
+    RemovedAlias {
+        alias: String,
+        target: String,
+    },
+    // Several operations undone and redone as one step.
+    Batch {
+        label: String,
+        ops: Vec<Operation>,
+    },
+}
+
+impl Operation {
+    fn label(&self) -> String {
+        match self {
+            Operation::AddedWorkout { snapshot, .. } => {
+                format!("Add {}", snapshot.exercise_name)
+            }
+            Operation::RemovedWorkout { snapshot } => {
+                format!("Remove {}", snapshot.exercise_name)
+            }
+            Operation::EditedWorkout { after, .. } => format!("Edit {}", after.exercise_name),
+            Operation::CreatedExercise { name } => format!("Create {}", name),
+            Operation::EditedExercise { after, .. } => format!("Edit {}", after.name),
+            Operation::TrashedWorkouts { .. } => "Delete workouts".to_string(),
+            Operation::TrashedExercises { .. } => "Delete exercises".to_string(),
+            Operation::Restored(_) => "Restore from trash".to_string(),
+            Operation::AddedAlias { alias, .. } => format!("Add alias {}", alias),
+            Operation::RemovedAlias { alias, .. } => format!("Remove alias {}", alias),
+            Operation::Batch { label, .. } => label.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct UndoHistory {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>,
+    id_map: HashMap<i64, i64>,
+}
+
+impl UndoHistory {
+    /// Records a fresh operation. Like any editor, a new change drops the redo stack.
+    pub fn record(&mut self, op: Operation) {
+        self.redo.clear();
+        self.push_undo(op);
+    }
+
+    fn push_undo(&mut self, op: Operation) {
+        self.undo.push(op);
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.id_map.clear();
+    }
+
+    /// Reverts the operations of a change that failed partway, last first,
+    /// without recording anything.
+    pub fn roll_back(
+        &mut self,
+        service: &mut AppService,
+        ops: Vec<Operation>,
+    ) -> Result<(), String> {
+        self.revert(
+            service,
+            Operation::Batch {
+                label: String::new(),
+                ops,
+            },
+        )
+        .map(|_| ())
+    }
+
+    /// Notes that workouts were re-created under new ids.
+    pub fn remap_workouts(&mut self, pairs: &[(i64, i64)]) {
+        for &(old, new) in pairs {
+            self.id_map.insert(old, new);
+        }
+    }
+
+    /// Adds the workouts and exercises `op` refers to, under their current ids.
+    fn touched(&self, op: &Operation, touched: &mut Touched) {
+        match op {
+            Operation::AddedWorkout { id, snapshot } => {
+                touched.workouts.push(self.resolve(*id));
+                touched.exercises.push(snapshot.exercise_name.clone());
+            }
+            Operation::RemovedWorkout { snapshot } => {
+                touched.exercises.push(snapshot.exercise_name.clone());
+            }
+            Operation::EditedWorkout { id, before, after } => {
+                touched.workouts.push(self.resolve(*id));
+                touched.exercises.push(before.exercise_name.clone());
+                touched.exercises.push(after.exercise_name.clone());
+            }
+            Operation::CreatedExercise { name } => touched.exercises.push(name.clone()),
+            Operation::EditedExercise { before, after } => {
+                touched.exercises.push(before.name.clone());
+                touched.exercises.push(after.name.clone());
+            }
+            // The trash entry says what comes back; the inverse lists it.
+            Operation::TrashedWorkouts { .. } | Operation::TrashedExercises { .. } => {}
+            Operation::Restored(restored) => {
+                touched.workouts.extend(
+                    restored
+                        .workout_ids
+                        .iter()
+                        .map(|&(_, new)| self.resolve(new)),
+                );
+                touched
+                    .exercises
+                    .extend(restored.exercise_names.iter().cloned());
+            }
+            Operation::AddedAlias { .. } | Operation::RemovedAlias { .. } => {}
+            Operation::Batch { ops, .. } => {
+                for op in ops {
+                    self.touched(op, touched);
+                }
+            }
+        }
+    }
+
+    /// Reverts `op`, logging the records it changed.
+    fn revert_logged(
+        &mut self,
+        service: &mut AppService,
+        op: Operation,
+    ) -> Result<Operation, String> {
+        let mut before = Touched::default();
+        self.touched(&op, &mut before);
+        let mut capture = Capture::take(
+            service,
+            &[
+                Scope::Workouts(&before.workouts),
+                Scope::Exercises(&before.exercises),
+                Scope::WorkoutsOf(&before.exercises),
+                Scope::Aliases,
+            ],
+        );
+        let reverted = self.revert(service, op);
+        if let Ok(inverse) = &reverted {
+            let mut after = Touched::default();
+            self.touched(inverse, &mut after);
+            capture.include_workouts(&after.workouts);
+            capture.include_exercises(&after.exercises);
+        }
+        capture.commit(service, Origin::Local);
+        reverted
+    }
+
+    fn resolve(&self, mut id: i64) -> i64 {
+        // Chains form when a record is re-created more than once.
+        let mut hops = 0;
+        while let Some(&next) = self.id_map.get(&id) {
+            id = next;
+            hops += 1;
+            if hops > MAX_HISTORY * 2 {
+                break;
+            }
+        }
+        id
+    }
+
+    /// Reverts `op` and returns the operation that would revert it again.
+    fn revert(&mut self, service: &mut AppService, op: Operation) -> Result<Operation, String> {
+        match op {
+            Operation::AddedWorkout { id, snapshot } => {
+                service
+                    .delete_workouts(&[self.resolve(id)])
+                    .map_err(|e| e.to_string())?;
+                Ok(Operation::RemovedWorkout { snapshot })
+            }
+            Operation::RemovedWorkout { snapshot } => {
+                let new_id = records::readd_workout(service, &snapshot)?;
+                self.remap_workouts(&[(snapshot.id, new_id)]);
+                Ok(Operation::AddedWorkout {
+                    id: new_id,
+                    snapshot,
+                })
+            }
+            Operation::EditedWorkout { id, before, after } => {
+                let id = self.resolve(id);
+                records::overwrite_workout(service, id, &before)?;
+                Ok(Operation::EditedWorkout {
+                    id,
+                    before: after,
+                    after: before,
+                })
+            }
+            // Trashed rather than deleted, in case workouts were logged since.
+            Operation::CreatedExercise { name } => {
+                let (_, entry_id) = trash::trash_exercises(service, &[name])?;
+                Ok(Operation::TrashedExercises { entry_id })
+            }
+            Operation::EditedExercise { before, after } => {
+                records::overwrite_exercise(service, &after.name, &before)?;
+                Ok(Operation::EditedExercise {
+                    before: after,
+                    after: before,
+                })
+            }
+            Operation::TrashedWorkouts { entry_id } | Operation::TrashedExercises { entry_id } => {
+                let restored = trash::restore(service, entry_id)?;
+                self.remap_workouts(&restored.workout_ids);
+                Ok(Operation::Restored(restored))
+            }
+            Operation::Restored(restored) => {
+                if !restored.workout_ids.is_empty() {
+                    let ids: Vec<i64> = restored
+                        .workout_ids
+                        .iter()
+                        .map(|&(_, new)| self.resolve(new))
+                        .collect();
+                    let (_, entry_id) = trash::trash_workouts(service, &ids)?;
+                    Ok(Operation::TrashedWorkouts { entry_id })
+                } else {
+                    let (_, entry_id) = trash::trash_exercises(service, &restored.exercise_names)?;
+                    Ok(Operation::TrashedExercises { entry_id })
+                }
+            }
+            Operation::AddedAlias { alias, target } => {
+                service.delete_alias(&alias).map_err(|e| e.to_string())?;
+                Ok(Operation::RemovedAlias { alias, target })
+            }
+            Operation::RemovedAlias { alias, target } => {
+                service
+                    .create_alias(&alias, &target)
+                    .map_err(|e| e.to_string())?;
+                Ok(Operation::AddedAlias { alias, target })
+            }
+            Operation::Batch { label, ops } => {
+                // Reverted last to first, so the inverse batch is already in
+                // the order it must be applied.
+                let mut inverse = Vec::with_capacity(ops.len());
+                for op in ops.into_iter().rev() {
+                    match self.revert(service, op) {
+                        Ok(op) => inverse.push(op),
+                        Err(e) => {
+                            // Redo what was already reverted so the batch
+                            // stays whole and can be tried again.
+                            for op in inverse.into_iter().rev() {
+                                if let Err(e) = self.revert(service, op) {
+                                    eprintln!("Failed to put back a partly undone change: {}", e);
+                                }
+                            }
+                            return Err(e);
+                        }
+                    }
+                }
+                Ok(Operation::Batch {
+                    label,
+                    ops: inverse,
+                })
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Touched {
+    workouts: Vec<i64>,
+    exercises: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct UndoStatus {
+    can_undo: bool,
+    can_redo: bool,
+    undo_label: Option<String>,
+    redo_label: Option<String>,
+}
+
+impl From<&UndoHistory> for UndoStatus {
+    fn from(history: &UndoHistory) -> Self {
+        UndoStatus {
+            can_undo: !history.undo.is_empty(),
+            can_redo: !history.redo.is_empty(),
+            undo_label: history.undo.last().map(Operation::label),
+            redo_label: history.redo.last().map(Operation::label),
+        }
+    }
+}
+
+// Undo/redo can touch workouts and exercises alike, so refresh both.
+fn emit_reverted(app: &tauri::AppHandle) {
+    events::workouts_changed(app, Vec::new(), Vec::new());
+    events::exercises_changed(app);
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_undo_status(undo: tauri::State<'_, UndoState>) -> Result<UndoStatus, String> {
+    let history = undo
+        .lock()
+        .map_err(|e| format!("Failed to lock undo history: {}", e))?;
+    Ok(UndoStatus::from(&*history))
+}
+
+#[tauri::command]
+pub async fn undo(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<UndoStatus, String> {
+    run_blocking(&state, move |pool| {
+        let mut history = lock(&app)?;
+        let op = history
+            .undo
+            .last()
+            .cloned()
+            .ok_or_else(|| "Nothing to undo".to_string())?;
+        let mut service = pool.write()?;
+        // Only taken off the stack once reverted, so a failure can be retried.
+        let inverse = history.revert_logged(&mut service, op)?;
+        history.undo.pop();
+        history.redo.push(inverse);
+        drop(service);
+        emit_reverted(&app);
+        Ok(UndoStatus::from(&*history))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn redo(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<UndoStatus, String> {
+    run_blocking(&state, move |pool| {
+        let mut history = lock(&app)?;
+        let op = history
+            .redo
+            .last()
+            .cloned()
+            .ok_or_else(|| "Nothing to redo".to_string())?;
+        let mut service = pool.write()?;
+        let inverse = history.revert_logged(&mut service, op)?;
+        history.redo.pop();
+        history.push_undo(inverse);
+        drop(service);
+        emit_reverted(&app);
+        Ok(UndoStatus::from(&*history))
+    })
+    .await
+}
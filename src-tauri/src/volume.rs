@@ -0,0 +1,318 @@
+//! Training volume per muscle, bucketed by day, week or month, for planning by
+//! "sets per muscle per week".
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use task_athlete_lib::{AppService, Workout};
+
+use crate::exercise_details;
+use crate::graphs::Period;
+use crate::muscles::{self, MuscleTargets};
+use crate::settings::GuiSettings;
+use crate::state::run_blocking;
+use crate::timezone;
+use crate::{list_workouts_in, parse_naive_date, AppState, WorkoutFiltersCmdParams};
+
+#[derive(Deserialize, Default)]
+pub struct MuscleVolumeQuery {
+    /// Local dates, both inclusive. Default to the first workout and today.
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    /// "day", "week" (the default) or "month".
+    pub period: Option<String>,
+    /// Muscles or groups to report; every muscle when empty.
+    pub muscles: Option<Vec<String>>,
+}
+
+/// A muscle's share of the work. Every set counts as a working set, since
+/// the lib doesn't mark warm-ups. Secondary muscles get
+/// `secondary_muscle_weight` of each set, so the numbers can be fractional.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct MuscleVolume {
+    pub muscle: String,
+    /// The muscle's group in the taxonomy; `None` for custom muscles.
+    pub group: Option<&'static str>,
+    pub sets: f64,
+    pub reps: f64,
+    /// Sets × reps × weight, in the units weights are logged in.
+    pub tonnage: f64,
+}
+
+impl MuscleVolume {
+    fn add(&mut self, workout: &Workout, share: f64) {
+        let sets = workout.sets.unwrap_or(1) as f64;
+        let reps = sets * workout.reps.unwrap_or(0) as f64;
+        self.sets += sets * share;
+        self.reps += reps * share;
+        self.tonnage += reps * workout.weight.unwrap_or(0.0) * share;
+    }
+}
+
+#[derive(Serialize)]
+pub struct VolumeBucket {
+    pub start: NaiveDate,
+    /// Last day of the bucket, inclusive.
+    pub end: NaiveDate,
+    /// Only muscles worked in the bucket, by name.
+    pub muscles: Vec<MuscleVolume>,
+}
+
+#[derive(Serialize)]
+pub struct MuscleVolumeReport {
+    pub period: Period,
+    pub secondary_muscle_weight: f64,
+    /// Every bucket in the range, empty ones included, oldest first.
+    pub buckets: Vec<VolumeBucket>,
+    /// The whole range per muscle, e.g. for a body heatmap.
+    pub totals: Vec<MuscleVolume>,
+}
+
+type Volumes = BTreeMap<String, MuscleVolume>;
+
+fn add_workout(volumes: &mut Volumes, workout: &Workout, weighted: &[(&str, f64)]) {
+    for &(muscle, share) in weighted {
+        volumes
+            .entry(muscle.to_lowercase())
+            .or_insert_with(|| MuscleVolume {
+                muscle: muscle.to_string(),
+                group: muscles::group_of(muscle),
+                ..Default::default()
+            })
+            .add(workout, share);
+    }
+}
+
+/// Each exercise's muscles, by lowercased name.
+fn exercise_targets(service: &AppService) -> Result<HashMap<String, MuscleTargets>, String> {
+    let mut details = exercise_details::load_all(service)?;
+    Ok(service
+        .list_exercises(None, None)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|definition| {
+            let details = details.remove(&definition.id).unwrap_or_default();
+            let targets = MuscleTargets::for_exercise(&definition, &details);
+            (definition.name.to_lowercase(), targets)
+        })
+        .collect())
+}
+
+pub fn muscle_volume(
+    service: &AppService,
+    query: &MuscleVolumeQuery,
+) -> Result<MuscleVolumeReport, String> {
+    let period = query
+        .period
+        .as_deref()
+        .map(Period::parse)
+        .transpose()?
+        .unwrap_or(Period::Week);
+    let start = query
+        .start_date
+        .as_deref()
+        .map(parse_naive_date)
+        .transpose()?;
+    let end = query
+        .end_date
+        .as_deref()
+        .map(parse_naive_date)
+        .transpose()?;
+    let tz = timezone::user_time_zone(service);
+    let secondary_weight = GuiSettings::load(service)?.secondary_muscle_weight;
+    let wanted: HashSet<String> = query
+        .muscles
+        .iter()
+        .flatten()
+        .flat_map(|filter| muscles::expand(filter))
+        .collect();
+    let targets = exercise_targets(service)?;
+
+    let in_range =
+        |day: &NaiveDate| start.map_or(true, |s| *day >= s) && end.map_or(true, |e| *day <= e);
+    let mut workouts: Vec<(NaiveDate, Workout)> =
+        list_workouts_in(service, &WorkoutFiltersCmdParams::default(), tz)?
+            .into_iter()
+            .map(|w| (timezone::local_date(tz, w.timestamp), w))
+            .filter(|(day, _)| in_range(day))
+            .collect();
+    workouts.sort_by_key(|(day, _)| *day);
+
+    let end = end.unwrap_or_else(|| timezone::today(tz));
+    let Some(start) = start.or_else(|| workouts.first().map(|(day, _)| *day)) else {
+        return Ok(MuscleVolumeReport {
+            period,
+            secondary_muscle_weight: secondary_weight,
+            buckets: Vec::new(),
+            totals: Vec::new(),
+        });
+    };
+
+    let mut buckets: BTreeMap<NaiveDate, Volumes> = BTreeMap::new();
+    let mut bucket = period.start_of(start);
+    while bucket <= end {
+        buckets.insert(bucket, Volumes::new());
+        bucket = period.next(bucket);
+    }
+    let mut totals = Volumes::new();
+    for (day, workout) in &workouts {
+        let Some(exercise) = targets.get(&workout.exercise_name.to_lowercase()) else {
+            continue;
+        };
+        let weighted: Vec<(&str, f64)> = exercise
+            .weighted(secondary_weight)
+            .into_iter()
+            .filter(|(muscle, _)| wanted.is_empty() || wanted.contains(&muscle.to_lowercase()))
+            .collect();
+        if let Some(volumes) = buckets.get_mut(&period.start_of(*day)) {
+            add_workout(volumes, workout, &weighted);
+        }
+        add_workout(&mut totals, workout, &weighted);
+    }
+
+    Ok(MuscleVolumeReport {
+        period,
+        secondary_muscle_weight: secondary_weight,
+        buckets: buckets
+            .into_iter()
+            .map(|(start, volumes)| VolumeBucket {
+                start,
+                end: period.next(start) - Duration::days(1),
+                muscles: volumes.into_values().collect(),
+            })
+            .collect(),
+        totals: totals.into_values().collect(),
+    })
+}
+
+// --- Tauri Commands ---
+
+/// Sets, reps and tonnage per muscle for each day, week or month of the
+/// range.
+#[tauri::command]
+pub async fn get_muscle_volume(
+    query: Option<MuscleVolumeQuery>,
+    state: tauri::State<'_, AppState>,
+) -> Result<MuscleVolumeReport, String> {
+    let query = query.unwrap_or_default();
+    run_blocking(&state, move |pool| muscle_volume(&pool.read()?, &query)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exercise_details::ExerciseDetails;
+    use crate::records::{self, WorkoutSnapshot};
+    use chrono::{TimeZone, Utc};
+    use task_athlete_lib::ExerciseType;
+
+    fn temp_service(name: &str) -> AppService {
+        let dir =
+            std::env::temp_dir().join(format!("task-athlete-gui-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let service = AppService::initialize_with_paths(
+            &dir.join("config.toml"),
+            &dir.join("workouts.sqlite"),
+        )
+        .unwrap();
+        exercise_details::ensure_schema(&service).unwrap();
+        service
+    }
+
+    fn bench(service: &mut AppService, day: u32, sets: i64) {
+        let snapshot = WorkoutSnapshot {
+            id: 0,
+            // Midday, so the local date is the same in any nearby time zone.
+            timestamp: Utc.with_ymd_and_hms(2024, 5, day, 12, 0, 0).unwrap(),
+            exercise_name: "Bench Press".to_string(),
+            exercise_type: Some("resistance".to_string()),
+            sets: Some(sets),
+            reps: Some(10),
+            weight: Some(50.0),
+            duration_minutes: None,
+            distance: None,
+            bodyweight: None,
+            notes: None,
+        };
+        records::add_snapshot(service, &snapshot).unwrap();
+    }
+
+    fn service_with_bench(name: &str) -> AppService {
+        let mut service = temp_service(name);
+        let id = service
+            .create_exercise(
+                "Bench Press",
+                ExerciseType::Resistance,
+                None,
+                Some("Chest, Triceps"),
+            )
+            .unwrap();
+        let details = ExerciseDetails {
+            secondary_muscles: vec!["Triceps".to_string()],
+            ..ExerciseDetails::default()
+        };
+        exercise_details::save(&service, id, &details).unwrap();
+        // Monday and Wednesday of one week, then Tuesday of the next.
+        bench(&mut service, 6, 3);
+        bench(&mut service, 8, 2);
+        bench(&mut service, 14, 4);
+        service
+    }
+
+    fn query(muscles: Option<Vec<String>>) -> MuscleVolumeQuery {
+        MuscleVolumeQuery {
+            start_date: Some("2024-05-06".to_string()),
+            end_date: Some("2024-05-26".to_string()),
+            period: None,
+            muscles,
+        }
+    }
+
+    fn volume_of<'a>(volumes: &'a [MuscleVolume], muscle: &str) -> &'a MuscleVolume {
+        volumes.iter().find(|v| v.muscle == muscle).unwrap()
+    }
+
+    #[test]
+    fn secondary_muscles_get_a_share_of_each_set() {
+        let service = service_with_bench("volume-weekly");
+        let report = muscle_volume(&service, &query(None)).unwrap();
+        assert_eq!(report.secondary_muscle_weight, 0.5);
+
+        let starts: Vec<NaiveDate> = report.buckets.iter().map(|b| b.start).collect();
+        let day = |d| NaiveDate::from_ymd_opt(2024, 5, d).unwrap();
+        assert_eq!(starts, vec![day(6), day(13), day(20)]);
+        assert_eq!(report.buckets[0].end, day(12));
+        assert!(report.buckets[2].muscles.is_empty());
+
+        let first = &report.buckets[0].muscles;
+        let chest = volume_of(first, "Chest");
+        assert_eq!(chest.group, Some("Chest"));
+        assert_eq!(chest.sets, 5.0);
+        assert_eq!(chest.reps, 50.0);
+        assert_eq!(chest.tonnage, 2500.0);
+        let triceps = volume_of(first, "Triceps");
+        assert_eq!(triceps.sets, 2.5);
+        assert_eq!(triceps.tonnage, 1250.0);
+
+        assert_eq!(volume_of(&report.totals, "Chest").sets, 9.0);
+        assert_eq!(volume_of(&report.totals, "Triceps").sets, 4.5);
+    }
+
+    #[test]
+    fn muscle_filters_expand_groups() {
+        let service = service_with_bench("volume-filtered");
+        let report = muscle_volume(&service, &query(Some(vec!["Arms".to_string()]))).unwrap();
+        let muscles: Vec<&str> = report.totals.iter().map(|v| v.muscle.as_str()).collect();
+        assert_eq!(muscles, vec!["Triceps"]);
+    }
+
+    #[test]
+    fn no_workouts_and_no_start_gives_an_empty_report() {
+        let service = temp_service("volume-empty");
+        let report = muscle_volume(&service, &MuscleVolumeQuery::default()).unwrap();
+        assert!(report.buckets.is_empty());
+        assert!(report.totals.is_empty());
+    }
+}
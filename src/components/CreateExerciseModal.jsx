@@ -10,10 +10,16 @@ const EXERCISE_TYPES = [
   { value: 'Cardio', label: 'Cardio' },
 ];
 
+const EQUIPMENT = ['barbell', 'dumbbell', 'cable', 'machine', 'bodyweight', 'kettlebell', 'band'];
+
 const CreateExerciseModal = ({ isOpen, onClose, onExerciseCreated, availableMuscles: propAvailableMuscles }) => {
   const [exerciseName, setExerciseName] = useState('');
   const [exerciseType, setExerciseType] = useState(''); // 'Resistance', 'BodyWeight', 'Cardio'
   const [musclesTargeted, setMusclesTargeted] = useState(''); // Comma-separated string
+  const [secondaryMuscles, setSecondaryMuscles] = useState(''); // Comma-separated string
+  const [equipment, setEquipment] = useState('');
+  const [instructions, setInstructions] = useState('');
+  const [referenceUrl, setReferenceUrl] = useState('');
   const [logConfig, setLogConfig] = useState({
     reps: false,
     weight: false,
@@ -41,6 +47,10 @@ const CreateExerciseModal = ({ isOpen, onClose, onExerciseCreated, availableMusc
       setExerciseName('');
       setExerciseType('');
       setMusclesTargeted('');
+      setSecondaryMuscles('');
+      setEquipment('');
+      setInstructions('');
+      setReferenceUrl('');
       setLogConfig({ reps: false, weight: false, duration: false, distance: false });
       setError(null);
       setSuccessMessage(null);
@@ -82,6 +92,7 @@ const CreateExerciseModal = ({ isOpen, onClose, onExerciseCreated, availableMusc
     }
     // Basic validation for muscles: allow empty, or non-empty must not be just commas/spaces
     const cleanedMuscles = musclesTargeted.split(',').map(m => m.trim()).filter(Boolean).join(', ');
+    const cleanedSecondary = secondaryMuscles.split(',').map(m => m.trim()).filter(Boolean);
 
 
     setIsSubmitting(true);
@@ -90,10 +101,14 @@ const CreateExerciseModal = ({ isOpen, onClose, onExerciseCreated, availableMusc
       name: exerciseName.trim(),
       typeStr: exerciseType, 
       muscles: cleanedMuscles ? cleanedMuscles : null,
+      secondaryMuscles: cleanedSecondary.length > 0 ? cleanedSecondary : null,
       logReps: logConfig.reps,     
       logWeight: logConfig.weight,   
       logDuration: logConfig.duration, 
       logDistance: logConfig.distance, 
+      equipment: equipment || null,
+      instructions: instructions.trim() || null,
+      referenceUrl: referenceUrl.trim() || null,
     };
 
 
@@ -172,12 +187,21 @@ const CreateExerciseModal = ({ isOpen, onClose, onExerciseCreated, availableMusc
 
           <div>
             <label htmlFor="musclesTargeted" className="block text-sm font-medium text-default mb-1">
-              Muscles Targeted <span className="text-xs text-muted">(comma-separated, e.g., Quads, Glutes)</span>
+              Primary Muscles <span className="text-xs text-muted">(comma-separated, e.g., Quads, Glutes)</span>
             </label>
             <input
               type="text" name="musclesTargeted" id="musclesTargeted"
               value={musclesTargeted} onInput={(e) => setMusclesTargeted(e.target.value)}
-              placeholder="e.g., Chest, Triceps, Shoulders"
+              placeholder="e.g., Chest"
+              className="w-full p-2.5 bg-surface text-default border border-subtle rounded-lg focus:ring-2 focus:ring-accent-subtle-bg focus:border-accent-emphasis shadow-sm"
+            />
+            <label htmlFor="secondaryMuscles" className="block text-sm font-medium text-default mt-3 mb-1">
+              Secondary Muscles <span className="text-xs text-muted">(worked less, count partially toward volume)</span>
+            </label>
+            <input
+              type="text" name="secondaryMuscles" id="secondaryMuscles"
+              value={secondaryMuscles} onInput={(e) => setSecondaryMuscles(e.target.value)}
+              placeholder="e.g., Triceps, Shoulders"
               className="w-full p-2.5 bg-surface text-default border border-subtle rounded-lg focus:ring-2 focus:ring-accent-subtle-bg focus:border-accent-emphasis shadow-sm"
             />
             {/* Optional: Display available muscles for reference or selection */}
@@ -186,6 +210,39 @@ const CreateExerciseModal = ({ isOpen, onClose, onExerciseCreated, availableMusc
             )} */}
           </div>
 
+          <div>
+            <label htmlFor="equipment" className="block text-sm font-medium text-default mb-1">Equipment</label>
+            <select
+              id="equipment" value={equipment} onChange={(e) => setEquipment(e.target.value)}
+              className="w-full p-2.5 bg-surface text-default border border-subtle rounded-lg focus:ring-2 focus:ring-accent-subtle-bg focus:border-accent-emphasis shadow-sm"
+            >
+              <option value="">None / not specified</option>
+              {EQUIPMENT.map(item => <option key={item} value={item} className="capitalize">{item}</option>)}
+            </select>
+          </div>
+
+          <div>
+            <label htmlFor="instructions" className="block text-sm font-medium text-default mb-1">
+              Instructions <span className="text-xs text-muted">(optional cues)</span>
+            </label>
+            <textarea
+              id="instructions" rows={3}
+              value={instructions} onInput={(e) => setInstructions(e.target.value)}
+              placeholder="e.g., Keep your shoulder blades retracted"
+              className="w-full p-2.5 bg-surface text-default border border-subtle rounded-lg focus:ring-2 focus:ring-accent-subtle-bg focus:border-accent-emphasis shadow-sm"
+            />
+          </div>
+
+          <div>
+            <label htmlFor="referenceUrl" className="block text-sm font-medium text-default mb-1">Reference Link</label>
+            <input
+              type="url" id="referenceUrl"
+              value={referenceUrl} onInput={(e) => setReferenceUrl(e.target.value)}
+              placeholder="https://..."
+              className="w-full p-2.5 bg-surface text-default border border-subtle rounded-lg focus:ring-2 focus:ring-accent-subtle-bg focus:border-accent-emphasis shadow-sm"
+            />
+          </div>
+
           <div>
             <p className="block text-sm font-medium text-default mb-2">Loggable Metrics:</p>
             <div className="grid grid-cols-2 gap-x-4 gap-y-2">
@@ -0,0 +1,88 @@
+// src-ui/components/ExerciseCatalog.jsx
+import { h } from 'preact';
+import { useState, useEffect } from 'preact/hooks';
+import { invoke } from '@tauri-apps/api/core';
+import { Loader2, Search, Download } from 'lucide-preact';
+
+// Browses the bundled exercise catalog and imports the checked entries.
+const ExerciseCatalog = ({ onImported }) => {
+  const [search, setSearch] = useState('');
+  const [entries, setEntries] = useState([]);
+  const [checked, setChecked] = useState(new Set());
+  const [loading, setLoading] = useState(true);
+  const [importing, setImporting] = useState(false);
+  const [message, setMessage] = useState(null);
+
+  useEffect(() => {
+    const timeoutId = setTimeout(async () => {
+      setLoading(true);
+      try {
+        setEntries(await invoke('search_exercise_catalog', { query: { search: search.trim() || null } }));
+      } catch (err) {
+        console.error("Catalog search error:", err);
+        setMessage(typeof err === 'string' ? err : "Failed to load the exercise catalog.");
+      } finally {
+        setLoading(false);
+      }
+    }, search ? 300 : 0);
+    return () => clearTimeout(timeoutId);
+  }, [search]);
+
+  const toggle = (name) => {
+    setChecked(prev => {
+      const next = new Set(prev);
+      next.has(name) ? next.delete(name) : next.add(name);
+      return next;
+    });
+  };
+
+  const handleImport = async () => {
+    setImporting(true);
+    setMessage(null);
+    try {
+      const result = await invoke('import_catalog_exercises', { names: [...checked] });
+      setMessage(`Added ${result.created.length} exercise(s)` +
+        (result.skipped.length > 0 ? `, skipped ${result.skipped.length} already added.` : '.'));
+      setChecked(new Set());
+      const added = new Set(result.created.map(e => e.name));
+      setEntries(prev => prev.map(e => added.has(e.name) ? { ...e, already_added: true } : e));
+      if (onImported) onImported(result);
+    } catch (err) {
+      console.error("Catalog import error:", err);
+      setMessage(typeof err === 'string' ? err : "Failed to import exercises.");
+    } finally {
+      setImporting(false);
+    }
+  };
+
+  return (
+    <div className="space-y-3">
+      <div className="relative">
+        <div className="absolute inset-y-0 left-0 pl-3 flex items-center pointer-events-none"><Search size={20} className="text-muted" /></div>
+        <input type="text" placeholder="Search the catalog..." value={search} onInput={(e) => setSearch(e.target.value)} className="w-full pl-10 pr-3 py-2.5 bg-surface text-default border border-subtle rounded-lg focus:ring-2 focus:ring-accent-subtle-bg focus:border-accent-emphasis shadow-sm"/>
+      </div>
+      {message && <p className="text-sm text-subtle">{message}</p>}
+      {loading ? (
+        <div className="flex justify-center py-8"><Loader2 size={36} className="animate-spin text-accent-emphasis" /></div>
+      ) : (
+        <div className="max-h-[calc(90vh-380px)] overflow-y-auto space-y-1 pr-1 -mr-1">
+          {entries.map(entry => (
+            <label key={entry.name} className={`flex items-start gap-3 p-2.5 border border-subtle rounded-lg ${entry.already_added ? 'opacity-50' : 'hover:bg-accent-subtle-bg cursor-pointer'}`}>
+              <input type="checkbox" className="mt-1" disabled={entry.already_added} checked={checked.has(entry.name)} onChange={() => toggle(entry.name)} />
+              <span>
+                <span className="block font-medium text-default">{entry.name}</span>
+                <span className="block text-xs text-muted">{[...entry.primary_muscles, ...entry.secondary_muscles].join(', ') || entry.type}</span>
+              </span>
+            </label>
+          ))}
+          {entries.length === 0 && <p className="text-center text-muted py-6">No catalog exercises match your search.</p>}
+        </div>
+      )}
+      <button type="button" onClick={handleImport} disabled={checked.size === 0 || importing} className="w-full py-2.5 bg-accent-emphasis hover:bg-accent-emphasis-hover text-on-accent rounded-lg font-medium flex items-center justify-center gap-2 disabled:opacity-50">
+        {importing ? <Loader2 size={18} className="animate-spin" /> : <Download size={18} />} Add {checked.size || ''} selected
+      </button>
+    </div>
+  );
+};
+
+export default ExerciseCatalog;
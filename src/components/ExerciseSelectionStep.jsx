@@ -2,7 +2,8 @@
 import { h } from 'preact';
 import { useState, useEffect, useCallback } from 'preact/hooks';
 import { invoke } from '@tauri-apps/api/core';
-import { Loader2, AlertTriangle, Search, XCircle, PlusCircle } from 'lucide-preact';
+import { Loader2, AlertTriangle, Search, XCircle, PlusCircle, BookOpen } from 'lucide-preact';
+import ExerciseCatalog from './ExerciseCatalog';
 
 const EXERCISE_TYPES = { BODYWEIGHT: 'BodyWeight', RESISTANCE: 'Resistance', CARDIO: 'Cardio' };
 const EXERCISE_TYPE_LABELS = { [EXERCISE_TYPES.BODYWEIGHT]: 'Bodyweight', [EXERCISE_TYPES.RESISTANCE]: 'Resistance', [EXERCISE_TYPES.CARDIO]: 'Cardio' };
@@ -17,16 +18,20 @@ const ExerciseSelectionStep = ({ onExerciseSelect, onOpenCreateExerciseModal, on
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState(null);
   const [initialExerciseCount, setInitialExerciseCount] = useState(0);
+  const [showCatalog, setShowCatalog] = useState(false);
 
   const fetchInitialStep1Data = useCallback(async () => {
     setLoading(true);
     setError(null);
     try {
-      const [musclesData, allExercisesInitial] = await Promise.all([
+      const [musclesData, muscleGroups, allExercisesInitial] = await Promise.all([
         invoke('list_all_muscles'),
+        invoke('get_muscle_taxonomy'),
         invoke('list_exercises', { typeFilterStr: null, muscleFilter: null }) // To get initial count and display
       ]);
-      setAvailableMuscles(musclesData || []);
+      // Groups (e.g. "Legs") filter on all their muscles; list them first.
+      const groupNames = (muscleGroups || []).map(g => g.name);
+      setAvailableMuscles([...groupNames, ...(musclesData || []).filter(m => !groupNames.includes(m))]);
       const count = allExercisesInitial ? allExercisesInitial.length : 0;
       setInitialExerciseCount(count);
       
@@ -111,6 +116,15 @@ const ExerciseSelectionStep = ({ onExerciseSelect, onOpenCreateExerciseModal, on
   
   const noFiltersActive = !searchTerm.trim() && selectedMuscles.length === 0 && !selectedExerciseType;
 
+  if (showCatalog) {
+    return (
+      <div className="space-y-4 sm:space-y-5">
+        <button type="button" onClick={() => setShowCatalog(false)} className="text-sm text-accent-emphasis hover:underline font-medium">← Back to my exercises</button>
+        <ExerciseCatalog onImported={() => { fetchInitialStep1Data(); fetchExercisesOnFilterChange(); }} />
+      </div>
+    );
+  }
+
   return (
     <div className="space-y-4 sm:space-y-5">
       {error && (
@@ -126,6 +140,7 @@ const ExerciseSelectionStep = ({ onExerciseSelect, onOpenCreateExerciseModal, on
           {searchTerm && (<button onClick={() => setSearchTerm('')} className="absolute inset-y-0 right-0 pr-3 flex items-center text-muted hover:text-subtle" aria-label="Clear search"><XCircle size={18} /></button>)}
         </div>
         <button type="button" onClick={onOpenCreateExerciseModal} title="Create New Exercise" className="p-2.5 bg-accent-emphasis hover:bg-accent-emphasis-hover text-on-accent rounded-lg shadow-sm transition-colors flex items-center justify-center"><PlusCircle size={20} /></button>
+        <button type="button" onClick={() => setShowCatalog(true)} title="Browse Exercise Catalog" className="p-2.5 bg-app hover:bg-accent-subtle-bg text-default border border-subtle rounded-lg shadow-sm transition-colors flex items-center justify-center"><BookOpen size={20} /></button>
       </div>
 
       <div>
@@ -154,7 +169,7 @@ const ExerciseSelectionStep = ({ onExerciseSelect, onOpenCreateExerciseModal, on
           </button>))}
       </div>)}
       
-      {!loading && exercisesToDisplay.length === 0 && ( noFiltersActive ? ( initialExerciseCount === 0 ? (<p className="text-center text-muted py-6">No exercises defined yet. Try creating one!</p>) : (<p className="text-center text-muted py-6">Search or apply filters to see results.</p>) ) : (<p className="text-center text-muted py-6">No exercises found matching your criteria.</p>) )}
+      {!loading && exercisesToDisplay.length === 0 && ( noFiltersActive ? ( initialExerciseCount === 0 ? (<p className="text-center text-muted py-6">No exercises defined yet. Try creating one, or <button type="button" onClick={() => setShowCatalog(true)} className="text-accent-emphasis hover:underline font-medium">add some from the catalog</button>.</p>) : (<p className="text-center text-muted py-6">Search or apply filters to see results.</p>) ) : (<p className="text-center text-muted py-6">No exercises found matching your criteria.</p>) )}
     </div>
   );
 };
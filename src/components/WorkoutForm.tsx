@@ -134,7 +134,7 @@ export const WorkoutForm: FunctionalComponent<WorkoutFormProps> = ({
                 new_duration: formData.duration,
                 new_distance_arg: formData.distance, // Note name difference
                 new_notes: formData.notes,
-                new_date: isoDateString ? new Date(isoDateString).toISOString().split('T')[0] : undefined, // Requires YYYY-MM-DD for edit
+                new_timestamp: isoDateString, // Full UTC ISO string, keeps the chosen time
             }
             : { // Add Params (match AddWorkoutCmdParams, AddWorkoutParams TS type)
                 exercise_identifier: formData.exercise_identifier!,
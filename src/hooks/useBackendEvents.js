@@ -0,0 +1,36 @@
+// src/hooks/useBackendEvents.js
+import { useEffect } from 'preact/hooks';
+import { listen } from '@tauri-apps/api/event';
+
+// Event names emitted by the backend (see src-tauri/src/events.rs).
+// Every payload carries `source`: "local", "sync" or "profile".
+export const WORKOUTS_CHANGED = 'workouts-changed';     // { ids, dates, source }
+export const EXERCISES_CHANGED = 'exercises-changed';   // { source }
+export const BODYWEIGHT_CHANGED = 'bodyweight-changed'; // { ids, source }
+export const CONFIG_CHANGED = 'config-changed';         // { source }
+
+// Calls handler(payload, eventName) whenever one of eventNames is emitted.
+// Wrap handler in useCallback so listeners aren't re-registered every render.
+export const useBackendEvents = (eventNames, handler) => {
+  const namesKey = eventNames.join(',');
+  useEffect(() => {
+    let cancelled = false;
+    const unlisteners = [];
+    eventNames.forEach(name => {
+      listen(name, (event) => handler(event.payload, name))
+        .then(unlisten => {
+          if (cancelled) unlisten();
+          else unlisteners.push(unlisten);
+        })
+        .catch(err => console.error(`Failed to listen for ${name}:`, err));
+    });
+    return () => {
+      cancelled = true;
+      unlisteners.forEach(unlisten => unlisten());
+    };
+  }, [namesKey, handler]);
+};
+
+// True when a workouts-changed payload may affect the given "YYYY-MM-DD" day.
+export const affectsDate = (payload, dateKey) =>
+  !payload || !Array.isArray(payload.dates) || payload.dates.length === 0 || payload.dates.includes(dateKey);
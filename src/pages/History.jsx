@@ -1,6 +1,7 @@
 import { h } from 'preact';
-import { useState, useEffect, useMemo } from 'preact/hooks';
+import { useState, useEffect, useMemo, useCallback } from 'preact/hooks';
 import { invoke } from '@tauri-apps/api/core';
+import { useBackendEvents, WORKOUTS_CHANGED, EXERCISES_CHANGED } from '../hooks/useBackendEvents';
 import { SlidersHorizontal, Calendar as CalendarIcon, ChevronRight, List, LayoutList, X, AlertTriangle, Loader2 } from 'lucide-preact'; // Added Loader2
 
 // --- Helper to parse muscle strings "Chest,Triceps" or ["Chest", "Triceps"] into ['chest', 'triceps'] ---
@@ -216,6 +217,10 @@ const History = () => {
   const [isLoading, setIsLoading] = useState(true);
   const [error, setError] = useState(null);
   const [showMobileFilters, setShowMobileFilters] = useState(false);
+  const [refreshKey, setRefreshKey] = useState(0);
+  const [copyMessage, setCopyMessage] = useState(null);
+
+  useBackendEvents([WORKOUTS_CHANGED, EXERCISES_CHANGED], useCallback(() => setRefreshKey(k => k + 1), []));
 
   useEffect(() => {
     const fetchData = async () => {
@@ -224,7 +229,7 @@ const History = () => {
       try {
         const [workoutsDataRaw, exercisesData, musclesData] = await Promise.all([
           invoke('list_workouts', { filters: {} }),
-          invoke('list_exercises', { typeFilterStr: null, musclesFilter: null }),
+          invoke('list_exercises', { typeFilterStr: null, musclesFilter: null, includeArchived: true }),
           invoke('list_all_muscles')
         ]);
 
@@ -256,7 +261,8 @@ const History = () => {
             } else if (originalDateValue && index < 5) {
               console.warn(`[HISTORY] Workout ${index} (id: ${w.id}): Non-string or empty date value: `, originalDateValue);
             }
-            return { ...w, date: isValidDate ? parsedDate : null };
+            // local_date is the day in the user's time zone, computed by the backend.
+            return { ...w, date: isValidDate ? parsedDate : null, dateKey: w.local_date };
           });
 
           const validDateWorkouts = parsedWorkouts.filter(w => w.date !== null);
@@ -277,7 +283,7 @@ const History = () => {
       }
     };
     fetchData();
-  }, []);
+  }, [refreshKey]);
 
   const filteredActiveWorkoutDatesInMonth = useMemo(() => {
     const year = calendarDate.getFullYear();
@@ -302,13 +308,11 @@ const History = () => {
     if (selectedExerciseNames.size > 0) {
       workoutsToConsider = workoutsToConsider.filter(workout => selectedExerciseNames.has(workout.exercise_name));
     }
+    const monthPrefix = `${year}-${String(month + 1).padStart(2, '0')}-`;
     const dates = new Set();
     workoutsToConsider.forEach(workout => {
-      if (workout.date instanceof Date && !isNaN(workout.date.getTime())) {
-        const d = workout.date;
-        if (d.getFullYear() === year && d.getMonth() === month) {
-          dates.add(d.toISOString().split('T')[0]);
-        }
+      if (workout.dateKey && workout.dateKey.startsWith(monthPrefix)) {
+        dates.add(workout.dateKey);
       }
     });
     return dates;
@@ -323,9 +327,7 @@ const History = () => {
     let filtered = [...allWorkouts];
     if (selectedCalendarDate) {
       filtered = filtered.filter(workout => {
-        if (!workout.date) return false;
-        const workoutDateStr = workout.date.toISOString().split('T')[0];
-        return workoutDateStr === selectedCalendarDate;
+        return workout.dateKey === selectedCalendarDate;
       });
     }
     if (selectedMuscleGroups.size > 0) {
@@ -361,7 +363,7 @@ const History = () => {
     let currentGroup = null;
     for (const workout of displayedWorkouts) {
       if (workout.date) {
-        const dateStr = workout.date.toISOString().split('T')[0];
+        const dateStr = workout.dateKey;
         if (!currentGroup || currentGroup.dateIso !== dateStr) {
           currentGroup = { dateIso: dateStr, workoutsOnDate: [] };
           result.push(currentGroup);
@@ -405,6 +407,20 @@ const History = () => {
     setSelectedCalendarDate(null);
   };
 
+  // Copies the selected day's workouts to today; the list refreshes on the change event.
+  const handleCopyToToday = async () => {
+    if (!selectedCalendarDate) return;
+    try {
+      const result = await invoke('copy_workouts', { source: { date: selectedCalendarDate } });
+      const pbNames = [...new Set(result.personal_bests.map(pb => pb.exercise_name))];
+      setCopyMessage(`Copied ${result.ids.length} workout(s) to today.` +
+        (pbNames.length > 0 ? ` New PBs: ${pbNames.join(', ')}` : ''));
+    } catch (err) {
+      console.error("[HISTORY] Failed to copy workouts:", err);
+      setCopyMessage(`Failed to copy workouts: ${err}`);
+    }
+  };
+
   const handleSelectWorkout = (workout) => {
     console.log("Selected workout (placeholder):", workout);
   };
@@ -507,7 +523,7 @@ setCurrentView('list');
           <div class="flex justify-between items-center mb-4">
             <h1 class="text-xl md:text-2xl font-bold text-default">
                 {currentView === 'list' && selectedCalendarDate
-                    ? `Workouts on ${new Date(selectedCalendarDate + 'T00:00:00Z').toLocaleDateString(undefined, { year: 'numeric', month: 'long', day: 'numeric' })}`
+                    ? `Workouts on ${new Date(selectedCalendarDate + 'T00:00:00Z').toLocaleDateString(undefined, { year: 'numeric', month: 'long', day: 'numeric', timeZone: 'UTC' })}`
                     : currentView === 'calendar'
                     ? 'Calendar View'
                     : "Workout History"}
@@ -531,16 +547,24 @@ setCurrentView('list');
           {currentView === 'list' && (
             <>
               {selectedCalendarDate && (
-                <button onClick={() => setSelectedCalendarDate(null)} class="mb-3 text-sm text-primary hover:underline font-medium">
-                  ← Show all dates
-                </button>
+                <div class="mb-3 flex items-center justify-between gap-3">
+                  <button onClick={() => setSelectedCalendarDate(null)} class="text-sm text-primary hover:underline font-medium">
+                    ← Show all dates
+                  </button>
+                  {displayedWorkouts.length > 0 && (
+                    <button onClick={handleCopyToToday} class="text-sm bg-hover hover:bg-app-alt text-default py-1.5 px-3 rounded-md font-medium">
+                      Repeat today
+                    </button>
+                  )}
+                </div>
               )}
+              {copyMessage && <p class="mb-3 text-sm text-subtle">{copyMessage}</p>}
               {displayedWorkouts.length > 0 ? (
                 <div class="space-y-6">
                   {groupedWorkoutsByDate.map(({ dateIso, workoutsOnDate }) => (
                     <div key={dateIso} class="bg-app p-4 sm:p-5 rounded-xl border border-divider shadow-themed-md">
                       <h2 class="text-base sm:text-lg font-semibold text-default mb-3 sm:mb-4 border-b border-divider pb-2 sm:pb-3">
-                        {new Date(dateIso + 'T00:00:00Z').toLocaleDateString(undefined, { year: 'numeric', month: 'long', day: 'numeric' })}
+                        {new Date(dateIso + 'T00:00:00Z').toLocaleDateString(undefined, { year: 'numeric', month: 'long', day: 'numeric', timeZone: 'UTC' })}
                       </h2>
                       <div class="space-y-4">
                         {workoutsOnDate.map(workout => {
@@ -5,6 +5,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { Calendar, Plus, ChevronLeft, ChevronRight, AlertTriangle, Loader2, Award, X as CloseIcon, Trash2, PlusSquare, Edit3 } from 'lucide-preact';
 import DatePicker from '../components/DatePicker';
 import AddExerciseModal from '../components/AddExerciseModal';
+import { useBackendEvents, affectsDate, WORKOUTS_CHANGED, EXERCISES_CHANGED, CONFIG_CHANGED } from '../hooks/useBackendEvents';
 
 const processBackendWorkouts = (backendWorkouts) => {
   if (!backendWorkouts || backendWorkouts.length === 0) return [];
@@ -72,17 +73,22 @@ const LogWorkout = () => {
 
   const dateFormatOptions = { weekday: 'long', year: 'numeric', month: 'long', day: 'numeric' };
 
+  const [definitionsVersion, setDefinitionsVersion] = useState(0);
+  const [configVersion, setConfigVersion] = useState(0);
+  useBackendEvents([EXERCISES_CHANGED], useCallback(() => setDefinitionsVersion(v => v + 1), []));
+  useBackendEvents([CONFIG_CHANGED], useCallback(() => setConfigVersion(v => v + 1), []));
+
   useEffect(() => { /* fetchAllDefs - remains same */
     const fetchAllDefs = async () => {
       try {
-        const defs = await invoke('list_exercises', { typeFilterStr: null, muscleFilter: null });
+        const defs = await invoke('list_exercises', { typeFilterStr: null, muscleFilter: null, includeArchived: true });
         const map = new Map();
         (defs || []).forEach(def => map.set(def.name, def));
         setAllExerciseDefinitionsMap(map);
       } catch (err) { console.error("LogWorkout: Failed to fetch all exercise definitions:", err); }
     };
     fetchAllDefs();
-  }, []);
+  }, [definitionsVersion]);
   useEffect(() => { /* fetchUserConfig - remains same */
     const fetchUserConfig = async () => {
       try {
@@ -91,7 +97,7 @@ const LogWorkout = () => {
       } catch (err) { console.error("LogWorkout: Failed to fetch user config:", err); }
     };
     fetchUserConfig();
-  }, []);
+  }, [configVersion]);
   const changeDate = (days) => { /* ... */
     const newDate = new Date(currentDate);
     newDate.setDate(newDate.getDate() + days);
@@ -120,6 +126,9 @@ const LogWorkout = () => {
   useEffect(() => { /* fetchWorkoutsForCurrentDate call - remains same */
     fetchWorkoutsForCurrentDate();
   }, [fetchWorkoutsForCurrentDate]);
+  useBackendEvents([WORKOUTS_CHANGED], useCallback((payload) => {
+    if (affectsDate(payload, dateKey)) fetchWorkoutsForCurrentDate();
+  }, [dateKey, fetchWorkoutsForCurrentDate]));
 
   const handleModalActionCompleted = (pbInfo) => {
     fetchWorkoutsForCurrentDate();
@@ -2,6 +2,7 @@
 import { h } from 'preact';
 import { useState, useEffect, useCallback } from 'preact/hooks';
 import { invoke } from '@tauri-apps/api/core';
+import { useBackendEvents, CONFIG_CHANGED, BODYWEIGHT_CHANGED } from '../hooks/useBackendEvents';
 import List from 'preact-material-components/List';
 import Switch from 'preact-material-components/Switch';
 import Select from 'preact-material-components/Select';
@@ -79,6 +80,16 @@ const Profile = () => {
     fetchConfig();
   }, [fetchConfig]);
 
+  // Refresh quietly (no loading screen) when settings change elsewhere, e.g. after a sync.
+  const refreshConfig = useCallback(async () => {
+    try {
+      setConfig(await invoke('get_config'));
+    } catch (err) {
+      console.error("Failed to refresh config:", err);
+    }
+  }, []);
+  useBackendEvents([CONFIG_CHANGED, BODYWEIGHT_CHANGED], refreshConfig);
+
   const handleSettingChange = useCallback(async (command, payload, updateFn) => {
     try {
       await invoke(command, payload);
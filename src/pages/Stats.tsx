@@ -1,7 +1,9 @@
 // src/components/Stats/Stats.tsx (or your actual path)
 import { h } from 'preact';
-import { useState, useEffect, useRef } from 'preact/hooks';
+import { useState, useEffect, useRef, useCallback } from 'preact/hooks';
 import { invoke } from '@tauri-apps/api/core';
+// @ts-ignore -- plain JS module
+import { useBackendEvents, WORKOUTS_CHANGED, EXERCISES_CHANGED, CONFIG_CHANGED } from '../hooks/useBackendEvents';
 import * as d3 from 'd3';
 import { ListChecks, Target, Timer } from 'lucide-preact';
 
@@ -54,6 +56,7 @@ const graphTypeOptions = [
   { value: 'MaxReps', label: 'Max Reps (at any weight)' },
   { value: 'WorkoutVolume', label: 'Total Volume' },
   { value: 'WorkoutReps', label: 'Total Reps' },
+  { value: 'WorkoutSets', label: 'Total Sets' },
   { value: 'WorkoutDuration', label: 'Total Duration (min)' },
   { value: 'WorkoutDistance', label: 'Total Distance' },
 ];
@@ -66,9 +69,13 @@ const timeFrameOptions = [
   { id: 'last_30d', label: 'Last 30 Days' },
 ];
 
+// "YYYY-MM-DD" of a date's local day; toISOString would give the UTC day.
+const toLocalDateKey = (date: Date): string =>
+  `${date.getFullYear()}-${String(date.getMonth() + 1).padStart(2, '0')}-${String(date.getDate()).padStart(2, '0')}`;
+
 const getTimeFrameDates = (timeFrameId: string): { startDate?: string; endDate?: string } => {
   const today = new Date();
-  const endDate = today.toISOString().split('T')[0];
+  const endDate = toLocalDateKey(today);
 
   switch (timeFrameId) {
     case 'ytd':
@@ -77,17 +84,17 @@ const getTimeFrameDates = (timeFrameId: string): { startDate?: string; endDate?:
       const oneYearAgo = new Date(today);
       oneYearAgo.setFullYear(today.getFullYear() - 1);
       oneYearAgo.setDate(oneYearAgo.getDate() + 1); // Correctly make it exactly 12 months
-      return { startDate: oneYearAgo.toISOString().split('T')[0], endDate };
+      return { startDate: toLocalDateKey(oneYearAgo), endDate };
     }
     case 'last_90d': {
       const ninetyDaysAgo = new Date(today);
       ninetyDaysAgo.setDate(today.getDate() - 89); // today - 89 days = 90 distinct days including today
-      return { startDate: ninetyDaysAgo.toISOString().split('T')[0], endDate };
+      return { startDate: toLocalDateKey(ninetyDaysAgo), endDate };
     }
     case 'last_30d': {
       const thirtyDaysAgo = new Date(today);
       thirtyDaysAgo.setDate(today.getDate() - 29); // today - 29 days = 30 distinct days including today
-      return { startDate: thirtyDaysAgo.toISOString().split('T')[0], endDate };
+      return { startDate: toLocalDateKey(thirtyDaysAgo), endDate };
     }
     case 'all':
     default:
@@ -372,14 +379,24 @@ const Stats = () => {
   }, []); // Empty dependency array means this runs once on mount and cleans up on unmount
 
 
+  // Bumped by backend events so the effects below refetch.
+  const [definitionsVersion, setDefinitionsVersion] = useState(0);
+  const [dataVersion, setDataVersion] = useState(0);
+  useBackendEvents([EXERCISES_CHANGED, CONFIG_CHANGED], useCallback(() => setDefinitionsVersion(v => v + 1), []));
+  useBackendEvents([WORKOUTS_CHANGED], useCallback(() => setDataVersion(v => v + 1), []));
+
   useEffect(() => {
     const fetchInitialData = async () => {
       setIsLoading(true); // Combined loading for exercises and initial chart
       try {
         // Fetch exercises
-        const fetchedExercises: ExerciseDefinition[] = await invoke('list_exercises', {});
+        const fetchedExercises: ExerciseDefinition[] = await invoke('list_exercises', { includeArchived: true });
         setExercises(fetchedExercises);
-        if (fetchedExercises.length > 0) {
+        // Keep the current selection when refreshing after a backend change.
+        const selectionStillExists = fetchedExercises.some(ex => ex.id.toString() === selectedExerciseId);
+        if (selectionStillExists) {
+          setIsLoading(false);
+        } else if (fetchedExercises.length > 0) {
           // Automatically select the first exercise
           const firstExId = fetchedExercises[0].id.toString();
           setSelectedExerciseId(firstExId);
@@ -399,7 +416,7 @@ const Stats = () => {
       // setIsLoading(false) will be handled by chart data fetch if exercises exist
     };
     fetchInitialData();
-  }, []);
+  }, [definitionsVersion]);
 
   useEffect(() => {
     if (!selectedExerciseId) {
@@ -456,7 +473,7 @@ const Stats = () => {
     if (selectedGraphType) fetchChartData(); // Fetch chart data if graph type is also selected
     fetchExerciseStats(); // Always fetch stats for the selected exercise
 
-  }, [selectedExerciseId, selectedGraphType, selectedTimeFrame]);
+  }, [selectedExerciseId, selectedGraphType, selectedTimeFrame, dataVersion]);
 
   const MetricCard = ({ title, value, icon: Icon }: {title: string, value: string | JSX.Element, icon: any}) => (
     <div class="bg-[var(--color-bg-surface)] p-4 rounded-md border border-[var(--color-border-subtle)] shadow-sm flex-shrink-0 w-full sm:w-auto sm:flex-grow">
@@ -11,6 +11,12 @@ export interface Config {
     backup_path: string | null;
     units: string; // Assuming Units is represented as a string for simplicity now
     bodyweight: number | null;
+    profile: string; // Name of the active profile
+    trash_retention_days: number; // Days deleted records stay in the trash (0 = forever)
+    time_zone: string | null; // IANA name; null follows the system
+    effective_time_zone: string; // Zone day boundaries are computed in
+    secondary_muscle_weight: number; // Share of the work credited to secondary muscles (0-1)
+    one_rm_formula: OneRmFormula; // Used for every estimated 1RM
     // Add other config fields if they exist and are serialized
 }
 
@@ -37,6 +43,7 @@ export interface Workout {
     bodyweight: number | null;
     estimated_1rm: number | null;
     volume: number | null;
+    local_date: string; // "YYYY-MM-DD" in the user's time zone
 }
 
 // From ExerciseDefinition struct in lib (Needs Serialize)
@@ -44,14 +51,34 @@ export interface ExerciseDefinition {
     id: number; // Assuming i64 maps to number
     name: string;
     exercise_type: ExerciseType;
-    muscles: string | null;
+    muscles: string | null; // Every muscle, primary first, canonical names
+    primary_muscles: string[];
     log_weight: boolean;
     log_reps: boolean;
     log_duration: boolean;
     log_distance: boolean;
-    is_active: boolean;
+    is_active: boolean; // false once archived
     is_alias: boolean;
     alias_target: string | null;
+    // Details stored by the GUI only
+    instructions: string | null;
+    equipment: Equipment | null;
+    reference_url: string | null;
+    media: string[]; // File names; read with read_exercise_media
+    secondary_muscles: string[];
+}
+
+export type Equipment = "barbell" | "dumbbell" | "cable" | "machine" | "bodyweight" | "kettlebell" | "band";
+
+// From get_exercise_details
+export interface ExerciseDetailsPayload {
+    exercise_id: number;
+    name: string;
+    instructions: string | null;
+    equipment: Equipment | null;
+    reference_url: string | null;
+    media: string[];
+    media_paths: string[];
 }
 
 // From ExerciseStats struct in lib (Needs Serialize)
@@ -64,6 +91,7 @@ export interface ExerciseStats {
     total_distance: number | null;
     first_workout_date: string | null; // ISO Date string "YYYY-MM-DD"
     last_workout_date: string | null; // ISO Date string "YYYY-MM-DD"
+    best_estimated_1rm: number | null; // With the configured formula
     // Add PersonalBests if it's part of this struct and serialized
     // personal_bests: PersonalBests | null; // Define PersonalBests interface if needed
 }
@@ -105,18 +133,23 @@ export interface EditWorkoutParams {
     new_duration?: number;
     new_distance_arg?: number;
     new_notes?: string;
-    new_date?: string; // "YYYY-MM-DD"
+    new_date?: string; // "YYYY-MM-DD", keeps the workout's local time of day
+    new_timestamp?: string; // RFC3339, replaces date and time (not with new_date)
 }
 
 // Command parameter types for exercises
 export interface CreateExerciseParams {
     name: string;
     type_str: ExerciseType; // Use the defined type
-    muscles?: string;
+    muscles?: string; // Primary muscles
+    secondary_muscles?: string[];
     log_weight?: boolean;
     log_reps?: boolean;
     log_duration?: boolean;
     log_distance?: boolean;
+    instructions?: string;
+    equipment?: Equipment;
+    reference_url?: string;
 }
 
 export interface EditExerciseParams {
@@ -128,4 +161,246 @@ export interface EditExerciseParams {
     log_reps?: boolean;
     log_duration?: boolean;
     log_distance?: boolean;
+    new_instructions?: string; // Empty string clears
+    new_equipment?: Equipment | "";
+    new_reference_url?: string;
+    new_secondary_muscles?: string[];
+}
+
+// Audit log (get_record_history, list_recent_changes)
+export type RecordKind = "workout" | "exercise" | "alias" | "bodyweight" | "config";
+
+export interface AuditEntry {
+    id: number;
+    timestamp: string; // ISO 8601
+    kind: RecordKind;
+    record_id: string;
+    action: "created" | "updated" | "deleted";
+    origin: "local" | "sync";
+    old_value: Record<string, unknown> | null;
+    new_value: Record<string, unknown> | null; // Version revert_record restores
+}
+
+// copy_workouts
+export type CopySource = { date: string } | { ids: number[] };
+
+export interface CopyOptions {
+    weight_increment?: number; // kg
+    reps_increment?: number;
+    sets_increment?: number;
+    duration_increment?: number; // minutes
+    distance_increment?: number; // km
+}
+
+export interface CopyResult {
+    ids: number[];
+    personal_bests: { workout_id: number; exercise_name: string; pb_info: PBInfo }[];
+}
+
+// Bundled exercise catalog (search_exercise_catalog, import_catalog_exercises)
+export interface CatalogEntry {
+    name: string;
+    type: "resistance" | "cardio" | "body-weight";
+    primary_muscles: string[];
+    secondary_muscles: string[];
+    equipment: string;
+    log_weight: boolean;
+    log_reps: boolean;
+    log_duration: boolean;
+    log_distance: boolean;
+    already_added: boolean; // An exercise or alias with this name exists
+}
+
+export interface CatalogImport {
+    created: { id: number; name: string }[];
+    skipped: string[];
+}
+
+// merge_exercises
+export interface MergeResult {
+    target: string;
+    moved_workouts: number[];
+    new_aliases: string[]; // Merged names and their aliases, now aliases of the target
+    personal_bests: Record<string, unknown> | null;
+}
+
+// From get_muscle_taxonomy
+export interface MuscleGroup {
+    name: string; // Also accepted wherever a muscle filter is
+    muscles: string[];
+}
+
+// From normalize_exercise_muscles
+export interface MuscleNormalization {
+    exercise: string;
+    before: string | null;
+    after: string | null;
+}
+
+// From get_muscle_volume. Secondary muscles count for secondary_muscle_weight
+// of each set, so values can be fractional.
+export interface MuscleVolume {
+    muscle: string;
+    group: string | null; // null for custom muscles
+    sets: number;
+    reps: number;
+    tonnage: number; // kg
+}
+
+export interface MuscleVolumeReport {
+    period: "week" | "month";
+    secondary_muscle_weight: number;
+    buckets: { start: string; end: string; muscles: MuscleVolume[] }[]; // Empty buckets included
+    totals: MuscleVolume[];
+}
+
+export type GraphMetric =
+    | "Estimated1RM" | "MaxWeight" | "MaxReps"
+    | "WorkoutVolume" | "WorkoutReps" | "WorkoutSets" | "WorkoutDuration" | "WorkoutDistance"
+    | "WorkoutCount" | "TrainingDays";
+
+// One series of get_aggregate_graph: a metric over the workouts the filters match
+export interface AggregateSeriesQuery {
+    label?: string;
+    metric: GraphMetric;
+    exercise_name?: string;
+    exercise_type?: ExerciseType;
+    muscle?: string; // A muscle or group
+    start_date?: string; // "YYYY-MM-DD"
+    end_date?: string; // Defaults to today
+    limit_days?: number; // Last N days up to end_date, when start_date is unset
+}
+
+export interface AggregateGraphPayload {
+    series: AggregateSeriesQuery[];
+    bucket?: "day" | "week" | "month";
+}
+
+export interface GraphSeries {
+    label: string;
+    metric: GraphMetric;
+    points: [string, number][]; // [bucket start date, value]; summed metrics include empty buckets as 0
+}
+
+export type Normalization = "absolute" | "percent_change" | "relative_to_bodyweight";
+
+// Payload of get_comparison_graph
+export interface ComparisonGraphPayload {
+    series: { identifier: string; profile?: string; label?: string }[]; // profile defaults to the active one
+    graph_type_str: GraphMetric;
+    start_date?: string;
+    end_date?: string;
+    normalization?: Normalization; // relative_to_bodyweight only for Estimated1RM, MaxWeight and WorkoutVolume
+}
+
+export interface ComparisonGraph {
+    normalization: Normalization;
+    dates: string[]; // Every day any series has a value
+    series: { label: string; exercise: string; profile: string; values: (number | null)[] }[]; // values aligned with dates
+}
+
+// Options of get_graph_trends, sent alongside the get_data_for_graph payload
+export interface TrendOptions {
+    moving_average?: "sma" | "ema";
+    window?: number; // Default 7
+    weekly?: "max" | "mean";
+    regression?: "linear" | "log";
+    project_to?: string; // "YYYY-MM-DD"
+}
+
+export interface TrendGraph {
+    raw: [string, number][];
+    moving_average: [string, number][] | null;
+    weekly: [string, number][] | null; // Dated by each week's Monday
+    regression: {
+        kind: "linear" | "log";
+        slope_per_week: number; // At the last point for a log fit
+        r_squared: number;
+        line: [string, number][];
+        projection: [string, number] | null;
+    } | null;
+}
+
+export type OneRmFormula = "epley" | "brzycki" | "lombardi" | "mayhew" | "oconner" | "wathan";
+
+// From calculate_rep_maxes
+export interface RepMaxTable {
+    formula: OneRmFormula;
+    one_rep_max: number;
+    rep_maxes: { reps: number; weight: number; percent: number }[]; // 1RM to 15RM
+    percentages: { percent: number; weight: number; reps: number | null }[]; // 100% down to 50%
+}
+
+export type RecordMetric = "weight" | "reps" | "estimated1rm" | "volume" | "duration" | "distance" | "rep_max";
+
+// A record as it stood after one workout. Stored units (kg, km, minutes).
+export interface RecordBreak {
+    metric: RecordMetric;
+    reps: number | null; // Rep count of a rep_max record
+    value: number;
+    workout_id: number;
+    date: string; // Local "YYYY-MM-DD"
+    previous_value: number | null; // null the first time the metric was logged
+    previous_date: string | null;
+}
+
+// From list_personal_records
+export interface ExerciseRecords {
+    exercise: string;
+    records: RecordBreak[];
+}
+
+// From get_pr_timeline
+export interface PrTimeline {
+    exercise: string;
+    breaks: RecordBreak[]; // Oldest first
+}
+
+// From get_streak_info. Dates are local "YYYY-MM-DD".
+export interface StreakInfo {
+    interval_days: number;
+    current_streak: number; // 0 once the streak has lapsed
+    longest_streak: number;
+    streak_start: string | null;
+    last_workout_date: string | null;
+    days_until_break: number | null; // 0 = today is the last chance
+    weeks: number; // This week included
+    weeks_trained: number;
+    weekly_consistency_percent: number;
+}
+
+// From get_activity_heatmap
+export interface ActivityHeatmap {
+    metric: "count" | "workouts" | "sets" | "volume" | "duration" | "distance";
+    max: number; // Highest daily value
+    days: [string, number][]; // Every day of the range, 0 on rest days
+}
+
+// From get_period_summary. Stored units (kg, km, minutes); dates are local "YYYY-MM-DD".
+export interface PeriodTotals {
+    sessions: number; // Days with a workout
+    workouts: number;
+    sets: number;
+    reps: number;
+    volume: number;
+    duration_minutes: number;
+    distance: number;
+}
+
+export interface PeriodSummary {
+    start: string;
+    end: string;
+    totals: PeriodTotals;
+    top_exercises: { exercise: string; workouts: number; sets: number; volume: number }[];
+    top_muscles: MuscleVolume[];
+    personal_records: (RecordBreak & { exercise: string })[]; // Oldest first
+    bodyweight: { start: number | null; end: number | null; change: number | null };
+    streaks: { streak_at_end: number; longest_in_period: number };
+}
+
+// From get_period_comparison. Percent changes are null where the previous total was 0.
+export interface PeriodComparison {
+    current: PeriodSummary;
+    previous: PeriodSummary; // The same number of days right before
+    change_percent: { [K in keyof PeriodTotals | "personal_records"]: number | null };
 }